@@ -0,0 +1,61 @@
+/* C API for nonogram-solver. Build with `cargo build --release -p nonogram-solver-ffi`.
+ * Regenerate from bindings/ffi with `cbindgen --config cbindgen.toml --output include/nonogram_solver.h`. */
+
+#ifndef NONOGRAM_SOLVER_H
+#define NONOGRAM_SOLVER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Opaque handle to parsed puzzle clues. */
+typedef struct NonogramPuzzle NonogramPuzzle;
+
+/* Opaque handle to a finished solve: every propagation step plus stats. */
+typedef struct NonogramSession NonogramSession;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The message of the most recent failure on this thread, or NULL. */
+const char *nonogram_last_error(void);
+
+/* Parse a puzzle from a NUL-terminated JSON string. Returns NULL on error. */
+NonogramPuzzle *nonogram_puzzle_from_json(const char *json);
+
+/* Release a puzzle. Passing NULL is a no-op. */
+void nonogram_puzzle_free(NonogramPuzzle *puzzle);
+
+/* Solve a puzzle, recording every step. Returns NULL if it is unsolvable. */
+NonogramSession *nonogram_session_new(const NonogramPuzzle *puzzle);
+
+/* Release a session. Passing NULL is a no-op. */
+void nonogram_session_free(NonogramSession *session);
+
+/* Grid height in cells. */
+size_t nonogram_session_rows(const NonogramSession *session);
+
+/* Grid width in cells. */
+size_t nonogram_session_cols(const NonogramSession *session);
+
+/* Number of recorded steps; the last one is the final grid. */
+size_t nonogram_session_step_count(const NonogramSession *session);
+
+/* Copy one step's masks, row-major, into `out`. Returns the number of cells;
+ * nothing is written when `out` is NULL, `len` is too small or `step` is out of range. */
+size_t nonogram_session_copy_step(const NonogramSession *session,
+                                  size_t step,
+                                  uint64_t *out,
+                                  size_t len);
+
+/* The whole session as JSON. Free it with `nonogram_string_free`. */
+char *nonogram_session_to_json(const NonogramSession *session);
+
+/* Release a string returned by this library. Passing NULL is a no-op. */
+void nonogram_string_free(char *value);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* NONOGRAM_SOLVER_H */
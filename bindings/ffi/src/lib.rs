@@ -0,0 +1,269 @@
+//! C ABI for embedding the solver in native apps.
+//!
+//! Puzzles go in as the same JSON the web app uses (`color_panel`,
+//! `row_groups`, `col_groups`). Results come out either as JSON or as flat,
+//! row-major `uint64_t` mask arrays. Every handle returned here must be
+//! released with its matching `*_free` function. The C declarations live in
+//! `bindings/ffi/include/nonogram_solver.h`.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+
+use nonogram_solver::prelude::{PuzzleData, SolveSteps, solve_puzzle_steps};
+
+/// Opaque handle to parsed puzzle clues.
+pub struct NonogramPuzzle {
+    data: PuzzleData,
+}
+
+/// Opaque handle to a finished solve: every propagation step plus stats.
+pub struct NonogramSession {
+    steps: SolveSteps,
+    rows: usize,
+    cols: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(message));
+}
+
+/// The message of the most recent failure on this thread, or NULL.
+///
+/// The pointer stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn nonogram_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Parse a puzzle from a NUL-terminated JSON string. Returns NULL on error.
+///
+/// # Safety
+///
+/// `json` must be NULL or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_puzzle_from_json(json: *const c_char) -> *mut NonogramPuzzle {
+    if json.is_null() {
+        set_last_error("puzzle JSON is NULL".to_string());
+        return std::ptr::null_mut();
+    }
+    // SAFETY: the caller guarantees a NUL-terminated string.
+    let json = unsafe { CStr::from_ptr(json) };
+    let Ok(json) = json.to_str() else {
+        set_last_error("puzzle JSON is not UTF-8".to_string());
+        return std::ptr::null_mut();
+    };
+    match serde_json::from_str::<PuzzleData>(json) {
+        Ok(data) => Box::into_raw(Box::new(NonogramPuzzle { data })),
+        Err(err) => {
+            set_last_error(format!("invalid puzzle JSON: {err}"));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Release a puzzle. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `puzzle` must be NULL or a handle from `nonogram_puzzle_from_json` that
+/// has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_puzzle_free(puzzle: *mut NonogramPuzzle) {
+    if !puzzle.is_null() {
+        // SAFETY: the handle came from `Box::into_raw` and is freed only once.
+        drop(unsafe { Box::from_raw(puzzle) });
+    }
+}
+
+/// Solve a puzzle, recording every step. Returns NULL if it is unsolvable.
+///
+/// # Safety
+///
+/// `puzzle` must be a live handle from `nonogram_puzzle_from_json`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_new(
+    puzzle: *const NonogramPuzzle,
+) -> *mut NonogramSession {
+    // SAFETY: the caller guarantees a live handle.
+    let Some(puzzle) = (unsafe { puzzle.as_ref() }) else {
+        set_last_error("puzzle handle is NULL".to_string());
+        return std::ptr::null_mut();
+    };
+    let rows = puzzle.data.row_groups.len();
+    let cols = puzzle.data.col_groups.len();
+    match solve_puzzle_steps(puzzle.data.clone()) {
+        Ok(steps) => Box::into_raw(Box::new(NonogramSession { steps, rows, cols })),
+        Err(err) => {
+            set_last_error(err.to_string());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Release a session. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `session` must be NULL or a handle from `nonogram_session_new` that has
+/// not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_free(session: *mut NonogramSession) {
+    if !session.is_null() {
+        // SAFETY: the handle came from `Box::into_raw` and is freed only once.
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
+
+/// Grid height in cells.
+///
+/// # Safety
+///
+/// `session` must be a live handle from `nonogram_session_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_rows(session: *const NonogramSession) -> usize {
+    // SAFETY: the caller guarantees a live handle.
+    unsafe { session.as_ref() }.map_or(0, |session| session.rows)
+}
+
+/// Grid width in cells.
+///
+/// # Safety
+///
+/// `session` must be a live handle from `nonogram_session_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_cols(session: *const NonogramSession) -> usize {
+    // SAFETY: the caller guarantees a live handle.
+    unsafe { session.as_ref() }.map_or(0, |session| session.cols)
+}
+
+/// Number of recorded steps; the last one is the final grid.
+///
+/// # Safety
+///
+/// `session` must be a live handle from `nonogram_session_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_step_count(session: *const NonogramSession) -> usize {
+    // SAFETY: the caller guarantees a live handle.
+    unsafe { session.as_ref() }.map_or(0, |session| session.steps.steps.len())
+}
+
+/// Copy one step's masks, row-major, into `out`.
+///
+/// Returns the number of cells in the grid. Nothing is written when `out` is
+/// NULL, `len` is too small or `step` is out of range, so callers can pass
+/// NULL first to size their buffer.
+///
+/// # Safety
+///
+/// `session` must be a live handle from `nonogram_session_new`, and `out`
+/// must be NULL or valid for `len` writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_copy_step(
+    session: *const NonogramSession,
+    step: usize,
+    out: *mut u64,
+    len: usize,
+) -> usize {
+    // SAFETY: the caller guarantees a live handle.
+    let Some(session) = (unsafe { session.as_ref() }) else {
+        return 0;
+    };
+    let cells = session.rows * session.cols;
+    let Some(grid) = session.steps.steps.get(step) else {
+        return cells;
+    };
+    if out.is_null() || len < cells {
+        return cells;
+    }
+    // SAFETY: `out` is valid for `len >= cells` writes.
+    let out = unsafe { std::slice::from_raw_parts_mut(out, cells) };
+    for (dst, &mask) in out.iter_mut().zip(grid.iter().flatten()) {
+        *dst = mask;
+    }
+    cells
+}
+
+/// The whole session (`SolveSteps`) as JSON. Free it with `nonogram_string_free`.
+///
+/// # Safety
+///
+/// `session` must be a live handle from `nonogram_session_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_session_to_json(session: *const NonogramSession) -> *mut c_char {
+    // SAFETY: the caller guarantees a live handle.
+    let Some(session) = (unsafe { session.as_ref() }) else {
+        set_last_error("session handle is NULL".to_string());
+        return std::ptr::null_mut();
+    };
+    match serde_json::to_string(&session.steps) {
+        Ok(json) => CString::new(json).map_or(std::ptr::null_mut(), CString::into_raw),
+        Err(err) => {
+            set_last_error(err.to_string());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Release a string returned by this library. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `value` must be NULL or a string returned by `nonogram_session_to_json`
+/// that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nonogram_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        // SAFETY: the string came from `CString::into_raw` and is freed only once.
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CROSS: &CStr = cr##"{
+        "color_panel": ["#ffffff", "#000000"],
+        "row_groups": [[{"len": 1, "color_id": 1}], [{"len": 3, "color_id": 1}], [{"len": 1, "color_id": 1}]],
+        "col_groups": [[{"len": 1, "color_id": 1}], [{"len": 3, "color_id": 1}], [{"len": 1, "color_id": 1}]]
+    }"##;
+
+    #[test]
+    fn solves_through_handles() {
+        unsafe {
+            let puzzle = nonogram_puzzle_from_json(CROSS.as_ptr());
+            assert!(!puzzle.is_null());
+            let session = nonogram_session_new(puzzle);
+            assert!(!session.is_null());
+            let last = nonogram_session_step_count(session) - 1;
+            assert_eq!(
+                nonogram_session_copy_step(session, last, std::ptr::null_mut(), 0),
+                9
+            );
+            let mut grid = [0u64; 9];
+            nonogram_session_copy_step(session, last, grid.as_mut_ptr(), grid.len());
+            assert_eq!(grid, [1, 2, 1, 2, 2, 2, 1, 2, 1]);
+            nonogram_session_free(session);
+            nonogram_puzzle_free(puzzle);
+        }
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        unsafe {
+            let puzzle = nonogram_puzzle_from_json(c"{".as_ptr());
+            assert!(puzzle.is_null());
+            let message = CStr::from_ptr(nonogram_last_error()).to_str().unwrap();
+            assert!(message.starts_with("invalid puzzle JSON"));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! JavaScript bindings for the solver, built with `wasm-pack`.
+//!
+//! Both entry points take a `PuzzleData` JSON document and return JSON, so
+//! callers do not need any generated TypeScript types:
+//!
+//! ```js
+//! import init, { solvePuzzle } from "nonogram-solver";
+//! await init();
+//! const { color_panel, grid } = JSON.parse(solvePuzzle(JSON.stringify(puzzle)));
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use nonogram_solver::prelude::{PuzzleData, solve_puzzle, solve_puzzle_steps};
+
+/// Solve a puzzle and return its `SolvedPuzzle` as JSON.
+#[wasm_bindgen(js_name = solvePuzzle)]
+pub fn solve_puzzle_js(puzzle_json: &str) -> Result<String, JsError> {
+    solve_puzzle_json(puzzle_json).map_err(|err| JsError::new(&err))
+}
+
+/// Solve a puzzle and return every propagation step (`SolveSteps`) as JSON.
+#[wasm_bindgen(js_name = solveSteps)]
+pub fn solve_steps_js(puzzle_json: &str) -> Result<String, JsError> {
+    solve_steps_json(puzzle_json).map_err(|err| JsError::new(&err))
+}
+
+/// The JSON round trip behind `solvePuzzle`, usable without a JS runtime.
+pub fn solve_puzzle_json(puzzle_json: &str) -> Result<String, String> {
+    let data = parse_data(puzzle_json)?;
+    let solved = solve_puzzle(data).map_err(|err| err.to_string())?;
+    serde_json::to_string(&solved).map_err(|err| err.to_string())
+}
+
+/// The JSON round trip behind `solveSteps`, usable without a JS runtime.
+pub fn solve_steps_json(puzzle_json: &str) -> Result<String, String> {
+    let data = parse_data(puzzle_json)?;
+    let steps = solve_puzzle_steps(data).map_err(|err| err.to_string())?;
+    serde_json::to_string(&steps).map_err(|err| err.to_string())
+}
+
+fn parse_data(puzzle_json: &str) -> Result<PuzzleData, String> {
+    serde_json::from_str(puzzle_json).map_err(|err| format!("invalid puzzle JSON: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CROSS: &str = r##"{
+        "color_panel": ["#ffffff", "#000000"],
+        "row_groups": [[{"len": 1, "color_id": 1}], [{"len": 3, "color_id": 1}], [{"len": 1, "color_id": 1}]],
+        "col_groups": [[{"len": 1, "color_id": 1}], [{"len": 3, "color_id": 1}], [{"len": 1, "color_id": 1}]]
+    }"##;
+
+    #[test]
+    fn solves_puzzle_from_json() {
+        let solved: serde_json::Value =
+            serde_json::from_str(&solve_puzzle_json(CROSS).unwrap()).unwrap();
+        assert_eq!(
+            solved["grid"],
+            serde_json::json!([[1, 2, 1], [2, 2, 2], [1, 2, 1]])
+        );
+    }
+
+    #[test]
+    fn reports_malformed_json() {
+        let err = solve_steps_json("{").unwrap_err();
+        assert!(err.starts_with("invalid puzzle JSON"));
+    }
+}
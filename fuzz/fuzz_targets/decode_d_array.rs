@@ -0,0 +1,22 @@
+//! Structured `var d=[...]` arrays, so mutations reach the decoder's arithmetic
+//! instead of stopping at the array parser.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nonogram_solver::puzzle_crawler::{PuzzleKind, parse_puzzle};
+
+fuzz_target!(|input: (bool, Vec<[i64; 4]>)| {
+    let (black_white, entries) = input;
+    let kind = if black_white {
+        PuzzleKind::BlackWhite
+    } else {
+        PuzzleKind::Color
+    };
+    let body: Vec<String> = entries
+        .iter()
+        .map(|entry| format!("[{},{},{},{}]", entry[0], entry[1], entry[2], entry[3]))
+        .collect();
+    let html = format!("var d=[{}];", body.join(","));
+    let _ = parse_puzzle(kind, &html);
+});
@@ -0,0 +1,14 @@
+//! Raw page bytes, as a pasted or downloaded page would arrive.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nonogram_solver::puzzle_crawler::{PuzzleKind, parse_puzzle, parse_puzzle_meta};
+
+fuzz_target!(|data: &[u8]| {
+    let html = String::from_utf8_lossy(data);
+    for kind in [PuzzleKind::Color, PuzzleKind::BlackWhite] {
+        let _ = parse_puzzle(kind, &html);
+        let _ = parse_puzzle_meta(kind, "0", &html);
+    }
+});
@@ -0,0 +1,63 @@
+// Offline support: keeps the app shell, the WASM solver and the other static
+// files in a cache so the app starts without a connection. Puzzles are not
+// cached here; the app keeps fetched puzzles in IndexedDB itself (see
+// src/offline.rs). Bump CACHE to drop everything cached by an older version.
+const CACHE = "nonogram-solver-v1";
+const SHELL = ["/", "/manifest.webmanifest", "/icon.svg"];
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(SHELL)));
+  self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+      .then(() => self.clients.claim()),
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  // Server functions and other sites always go to the network.
+  if (request.method !== "GET" || url.origin !== self.location.origin || url.pathname.startsWith("/api/")) {
+    return;
+  }
+  if (request.mode === "navigate") {
+    // Pages: the network first, so a deploy shows up at once; offline, the
+    // cached shell, which loads any puzzle from the app's own storage.
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          if (response.ok) {
+            const copy = response.clone();
+            caches.open(CACHE).then((cache) => cache.put("/", copy));
+          }
+          return response;
+        })
+        .catch(() => caches.match("/")),
+    );
+    return;
+  }
+  // Scripts, WASM, styles: from the cache, refreshed in the background.
+  event.respondWith(
+    caches.open(CACHE).then((cache) =>
+      cache.match(request).then((cached) => {
+        const refresh = fetch(request).then((response) => {
+          if (response.ok) {
+            cache.put(request, response.clone());
+          }
+          return response;
+        });
+        if (cached) {
+          refresh.catch(() => {});
+          return cached;
+        }
+        return refresh;
+      }),
+    ),
+  );
+});
@@ -0,0 +1,212 @@
+//! Command-line tools for batch jobs. For now just one:
+//!
+//! ```text
+//! nonogram booklet --ids ids.txt --out booklet.html [--title "Club night"]
+//! ```
+//!
+//! fetches every puzzle listed in `ids.txt` (one `56215` or `bw:1822` per
+//! line; blank lines and `#` comments are skipped), solves it, and writes a
+//! printable booklet of blank sheets with an answers appendix. The booklet
+//! is HTML: print it from a browser, or save it as PDF from there.
+//!
+//! Fetches use the crawler's strict politeness, so robots.txt is read first
+//! and pages are at least five seconds apart. As for the server,
+//! `NONOGRAM_CRAWLER_CONTACT` goes in the `User-Agent` and
+//! `NONOGRAM_FETCH_PROXY` sends fetches through a proxy.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use nonogram_solver::booklet::{BookletPuzzle, render_booklet};
+use nonogram_solver::crawl_policy::{PoliteCrawler, Politeness};
+use nonogram_solver::nonogram_solver::{SolverOptions, solve_puzzle_steps_with};
+use nonogram_solver::puzzle_crawler::{
+    HtmlFetcher, PuzzleKind, PuzzleRef, parse_puzzle, parse_puzzle_meta,
+};
+
+const USAGE: &str = "usage: nonogram booklet --ids <file> --out <file.html> [--title <text>]";
+
+#[derive(Debug, PartialEq)]
+struct BookletArgs {
+    ids: PathBuf,
+    out: PathBuf,
+    title: String,
+}
+
+fn parse_args(args: &[String]) -> Result<BookletArgs, String> {
+    let [command, rest @ ..] = args else {
+        return Err(USAGE.to_string());
+    };
+    if command != "booklet" {
+        return Err(format!("unknown command `{command}`\n{USAGE}"));
+    }
+    let (mut ids, mut out, mut title) = (None, None, None);
+    let mut rest = rest.iter();
+    while let Some(flag) = rest.next() {
+        let slot = match flag.as_str() {
+            "--ids" => &mut ids,
+            "--out" => &mut out,
+            "--title" => &mut title,
+            _ => return Err(format!("unknown option `{flag}`\n{USAGE}")),
+        };
+        let value = rest
+            .next()
+            .ok_or_else(|| format!("`{flag}` needs a value"))?;
+        *slot = Some(value.clone());
+    }
+    let (Some(ids), Some(out)) = (ids, out) else {
+        return Err(USAGE.to_string());
+    };
+    let out = PathBuf::from(out);
+    if out
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    {
+        return Err(
+            "the booklet is HTML; write it to a .html file and print it to PDF from a browser"
+                .to_string(),
+        );
+    }
+    Ok(BookletArgs {
+        ids: ids.into(),
+        out,
+        title: title.unwrap_or_else(|| "Nonogram booklet".to_string()),
+    })
+}
+
+/// The puzzles listed in an ids file, or the first line that is not one.
+fn parse_ids(text: &str) -> Result<Vec<PuzzleRef>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| {
+            let (kind, id) = match line.strip_prefix("bw:") {
+                Some(id) => (PuzzleKind::BlackWhite, id),
+                None => (PuzzleKind::Color, line),
+            };
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("line {}: `{line}` is not a puzzle id", idx + 1));
+            }
+            Ok(PuzzleRef::nonograms_org(kind, id))
+        })
+        .collect()
+}
+
+async fn booklet_puzzle(
+    crawler: &PoliteCrawler,
+    puzzle: &PuzzleRef,
+) -> Result<BookletPuzzle, String> {
+    let html = crawler
+        .fetch_puzzle_page(puzzle)
+        .await
+        .map_err(|err| err.to_string())?;
+    let data = parse_puzzle(puzzle.kind, &html).map_err(|err| err.to_string())?;
+    let meta = parse_puzzle_meta(puzzle.kind, &puzzle.id, &html);
+    let solved = solve_puzzle_steps_with(data.clone(), &SolverOptions::default())
+        .map_err(|err| err.to_string())?;
+    if !solved.stats.solved {
+        eprintln!(
+            "{}: line logic stalls; its answer shows open cells",
+            puzzle.id
+        );
+    }
+    Ok(BookletPuzzle {
+        title: meta
+            .title
+            .unwrap_or_else(|| format!("Nonogram {}", puzzle.id)),
+        data,
+        answer: solved.steps.last().cloned().unwrap_or_default(),
+    })
+}
+
+fn run(args: BookletArgs) -> Result<(), String> {
+    let ids = std::fs::read_to_string(&args.ids)
+        .map_err(|err| format!("cannot read {}: {err}", args.ids.display()))?;
+    let puzzles = parse_ids(&ids)?;
+    let mut crawler = PoliteCrawler::new(Duration::ZERO).with_politeness(Politeness::Strict);
+    if let Ok(proxy) = std::env::var("NONOGRAM_FETCH_PROXY") {
+        crawler = crawler.with_fetcher(HtmlFetcher::via_proxy(proxy));
+    }
+    if let Ok(contact) = std::env::var("NONOGRAM_CRAWLER_CONTACT") {
+        crawler = crawler.with_contact(&contact);
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    let mut sheets = Vec::with_capacity(puzzles.len());
+    for puzzle in &puzzles {
+        eprintln!("fetching {}", puzzle.id);
+        let sheet = runtime
+            .block_on(booklet_puzzle(&crawler, puzzle))
+            .map_err(|err| format!("{}: {err}", puzzle.id))?;
+        sheets.push(sheet);
+    }
+    std::fs::write(&args.out, render_booklet(&args.title, &sheets))
+        .map_err(|err| format!("cannot write {}: {err}", args.out.display()))?;
+    let puzzles = if sheets.len() == 1 {
+        "puzzle"
+    } else {
+        "puzzles"
+    };
+    eprintln!("wrote {} {puzzles} to {}", sheets.len(), args.out.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn reads_booklet_options_and_ids() {
+        assert_eq!(
+            parse_args(&args("booklet --out b.html --ids ids.txt")),
+            Ok(BookletArgs {
+                ids: "ids.txt".into(),
+                out: "b.html".into(),
+                title: "Nonogram booklet".to_string(),
+            })
+        );
+        assert!(parse_args(&args("booklet --ids ids.txt")).is_err());
+        assert!(parse_args(&args("booklet --ids ids.txt --out b.PDF")).is_err());
+        assert!(parse_args(&args("sheet --ids ids.txt --out b.html")).is_err());
+
+        let ids = parse_ids("# club night\n56215\n\nbw:1822\n").unwrap();
+        assert_eq!(
+            ids,
+            [
+                PuzzleRef::nonograms_org(PuzzleKind::Color, "56215"),
+                PuzzleRef::nonograms_org(PuzzleKind::BlackWhite, "1822"),
+            ]
+        );
+        assert_eq!(
+            parse_ids("56215\nbw:\n"),
+            Err("line 2: `bw:` is not a puzzle id".to_string())
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Many puzzles as one printable HTML booklet, for events where players
+//! solve on paper: a blank sheet per puzzle, then an answers appendix.
+//!
+//! Each sheet and the appendix start on a new page when printed, so the
+//! browser's "Save as PDF" gives the booklet as a PDF. Sheets use the same
+//! clue tables as [`crate::report`].
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::nonogram_solver::Grid;
+use crate::puzzle_crawler::PuzzleData;
+use crate::report::{STYLE, cell_color, palette_colors, push_table};
+
+const BOOKLET_STYLE: &str = "section{break-after:page}\
+td.c{background:#fff}\
+.answers table{display:inline-table;margin:0 2em 2em 0;vertical-align:top}\
+.answers td{width:.6em;height:.6em}\
+.answers td.t,.answers td.r{display:none}\
+@media print{body{margin:0}}";
+
+/// One puzzle of a booklet. `answer` is its solved grid, or the last step of
+/// a solve that stalled; cells still open there print gray.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookletPuzzle {
+    pub title: String,
+    pub data: PuzzleData,
+    pub answer: Grid,
+}
+
+/// Render `puzzles` as one HTML document: a numbered sheet for each, then
+/// every answer under the same number.
+pub fn render_booklet(title: &str, puzzles: &[BookletPuzzle]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(title));
+    let _ = writeln!(
+        html,
+        "<style>{STYLE}{BOOKLET_STYLE}</style>\n</head>\n<body>"
+    );
+
+    for (idx, puzzle) in puzzles.iter().enumerate() {
+        let palette = palette_colors(&puzzle.data);
+        let cols = puzzle.data.col_groups.len();
+        let rows = puzzle.data.row_groups.len();
+        html.push_str("<section>\n");
+        let _ = writeln!(
+            html,
+            "<h2>{}. {} ({cols}x{rows})</h2>",
+            idx + 1,
+            escape(&puzzle.title)
+        );
+        push_table(
+            &mut html,
+            "class=\"sheet\"",
+            &puzzle.data,
+            &palette,
+            |_, _| None,
+        );
+        html.push_str("</section>\n");
+    }
+
+    if !puzzles.is_empty() {
+        html.push_str("<section class=\"answers\">\n<h2>Answers</h2>\n");
+        for (idx, puzzle) in puzzles.iter().enumerate() {
+            let palette = palette_colors(&puzzle.data);
+            let _ = writeln!(html, "<h3>{}. {}</h3>", idx + 1, escape(&puzzle.title));
+            push_table(
+                &mut html,
+                "class=\"answer\"",
+                &puzzle.data,
+                &palette,
+                |row, col| {
+                    let mask = puzzle.answer.get(row).and_then(|line| line.get(col));
+                    Some(cell_color(mask.copied(), &palette))
+                },
+            );
+        }
+        html.push_str("</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn prints_blank_sheets_then_answers() {
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000"])
+            .row([(1, 1)])
+            .row([(2, 1)])
+            .col([(2, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let answer = solve_puzzle(data.clone()).unwrap().grid;
+        let puzzles: Vec<BookletPuzzle> = ["Hook", "Fish & <chips>"]
+            .into_iter()
+            .map(|title| BookletPuzzle {
+                title: title.to_string(),
+                data: data.clone(),
+                answer: answer.clone(),
+            })
+            .collect();
+        let html = render_booklet("Club night", &puzzles);
+
+        assert!(html.contains("<title>Club night</title>"));
+        assert!(html.contains("<h2>2. Fish &amp; &lt;chips&gt; (2x2)</h2>"));
+        let (sheets, answers) = html.split_once("<h2>Answers</h2>").unwrap();
+        assert_eq!(sheets.matches("<td class=\"c\"></td>").count(), 8);
+        assert!(!sheets.contains("style=\"background:#ff0000\"></td>"));
+        assert_eq!(
+            answers
+                .matches("style=\"background:#ff0000\"></td>")
+                .count(),
+            6
+        );
+        assert!(!render_booklet("Empty", &[]).contains("Answers"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Read plain-text clue lists, as copied out of mobile nonogram apps.
+//!
+//! ```text
+//! rows:
+//! 1
+//! 2
+//! cols:
+//! 2
+//! 1
+//! ```
+//!
+//! The parser is lenient about everything but the numbers:
+//!
+//! - Sections start with `rows:` (or `row:`, `left:`) and `cols:` (or
+//!   `columns:`, `col:`, `top:`), in any case and in either order.
+//! - Clues are separated by newlines, `;`, `|` or `/`, so a list squeezed
+//!   onto one line (`rows: 1; 2 cols: 2; 1`) still works. Blank clues are
+//!   skipped; write `0` or `-` for an empty line.
+//! - Groups are separated by commas or spaces. A letter before or after the
+//!   number (`3r`, `r3`) names its color; unlettered groups are black.
+//! - A blotted length is written `?`, a bounded one `2-4`.
+//!
+//! Letters map to fixed display colors (`r` red, `b` blue, `k` black, ...)
+//! and ids are assigned in order of first use.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::puzzle_builder::ValidationError;
+use crate::puzzle_crawler::{Group, GroupLen, PuzzleData};
+
+/// Section headers and whether they start the row clues.
+const HEADERS: [(&str, bool); 7] = [
+    ("rows", true),
+    ("row", true),
+    ("left", true),
+    ("columns", false),
+    ("cols", false),
+    ("col", false),
+    ("top", false),
+];
+
+/// Display colors for color letters; letters not listed here cycle through
+/// `SPARE_INKS`.
+const LETTER_INKS: [(char, &str); 12] = [
+    ('k', "#000000"),
+    ('r', "#d62728"),
+    ('g', "#2ca02c"),
+    ('b', "#1f77b4"),
+    ('y', "#e6c619"),
+    ('o', "#ff7f0e"),
+    ('p', "#9467bd"),
+    ('n', "#8c564b"),
+    ('c', "#17becf"),
+    ('m', "#e377c2"),
+    ('a', "#7f7f7f"),
+    ('w', "#c7c7c7"),
+];
+const SPARE_INKS: [&str; 4] = ["#bcbd22", "#393b79", "#637939", "#843c39"];
+
+/// Why a clue list could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueTextError {
+    /// The text has neither a `rows:` nor a `cols:` header, so it is
+    /// probably not a clue list at all.
+    NoSections,
+    /// Only one of the two sections is present; names the missing one.
+    MissingSection(&'static str),
+    /// A group that is not a length with an optional color letter.
+    BadGroup(String),
+    /// The clues parsed but do not describe a solvable-shaped puzzle.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for ClueTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSections => write!(f, "no rows: or cols: section"),
+            Self::MissingSection(name) => write!(f, "missing the {name}: section"),
+            Self::BadGroup(token) => write!(f, "\"{token}\" is not a clue number"),
+            Self::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for ClueTextError {}
+
+/// Parse a clue list into a validated puzzle.
+pub fn parse_clue_text(text: &str) -> Result<PuzzleData, ClueTextError> {
+    let mut rows = None;
+    let mut cols = None;
+    for (is_rows, body) in sections(text) {
+        let clues = parse_section(body)?;
+        if is_rows {
+            rows = Some(clues);
+        } else {
+            cols = Some(clues);
+        }
+    }
+    let (rows, cols) = match (rows, cols) {
+        (None, None) => return Err(ClueTextError::NoSections),
+        (None, Some(_)) => return Err(ClueTextError::MissingSection("rows")),
+        (Some(_), None) => return Err(ClueTextError::MissingSection("cols")),
+        (Some(rows), Some(cols)) => (rows, cols),
+    };
+
+    // Color ids in order of first use; `None` is an unlettered (black) group.
+    let mut labels: Vec<Option<char>> = Vec::new();
+    let mut to_group = |(len, label): (GroupLen, Option<char>)| {
+        let idx = labels.iter().position(|&l| l == label).unwrap_or_else(|| {
+            labels.push(label);
+            labels.len() - 1
+        });
+        Group {
+            len,
+            color_id: idx + 1,
+        }
+    };
+    let mut builder = PuzzleData::builder();
+    for clue in rows {
+        builder = builder.row(clue.into_iter().map(&mut to_group).collect::<Vec<_>>());
+    }
+    for clue in cols {
+        builder = builder.col(clue.into_iter().map(&mut to_group).collect::<Vec<_>>());
+    }
+    let mut spare = SPARE_INKS.iter().cycle();
+    let inks = labels.iter().map(|label| match label {
+        None => "#000000",
+        Some(letter) => LETTER_INKS
+            .iter()
+            .find(|(l, _)| l == letter)
+            .map_or_else(|| *spare.next().unwrap_or(&"#000000"), |(_, ink)| *ink),
+    });
+    builder
+        .palette(core::iter::once("#ffffff").chain(inks))
+        .build()
+        .map_err(ClueTextError::Invalid)
+}
+
+type Clue = Vec<(GroupLen, Option<char>)>;
+
+/// `(is_rows, body)` for each header in `text`, in order.
+fn sections(text: &str) -> Vec<(bool, &str)> {
+    let lower = text.to_ascii_lowercase();
+    let mut found: Vec<(usize, usize, bool)> = Vec::new();
+    let mut idx = 0;
+    while idx < lower.len() {
+        let at_word_start = !lower[..idx]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphabetic());
+        let header = HEADERS.iter().find_map(|&(name, is_rows)| {
+            let rest = lower[idx..].strip_prefix(name)?;
+            let after = rest.trim_start_matches([' ', '\t']).strip_prefix(':')?;
+            Some((lower.len() - after.len(), is_rows))
+        });
+        match header {
+            Some((body_start, is_rows)) if at_word_start => {
+                found.push((idx, body_start, is_rows));
+                idx = body_start;
+            }
+            _ => idx += lower[idx..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    found
+        .iter()
+        .enumerate()
+        .map(|(n, &(_, body_start, is_rows))| {
+            let end = found.get(n + 1).map_or(text.len(), |next| next.0);
+            (is_rows, &text[body_start..end])
+        })
+        .collect()
+}
+
+fn parse_section(body: &str) -> Result<Vec<Clue>, ClueTextError> {
+    body.split(['\n', ';', '|', '/'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_clue)
+        .collect()
+}
+
+fn parse_clue(line: &str) -> Result<Clue, ClueTextError> {
+    let mut clue = Vec::new();
+    for token in line.split([',', ' ', '\t', '\r']).filter(|t| !t.is_empty()) {
+        if token == "-" {
+            continue;
+        }
+        let bad = || ClueTextError::BadGroup(token.into());
+        let lower = token.to_ascii_lowercase();
+        let body = lower.trim_matches(|c: char| c.is_ascii_lowercase());
+        let letters: Vec<char> = lower.chars().filter(char::is_ascii_lowercase).collect();
+        if letters.len() > 1 {
+            return Err(bad());
+        }
+        let number = |digits: &str| -> Result<usize, ClueTextError> {
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(bad());
+            }
+            digits.parse().map_err(|_| bad())
+        };
+        let len = match body.split_once('-') {
+            _ if body == "?" => GroupLen::Unknown,
+            Some((min, max)) => GroupLen::Range(number(min)?, number(max)?),
+            None => GroupLen::Exact(number(body)?),
+        };
+        if len.max() != Some(0) {
+            clue.push((len, letters.first().copied()));
+        }
+    }
+    Ok(clue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn lens(groups: &[Vec<Group>]) -> Vec<Vec<(usize, usize)>> {
+        groups
+            .iter()
+            .map(|clue| clue.iter().map(|g| (g.len.min(), g.color_id)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn reads_multiline_and_single_line_lists() {
+        let multiline = "Rows:\n1\n2\n\nColumns:\n2\n1\n";
+        let single = "rows: 1; 2 cols: 2; 1";
+        for text in [multiline, single] {
+            let data = parse_clue_text(text).unwrap();
+            assert_eq!(data.color_panel, vec!["#ffffff", "#000000"]);
+            assert_eq!(lens(&data.row_groups), vec![vec![(1, 1)], vec![(2, 1)]]);
+            assert_eq!(lens(&data.col_groups), vec![vec![(2, 1)], vec![(1, 1)]]);
+        }
+    }
+
+    #[test]
+    fn assigns_color_letters_in_order_of_use() {
+        let data = parse_clue_text("top: 1r | 1b\nleft: r1, b1 | 0").unwrap();
+        assert_eq!(data.color_panel, vec!["#ffffff", "#d62728", "#1f77b4"]);
+        assert_eq!(lens(&data.row_groups), vec![vec![(1, 1), (1, 2)], vec![]]);
+        assert_eq!(lens(&data.col_groups), vec![vec![(1, 1)], vec![(1, 2)]]);
+    }
+
+    #[test]
+    fn reads_blotted_and_bounded_lengths() {
+        let data = parse_clue_text("rows: ?; 1-2 cols: 1; 2").unwrap();
+        assert_eq!(data.row_groups[0][0].len, GroupLen::Unknown);
+        assert_eq!(data.row_groups[1][0].len, GroupLen::Range(1, 2));
+        assert_eq!(data.col_groups[1][0].len, GroupLen::Exact(2));
+    }
+
+    #[test]
+    fn explains_what_is_wrong() {
+        assert_eq!(
+            parse_clue_text("https://example.com/puzzle"),
+            Err(ClueTextError::NoSections)
+        );
+        assert_eq!(
+            parse_clue_text("rows: 1"),
+            Err(ClueTextError::MissingSection("cols"))
+        );
+        assert_eq!(
+            parse_clue_text("rows: 1x2 cols: 1")
+                .unwrap_err()
+                .to_string(),
+            "\"1x2\" is not a clue number"
+        );
+        assert!(matches!(
+            parse_clue_text("rows: 3 cols: 1"),
+            Err(ClueTextError::Invalid(ValidationError::LineTooShort { .. }))
+        ));
+    }
+}
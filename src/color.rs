@@ -0,0 +1,302 @@
+//! Palette colors: parse `#rgb` / `#rrggbb`, normalize to lowercase
+//! `#rrggbb`, pick readable text on top of a swatch, spot palette entries
+//! that are too close to tell apart, and repaint a palette for display.
+//!
+//! ```
+//! use nonogram_solver::prelude::*;
+//!
+//! let navy: Color = " #00F ".parse().unwrap();
+//! assert_eq!(navy.to_string(), "#0000ff");
+//! assert!(navy.is_dark());
+//! assert!("blue".parse::<Color>().is_err());
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// CIE76 distance below which two colors are hard to tell apart (the usual
+/// "just noticeable difference").
+pub const JUST_NOTICEABLE_DELTA_E: f64 = 2.3;
+
+/// An opaque sRGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color([u8; 3]);
+
+/// Why a string is not a `#rgb` / `#rrggbb` color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorError {
+    /// The value does not start with `#`.
+    MissingHash,
+    /// Three or six hex digits are expected; this many were given.
+    BadLength(usize),
+    /// A character after `#` is not a hex digit.
+    BadDigit(char),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHash => write!(f, "color must start with '#'"),
+            Self::BadLength(len) => {
+                write!(f, "color needs 3 or 6 hex digits, got {len}")
+            }
+            Self::BadDigit(ch) => write!(f, "{ch:?} is not a hex digit"),
+        }
+    }
+}
+
+impl core::error::Error for ColorError {}
+
+impl Color {
+    pub const WHITE: Self = Self([0xff, 0xff, 0xff]);
+    pub const BLACK: Self = Self([0, 0, 0]);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b])
+    }
+
+    pub const fn channels(self) -> [u8; 3] {
+        self.0
+    }
+
+    /// Perceived brightness (Rec. 601 luma), 0.0 (black) to 255.0 (white).
+    pub fn luma(self) -> f64 {
+        let [r, g, b] = self.0.map(f64::from);
+        0.299 * r + 0.587 * g + 0.114 * b
+    }
+
+    /// Whether white text reads better than black on this color.
+    pub fn is_dark(self) -> bool {
+        self.luma() < 140.0
+    }
+
+    /// Black or white, whichever contrasts more with this color.
+    pub fn text_color(self) -> Self {
+        if self.is_dark() {
+            Self::WHITE
+        } else {
+            Self::BLACK
+        }
+    }
+
+    /// CIE76 color difference (ΔE*ab) under D65: about 100 between black and
+    /// white, under [`JUST_NOTICEABLE_DELTA_E`] for colors that look the same.
+    pub fn delta_e(self, other: Self) -> f64 {
+        let [l1, a1, b1] = self.lab();
+        let [l2, a2, b2] = other.lab();
+        let (dl, da, db) = (l1 - l2, a1 - a2, b1 - b2);
+        root(dl * dl + da * da + db * db, 2)
+    }
+
+    /// CIELAB coordinates, D65 white point.
+    fn lab(self) -> [f64; 3] {
+        let [r, g, b] = self.0.map(|channel| {
+            let c = f64::from(channel) / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                // ((c + 0.055) / 1.055)^2.4, as x^2 * (x^2)^(1/5).
+                let x = (c + 0.055) / 1.055;
+                x * x * root(x * x, 5)
+            }
+        });
+        let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.950_47;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.088_83;
+        let f = |t: f64| {
+            const EPSILON: f64 = 216.0 / 24_389.0;
+            if t > EPSILON {
+                root(t, 3)
+            } else {
+                t * 841.0 / 108.0 + 4.0 / 29.0
+            }
+        };
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+}
+
+/// `n`th root of a non-negative `x` by Newton's method; `core` has no `powf`.
+fn root(x: f64, n: u32) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // Starting above the root keeps every step above it, so this converges
+    // monotonically.
+    let mut y = x.max(1.0);
+    for _ in 0..64 {
+        let power = (1..n).fold(1.0, |acc, _| acc * y);
+        let next = y - (power * y - x) / (f64::from(n) * power);
+        if next >= y {
+            break;
+        }
+        y = next;
+    }
+    y
+}
+
+/// Two palette entries closer than the threshold they were checked against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearDuplicate {
+    pub first: usize,
+    pub second: usize,
+    pub delta_e: f64,
+}
+
+/// Every pair of palette entries (by index, `first < second`) whose colors
+/// differ by less than `threshold`.
+///
+/// Near-duplicates are still distinct clue colors; this only tells a viewer
+/// that it cannot rely on them looking different.
+pub fn near_duplicates(palette: &[Color], threshold: f64) -> Vec<NearDuplicate> {
+    let mut pairs = Vec::new();
+    for (first, &a) in palette.iter().enumerate() {
+        for (second, &b) in palette.iter().enumerate().skip(first + 1) {
+            let delta_e = a.delta_e(b);
+            if delta_e < threshold {
+                pairs.push(NearDuplicate {
+                    first,
+                    second,
+                    delta_e,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// For each palette index, the index whose color to draw it with: the
+/// earliest entry closer than `threshold`, or itself.
+///
+/// Only for rendering (e.g. a legend that should not show two swatches that
+/// look the same); solving must keep using the original ids.
+pub fn display_palette(palette: &[Color], threshold: f64) -> Vec<usize> {
+    let mut shown: Vec<usize> = Vec::with_capacity(palette.len());
+    for (idx, &color) in palette.iter().enumerate() {
+        let merged = shown
+            .iter()
+            .enumerate()
+            .find(|&(other, &rep)| rep == other && palette[other].delta_e(color) < threshold)
+            .map_or(idx, |(other, _)| other);
+        shown.push(merged);
+    }
+    shown
+}
+
+/// `palette` with each `(idx, color)` of `overrides` drawn in `color`
+/// instead; entries past the palette are ignored.
+///
+/// Ids keep their meaning, so the result can stand in for a puzzle's
+/// `color_panel` wherever it is drawn (grid, report, thumbnail, card) while
+/// the solve keeps the site's colors.
+pub fn repaint(palette: &[String], overrides: &[(usize, Color)]) -> Vec<String> {
+    let mut shown = palette.to_vec();
+    for &(idx, color) in overrides {
+        if let Some(entry) = shown.get_mut(idx) {
+            *entry = color.to_string();
+        }
+    }
+    shown
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    /// Accepts `#rgb` or `#rrggbb` in either case, ignoring surrounding
+    /// whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.trim().strip_prefix('#').ok_or(ColorError::MissingHash)?;
+        let mut values = [0u8; 6];
+        let mut len = 0;
+        for ch in digits.chars() {
+            let value = ch.to_digit(16).ok_or(ColorError::BadDigit(ch))?;
+            if let Some(slot) = values.get_mut(len) {
+                *slot = value as u8;
+            }
+            len += 1;
+        }
+        match len {
+            3 => Ok(Self(
+                [values[0], values[1], values[2]].map(|nibble| nibble * 0x11),
+            )),
+            6 => Ok(Self([
+                values[0] << 4 | values[1],
+                values[2] << 4 | values[3],
+                values[4] << 4 | values[5],
+            ])),
+            _ => Err(ColorError::BadLength(len)),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    /// Lowercase `#rrggbb`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_normalizes_hex() {
+        assert_eq!("#FFF".parse(), Ok(Color::WHITE));
+        assert_eq!("#1a2B3c".parse::<Color>().unwrap().to_string(), "#1a2b3c");
+        assert_eq!(" #abc\n".parse(), Ok(Color::rgb(0xaa, 0xbb, 0xcc)));
+        assert_eq!("123456".parse::<Color>(), Err(ColorError::MissingHash));
+        assert_eq!("#12345".parse::<Color>(), Err(ColorError::BadLength(5)));
+        assert_eq!("#1234567".parse::<Color>(), Err(ColorError::BadLength(7)));
+        assert_eq!("#12g".parse::<Color>(), Err(ColorError::BadDigit('g')));
+        assert_eq!("#".parse::<Color>(), Err(ColorError::BadLength(0)));
+    }
+
+    #[test]
+    fn luma_picks_readable_text() {
+        assert_eq!(Color::BLACK.luma(), 0.0);
+        assert!((Color::WHITE.luma() - 255.0).abs() < 1e-9);
+        assert_eq!(Color::rgb(0, 0, 0x80).text_color(), Color::WHITE);
+        assert_eq!(Color::rgb(0xff, 0xff, 0).text_color(), Color::BLACK);
+        assert_eq!(Color::rgb(0xcc, 0, 0).text_color(), Color::WHITE);
+    }
+
+    #[test]
+    fn finds_near_duplicate_palette_colors() {
+        assert!((Color::BLACK.delta_e(Color::WHITE) - 100.0).abs() < 0.01);
+        assert!(Color::rgb(0xff, 0, 0).delta_e(Color::rgb(0xfe, 0, 1)) < 1.0);
+
+        let palette = [
+            Color::WHITE,
+            Color::rgb(0xc0, 0x10, 0x10),
+            Color::rgb(0x10, 0x10, 0xc0),
+            Color::rgb(0xc1, 0x11, 0x10),
+        ];
+        let pairs = near_duplicates(&palette, JUST_NOTICEABLE_DELTA_E);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].first, pairs[0].second), (1, 3));
+        assert_eq!(
+            display_palette(&palette, JUST_NOTICEABLE_DELTA_E),
+            [0, 1, 2, 1]
+        );
+    }
+
+    #[test]
+    fn repaints_entries_in_place() {
+        let palette = [
+            "#FFF".to_string(),
+            "#808000".to_string(),
+            "#800000".to_string(),
+        ];
+        assert_eq!(
+            repaint(
+                &palette,
+                &[(1, Color::rgb(0xff, 0xd7, 0)), (7, Color::BLACK)]
+            ),
+            ["#FFF", "#ffd700", "#800000"]
+        );
+    }
+}
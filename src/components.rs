@@ -1,3 +1,14 @@
+pub mod daily_card;
+pub mod error_screen;
+pub mod favorites_page;
 pub mod github_corner;
+pub mod line_inspector;
+pub mod palette_editor;
 pub mod puzzle_grid;
+pub mod puzzle_session;
+pub mod puzzle_sheet;
 pub mod puzzle_viewer;
+pub mod solver_settings;
+pub mod stats_panel;
+pub mod step_minimap;
+pub mod theme_toggle;
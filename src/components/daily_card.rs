@@ -0,0 +1,35 @@
+use dioxus::prelude::*;
+
+use crate::daily::daily_puzzle;
+use crate::i18n::{Msg, use_locale};
+
+/// Landing card for the puzzle of the day; hidden until the server has one.
+#[component]
+pub fn DailyCard(on_open: EventHandler<String>) -> Element {
+    let locale = use_locale();
+    let daily = use_resource(daily_puzzle);
+
+    let Some(Ok(Some(pick))) = daily() else {
+        return rsx! {};
+    };
+    let title = pick
+        .meta
+        .title
+        .clone()
+        .unwrap_or_else(|| locale.format(Msg::PuzzleFallbackTitle, &[&pick.meta.id]));
+    let input = pick.input.clone();
+
+    rsx! {
+        div { class: "daily-card",
+            div { class: "daily-heading", {locale.format(Msg::DailyHeading, &[&pick.date])} }
+            div { class: "daily-title", "{title}" }
+            div { class: "daily-size", "{pick.cols} × {pick.rows}" }
+            button {
+                class: "grid-toggle",
+                r#type: "button",
+                onclick: move |_| on_open.call(input.clone()),
+                {locale.text(Msg::DailyOpen)}
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use crate::local_store;
+
+/// Why a session has no puzzle to show; translated when rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    InvalidInput,
+    /// The input looked like a pasted clue list but did not parse.
+    BadClues(String),
+    Network(String),
+    NotFound,
+    Parse {
+        detail: String,
+        snapshot: String,
+        /// Diagnostic bundle id, shown so a report can name it.
+        bundle: String,
+    },
+    Solver(String),
+    /// The puzzle or its solve ran past the web app's `SolverOptions` limits.
+    TooLarge(String),
+    /// The server is pacing requests; retry after this many seconds.
+    Busy(u64),
+    /// The server function itself failed (server down, bad response).
+    Server(String),
+    /// No connection and no saved copy; the puzzle is queued for later.
+    Offline,
+}
+
+#[component]
+pub fn ErrorScreen(error: LoadError, puzzle_id: String, on_retry: EventHandler<()>) -> Element {
+    let locale = use_locale();
+    let (title, body, detail) = match &error {
+        LoadError::InvalidInput => (Msg::ErrorInvalidTitle, Msg::ErrorInvalidBody, None),
+        LoadError::BadClues(detail) => (
+            Msg::ErrorInvalidTitle,
+            Msg::ErrorCluesBody,
+            Some(detail.clone()),
+        ),
+        LoadError::Network(detail) | LoadError::Server(detail) => (
+            Msg::ErrorNetworkTitle,
+            Msg::ErrorNetworkBody,
+            Some(detail.clone()),
+        ),
+        LoadError::NotFound => (Msg::ErrorNotFoundTitle, Msg::ErrorNotFoundBody, None),
+        LoadError::Offline => (Msg::ErrorOfflineTitle, Msg::ErrorOfflineBody, None),
+        LoadError::Parse { detail, bundle, .. } => (
+            Msg::ErrorParseTitle,
+            Msg::ErrorParseBody,
+            Some(format!(
+                "{detail} ({})",
+                locale.format(Msg::BugReportId, &[bundle])
+            )),
+        ),
+        LoadError::Solver(detail) => (
+            Msg::ErrorSolverTitle,
+            Msg::ErrorSolverBody,
+            Some(detail.clone()),
+        ),
+        LoadError::TooLarge(detail) => (
+            Msg::ErrorTooLargeTitle,
+            Msg::ErrorTooLargeBody,
+            Some(detail.clone()),
+        ),
+        LoadError::Busy(secs) => (
+            Msg::ErrorBusyTitle,
+            Msg::ErrorBusyBody,
+            Some(locale.format(Msg::RetryAfter, &[secs])),
+        ),
+    };
+    let retryable = !matches!(
+        error,
+        LoadError::InvalidInput | LoadError::BadClues(_) | LoadError::TooLarge(_)
+    );
+    let snapshot = match &error {
+        LoadError::Parse {
+            detail,
+            snapshot,
+            bundle,
+        } => Some(bug_report(&puzzle_id, detail, bundle, snapshot)),
+        _ => None,
+    };
+
+    rsx! {
+        div { class: "error-screen", role: "alert",
+            h2 { class: "error-title", {locale.text(title)} }
+            p { class: "error-body", {locale.text(body)} }
+            if let Some(detail) = detail {
+                code { class: "error-detail", "{detail}" }
+            }
+            div { class: "error-actions",
+                if retryable {
+                    button {
+                        class: "grid-toggle",
+                        r#type: "button",
+                        onclick: move |_| on_retry.call(()),
+                        {locale.text(Msg::Retry)}
+                    }
+                }
+                if let Some(report) = snapshot {
+                    button {
+                        class: "grid-toggle",
+                        r#type: "button",
+                        onclick: move |_| {
+                            local_store::download_text(
+                                "nonogram-page-snapshot.html",
+                                "text/html",
+                                &report,
+                            )
+                        },
+                        {locale.text(Msg::DownloadSnapshot)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The fetched page with a leading comment describing the failure.
+fn bug_report(puzzle_id: &str, detail: &str, bundle: &str, snapshot: &str) -> String {
+    let detail = detail.replace("--", "- -");
+    format!(
+        "<!-- nonogram-solver parse failure\n     puzzle: {puzzle_id}\n     bundle: {bundle}\n     error: {detail} -->\n{snapshot}"
+    )
+}
@@ -0,0 +1,84 @@
+use dioxus::prelude::*;
+
+use crate::favorites::{self, Favorite, use_favorites};
+use crate::i18n::{Msg, use_locale};
+
+#[component]
+pub fn FavoritesPage(on_open: EventHandler<String>) -> Element {
+    let favorites = use_favorites();
+    let locale = use_locale();
+    let mut import_error = use_signal(|| None::<String>);
+    let list = favorites();
+    let is_empty = list.is_empty();
+
+    rsx! {
+        div { class: "favorites",
+            div { class: "favorites-actions",
+                button {
+                    class: "grid-toggle",
+                    r#type: "button",
+                    disabled: is_empty,
+                    onclick: move |_| favorites::export(&favorites.read()),
+                    {locale.text(Msg::FavoritesExport)}
+                }
+                label { class: "grid-toggle file-button",
+                    {locale.text(Msg::FavoritesImport)}
+                    input {
+                        class: "sr-only",
+                        r#type: "file",
+                        accept: "application/json,.json",
+                        onchange: move |e| async move {
+                            let Some(file) = e.files().into_iter().next() else {
+                                return;
+                            };
+                            let parsed = match file.read_string().await {
+                                Ok(text) => serde_json::from_str::<Vec<Favorite>>(&text)
+                                    .map_err(|err| err.to_string()),
+                                Err(err) => Err(err.to_string()),
+                            };
+                            match parsed {
+                                Ok(imported) => {
+                                    favorites::merge(favorites, imported);
+                                    import_error.set(None);
+                                }
+                                Err(err) => import_error.set(Some(locale.format(Msg::FavoritesImportFailed, &[&err]))),
+                            }
+                        },
+                    }
+                }
+            }
+            if let Some(err) = import_error() {
+                div { class: "status", "{err}" }
+            }
+            if is_empty {
+                div { class: "status", {locale.text(Msg::FavoritesEmpty)} }
+            }
+            ul { class: "favorites-list",
+                for favorite in list {
+                    li { key: "{favorite.url}", class: "favorite-item",
+                        button {
+                            class: "favorite-open",
+                            r#type: "button",
+                            onclick: {
+                                let url = favorite.url.clone();
+                                move |_| on_open.call(url.clone())
+                            },
+                            {favorite.label(locale)}
+                        }
+                        span { class: "favorite-id", "#{favorite.id}" }
+                        button {
+                            class: "favorite-remove",
+                            r#type: "button",
+                            "aria-label": locale.text(Msg::RemoveFavorite),
+                            onclick: {
+                                let url = favorite.url.clone();
+                                move |_| favorites::remove(favorites, &url)
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
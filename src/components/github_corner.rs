@@ -1,14 +1,17 @@
 use dioxus::prelude::*;
 
+use crate::i18n::{Msg, use_locale};
+
 #[component]
 pub fn GithubCorner(href: String) -> Element {
+    let locale = use_locale();
     rsx! {
         a {
             class: "github-corner",
             href,
             target: "_blank",
             rel: "noopener noreferrer",
-            "aria-label": "View source on GitHub",
+            "aria-label": locale.text(Msg::ViewSource),
             svg {
                 view_box: "0 0 250 250",
                 path { d: "M0,0 L115,115 L130,115 L142,142 L250,250 L250,0 Z" }
@@ -0,0 +1,229 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use nonogram_solver::color::Color;
+use nonogram_solver::nonogram_solver::{
+    group_ownership, line_extremes, mask_to_color_index, solve_line,
+};
+use nonogram_solver::puzzle_crawler::{Group, PuzzleData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Row,
+    Col,
+}
+
+/// One row or column of the shown step, with its leftmost and rightmost
+/// placements and a button that runs the line solver on it alone. Hovering a
+/// clue group outlines the cells it covers in every filling.
+#[component]
+pub fn LineInspector(data: PuzzleData, grid: Vec<Vec<u64>>, color_panel: Vec<String>) -> Element {
+    let locale = use_locale();
+    let mut axis = use_signal(|| Axis::Row);
+    let mut index = use_signal(|| 0usize);
+    // The line that was solved and the result, so a stale result is not shown
+    // after picking another line or step.
+    let mut solved = use_signal(|| None::<(Axis, usize, Vec<u64>, Option<Vec<u64>>)>);
+    let mut hovered = use_signal(|| None::<usize>);
+
+    let (clues, count) = match axis() {
+        Axis::Row => (&data.row_groups, data.row_groups.len()),
+        Axis::Col => (&data.col_groups, data.col_groups.len()),
+    };
+    let idx = index().min(count.saturating_sub(1));
+    let groups: Vec<Group> = clues.get(idx).cloned().unwrap_or_default();
+    let cells: Vec<u64> = match axis() {
+        Axis::Row => grid.get(idx).cloned().unwrap_or_default(),
+        Axis::Col => grid
+            .iter()
+            .filter_map(|line| line.get(idx).copied())
+            .collect(),
+    };
+    let extremes = line_extremes(&groups, &cells);
+    let owners = group_ownership(&groups, &cells).unwrap_or_default();
+    let after = solved
+        .read()
+        .as_ref()
+        .filter(|(a, i, before, _)| *a == axis() && *i == idx && *before == cells)
+        .map(|(_, _, _, after)| after.clone());
+
+    let chips: Vec<(usize, String, String)> = groups
+        .iter()
+        .enumerate()
+        .map(|(g, group)| {
+            let ink = palette_color(&color_panel, group.color_id);
+            (
+                g,
+                format!("background: {ink}; color: {};", ink.text_color()),
+                group.len.to_string(),
+            )
+        })
+        .collect();
+    let current: Vec<(String, &'static str)> = cells
+        .iter()
+        .enumerate()
+        .map(|(cell, &mask)| {
+            let owned = hovered().is_some_and(|g| owners.get(cell) == Some(&Some(g)));
+            (
+                mask_background(mask, &color_panel),
+                if owned {
+                    "line-cell owned"
+                } else {
+                    "line-cell"
+                },
+            )
+        })
+        .collect();
+    let placements = extremes.as_ref().map(|(left, right)| {
+        [left, right].map(|ranges| {
+            let mut row = vec![String::from("transparent"); cells.len()];
+            for (range, group) in ranges.iter().zip(&groups) {
+                let ink = palette_color(&color_panel, group.color_id).to_string();
+                for cell in row.iter_mut().skip(range.start).take(range.len()) {
+                    *cell = ink.clone();
+                }
+            }
+            row
+        })
+    });
+    let narrowed: Option<Vec<(String, &'static str)>> = after.clone().flatten().map(|after| {
+        after
+            .iter()
+            .zip(&cells)
+            .map(|(&mask, &before)| {
+                let class = if mask != before {
+                    "line-cell changed"
+                } else {
+                    "line-cell"
+                };
+                (mask_background(mask, &color_panel), class)
+            })
+            .collect()
+    });
+    let changed = after.as_ref().and_then(Option::as_ref).map_or(0, |after| {
+        after.iter().zip(&cells).filter(|(a, b)| a != b).count()
+    });
+
+    rsx! {
+        details { class: "stats-panel line-inspector",
+            summary { class: "stats-summary", {locale.text(Msg::InspectLine)} }
+            div { class: "line-picker",
+                select {
+                    "aria-label": locale.text(Msg::LineAxis),
+                    onchange: move |e| {
+                        axis.set(if e.value() == "col" { Axis::Col } else { Axis::Row });
+                        index.set(0);
+                    },
+                    option { value: "row", selected: axis() == Axis::Row, {locale.text(Msg::LineRow)} }
+                    option { value: "col", selected: axis() == Axis::Col, {locale.text(Msg::LineColumn)} }
+                }
+                input {
+                    r#type: "number",
+                    "aria-label": locale.text(Msg::LineNumber),
+                    min: "1",
+                    max: "{count}",
+                    value: "{idx + 1}",
+                    oninput: move |e| {
+                        if let Ok(number) = e.value().parse::<usize>() {
+                            index.set(number.saturating_sub(1));
+                        }
+                    },
+                }
+                button {
+                    class: "grid-toggle",
+                    r#type: "button",
+                    disabled: count == 0,
+                    onclick: {
+                        let groups = groups.clone();
+                        let cells = cells.clone();
+                        move |_| {
+                            let mut after = cells.clone();
+                            let fits = solve_line(&groups, &mut after);
+                            solved.set(Some((axis(), idx, cells.clone(), fits.then_some(after))));
+                        }
+                    },
+                    {locale.text(Msg::SolveThisLine)}
+                }
+            }
+            dl { class: "stats-list",
+                dt { {locale.text(Msg::LineClue)} }
+                dd {
+                    for (g, style, len) in chips {
+                        span {
+                            class: "clue-chip",
+                            style,
+                            onmouseenter: move |_| hovered.set(Some(g)),
+                            onmouseleave: move |_| hovered.set(None),
+                            "{len}"
+                        }
+                    }
+                }
+                dt { {locale.text(Msg::LineCurrent)} }
+                dd { {line_cells(current.into_iter())} }
+                if let Some([left, right]) = placements {
+                    dt { {locale.text(Msg::LineLeftmost)} }
+                    dd { {line_cells(left.into_iter().map(|bg| (bg, "line-cell")))} }
+                    dt { {locale.text(Msg::LineRightmost)} }
+                    dd { {line_cells(right.into_iter().map(|bg| (bg, "line-cell")))} }
+                } else {
+                    dt {}
+                    dd { {locale.text(Msg::LineNoFilling)} }
+                }
+                if let Some(narrowed) = narrowed {
+                    dt { {locale.text(Msg::LineAfter)} }
+                    dd {
+                        {line_cells(narrowed.into_iter())}
+                        div { {locale.format(Msg::LineNarrowed, &[&changed])} }
+                    }
+                } else if after.is_some() {
+                    dt { {locale.text(Msg::LineAfter)} }
+                    dd { {locale.text(Msg::LineNoFilling)} }
+                }
+            }
+        }
+    }
+}
+
+fn line_cells(cells: impl Iterator<Item = (String, &'static str)>) -> Element {
+    rsx! {
+        div { class: "line-cells",
+            for (background, class) in cells {
+                div { class, style: "background: {background};" }
+            }
+        }
+    }
+}
+
+fn palette_color(color_panel: &[String], idx: usize) -> Color {
+    color_panel
+        .get(idx)
+        .and_then(|color| color.parse().ok())
+        .unwrap_or(Color::WHITE)
+}
+
+/// A solved cell's color, or equal stripes of every color it may still be.
+fn mask_background(mask: u64, color_panel: &[String]) -> String {
+    if let Some(idx) = mask_to_color_index(mask) {
+        return palette_color(color_panel, idx).to_string();
+    }
+    let options: Vec<Color> = (0..color_panel.len().min(64))
+        .filter(|&idx| mask >> idx & 1 != 0)
+        .map(|idx| palette_color(color_panel, idx))
+        .collect();
+    if options.is_empty() {
+        return String::from("transparent");
+    }
+    let step = 100.0 / options.len() as f64;
+    let stops: Vec<String> = options
+        .iter()
+        .enumerate()
+        .map(|(n, color)| {
+            format!(
+                "{color} {:.1}% {:.1}%",
+                n as f64 * step,
+                (n + 1) as f64 * step
+            )
+        })
+        .collect();
+    format!("linear-gradient(135deg, {})", stops.join(", "))
+}
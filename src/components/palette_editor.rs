@@ -0,0 +1,56 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use nonogram_solver::color::Color;
+
+/// Color pickers for each palette entry, to draw the puzzle in nicer or
+/// more contrasting colors. `palette` is the puzzle's own; `overrides`
+/// holds the entries picked so far, which only change how cells are drawn.
+#[component]
+pub fn PaletteEditor(palette: Vec<String>, overrides: Signal<Vec<(usize, Color)>>) -> Element {
+    let locale = use_locale();
+    let entries: Vec<(usize, String)> = palette
+        .iter()
+        .enumerate()
+        .map(|(idx, original)| {
+            let color = overrides
+                .read()
+                .iter()
+                .find(|&&(entry, _)| entry == idx)
+                .map(|&(_, color)| color)
+                .or_else(|| original.parse().ok())
+                .unwrap_or(Color::WHITE);
+            (idx, color.to_string())
+        })
+        .collect();
+
+    rsx! {
+        details { class: "stats-panel palette-editor",
+            summary { class: "stats-summary", {locale.text(Msg::PaletteEditor)} }
+            div { class: "palette-editor-entries",
+                for (idx, hex) in entries {
+                    input {
+                        r#type: "color",
+                        "aria-label": locale.format(Msg::PaletteEntry, &[&idx]),
+                        value: "{hex}",
+                        oninput: move |e| {
+                            let Ok(color) = e.value().parse::<Color>() else {
+                                return;
+                            };
+                            let mut overrides = overrides.write();
+                            overrides.retain(|&(entry, _)| entry != idx);
+                            overrides.push((idx, color));
+                        },
+                    }
+                }
+                button {
+                    class: "grid-toggle",
+                    r#type: "button",
+                    disabled: overrides.read().is_empty(),
+                    onclick: move |_| overrides.write().clear(),
+                    {locale.text(Msg::PaletteReset)}
+                }
+            }
+        }
+    }
+}
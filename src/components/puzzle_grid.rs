@@ -1,40 +1,161 @@
 use dioxus::prelude::*;
 
-use nonogram_solver::nonogram_solver::mask_to_color_index;
+use crate::i18n::{Locale, Msg, use_locale};
+use nonogram_solver::color::Color;
+use nonogram_solver::nonogram_solver::{
+    Conflict, LineEffort, determined_color_counts, mask_to_color_index,
+};
+
+const MIN_ZOOM: f64 = 1.0;
+const MAX_ZOOM: f64 = 4.0;
+/// Cells never shrink below this; narrower containers scroll instead.
+const MIN_CELL_PX: f64 = 6.0;
+const MAX_CELL_PX: f64 = 32.0;
+/// Horizontal padding of `.grid`, both sides.
+const GRID_PADDING_PX: f64 = 12.0;
+
+/// Two-finger gesture state captured when the second finger lands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pinch {
+    distance: f64,
+    mid_x: f64,
+    mid_y: f64,
+    zoom: f64,
+    pan_x: f64,
+    pan_y: f64,
+}
 
 #[component]
-pub fn PuzzleGrid(color_panel: Vec<String>, grid: Vec<Vec<u64>>, is_initial: bool) -> Element {
+pub fn PuzzleGrid(
+    color_panel: Vec<String>,
+    grid: Vec<Vec<u64>>,
+    is_initial: bool,
+    /// Per-cell color probabilities to blend into undetermined cells.
+    heat: Option<Vec<Vec<Vec<f64>>>>,
+    /// Expected cell count per color, shown as progress next to each swatch.
+    expected: Vec<usize>,
+    /// Colors currently solved as background, shown struck through.
+    background: Vec<usize>,
+    /// Called with a color id when its swatch is clicked; white never is.
+    on_toggle_color: EventHandler<usize>,
+    /// A cell to outline, such as the one holding a what-if guess.
+    marked: Option<(usize, usize)>,
+    /// A line or cell to flash, where propagation ran into a contradiction.
+    conflict: Option<Conflict>,
+    /// Called with `(row, col)` when a cell is clicked.
+    on_cell_click: EventHandler<(usize, usize)>,
+    /// Solver work per row and per column, shaded in rails beside the grid.
+    effort: Option<(Vec<LineEffort>, Vec<LineEffort>)>,
+) -> Element {
+    let locale = use_locale();
     let mut show_lines = use_signal(|| true);
+    let mut zoom = use_signal(|| MIN_ZOOM);
+    let mut pan = use_signal(|| (0.0f64, 0.0f64));
+    let mut pinch = use_signal(|| None::<Pinch>);
+    // Content width of the viewport, once the browser has laid it out.
+    let mut available = use_signal(|| None::<f64>);
     let rows = grid.len();
     let cols = grid.first().map(|row| row.len()).unwrap_or(0);
-    let cell_size = cell_size_for_grid(rows, cols);
+    let (cell_size_css, overflows) = match available() {
+        Some(width) => {
+            let fitted = (width - GRID_PADDING_PX) / cols.max(1) as f64;
+            let cell_size = fitted.floor().clamp(MIN_CELL_PX, MAX_CELL_PX);
+            // Also keep tall grids within the screen height.
+            let css = format!(
+                "max({MIN_CELL_PX}px, min({cell_size}px, calc(75vh / {})))",
+                rows.max(1)
+            );
+            (css, fitted < MIN_CELL_PX)
+        }
+        // Before the first measurement (and when rendered on the server), a
+        // guess from the window width.
+        None => (
+            format!(
+                "min({MAX_CELL_PX}px, calc((100vw - 88px) / {}))",
+                cols.max(1)
+            ),
+            false,
+        ),
+    };
     let grid_style = format!(
-        "display: grid; grid-template-columns: repeat({}, {}px); gap: 0;",
-        cols, cell_size
+        "display: grid; grid-template-columns: repeat({}, var(--cell-size)); gap: 0; --cell-size: {};",
+        cols, cell_size_css
+    );
+    let in_conflict = move |row: usize, col: usize| match conflict {
+        Some(Conflict::Row(line)) => line == row,
+        Some(Conflict::Column(line)) => line == col,
+        Some(Conflict::Cell { row: r, col: c }) => (r, c) == (row, col),
+        Some(Conflict::Exhausted) | None => false,
+    };
+    let (pan_x, pan_y) = pan();
+    let zoomed = zoom() > MIN_ZOOM;
+    let viewport_style = format!(
+        "transform: translate({pan_x}px, {pan_y}px) scale({});",
+        zoom()
     );
-    let cells: Vec<String> = grid
+    let cells: Vec<(usize, usize, String)> = grid
         .iter()
-        .flat_map(|row| row.iter())
-        .map(|mask| {
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .map(move |(col, mask)| (row, col, mask))
+        })
+        .map(|(row, col, mask)| {
+            let solved_color = mask_to_color_index(*mask).and_then(|idx| color_panel.get(idx));
+            let heat_color = heat
+                .as_ref()
+                .filter(|_| solved_color.is_none())
+                .and_then(|heat| heat.get(row)?.get(col))
+                .and_then(|probs| blend_colors(&color_panel, probs));
             let color = if is_initial {
                 "#ffffff"
+            } else if let Some(blend) = heat_color.as_deref() {
+                blend
             } else {
-                mask_to_color_index(*mask)
-                    .and_then(|idx| color_panel.get(idx))
-                    .map(|c| c.as_str())
-                    .unwrap_or("#ffffff")
+                solved_color.map(|c| c.as_str()).unwrap_or("#ffffff")
             };
-            format!(
-                "width: {}px; height: {}px; background-color: {};",
-                cell_size, cell_size, color
-            )
+            let style = format!(
+                "width: var(--cell-size); height: var(--cell-size); background-color: {};",
+                color
+            );
+            (row, col, style)
         })
         .collect();
 
-    let swatches: Vec<(String, String)> = color_panel
+    let determined = if is_initial {
+        vec![0; color_panel.len()]
+    } else {
+        determined_color_counts(&grid, color_panel.len())
+    };
+    let swatches: Vec<(usize, &'static str, String, String, String)> = color_panel
         .iter()
-        .map(|color| (format!("background-color: {};", color), color.to_string()))
+        .enumerate()
+        .map(|(idx, color)| {
+            let done = determined.get(idx).copied().unwrap_or(0);
+            let total = expected.get(idx).copied().unwrap_or(0);
+            let class = if idx == 0 {
+                "swatch"
+            } else if background.contains(&idx) {
+                "swatch toggleable dropped"
+            } else {
+                "swatch toggleable"
+            };
+            (
+                idx,
+                class,
+                format!("background-color: {};", color),
+                color.to_string(),
+                format!("{done}/{total}"),
+            )
+        })
         .collect();
+    let rails = effort.as_ref().map(|(row_effort, col_effort)| {
+        (
+            effort_marks(row_effort, col_effort, row_effort, locale),
+            effort_marks(row_effort, col_effort, col_effort, locale),
+        )
+    });
     let grid_class = if show_lines() {
         "grid show-lines"
     } else {
@@ -45,39 +166,169 @@ pub fn PuzzleGrid(color_panel: Vec<String>, grid: Vec<Vec<u64>>, is_initial: boo
         div { class: "puzzle-meta",
             div { class: "puzzle-meta-line",
                 span { class: "puzzle-size", "{cols} × {rows}" }
-                span { class: "palette-label", "colors" }
+                span { class: "palette-label", {locale.text(Msg::PaletteLabel)} }
                 div { class: "palette-inline",
-                    for (style, color) in swatches {
-                        div { class: "swatch-color", style: style, title: color.clone(), "data-color": "{color}" }
+                    for (idx, class, style, color, progress) in swatches {
+                        span {
+                            class,
+                            title: if idx > 0 { locale.text(Msg::ToggleBackground) },
+                            onclick: move |_| {
+                                if idx > 0 {
+                                    on_toggle_color.call(idx);
+                                }
+                            },
+                            div { class: "swatch-color", style: style, title: color.clone(), "data-color": "{color}" }
+                            span { class: "swatch-progress", "{progress}" }
+                        }
                     }
                 }
                 button {
                     class: "grid-toggle",
                     r#type: "button",
                     onclick: move |_| *show_lines.write() = !show_lines(),
-                    if show_lines() { "Hide grid" } else { "Show grid" }
+                    if show_lines() { {locale.text(Msg::HideGrid)} } else { {locale.text(Msg::ShowGrid)} }
                 }
             }
         }
-        div { class: grid_class, style: grid_style,
-            for cell_style in cells {
-                div { class: "cell", style: cell_style }
+        div {
+            class: if overflows { "grid-viewport overflowing" } else { "grid-viewport" },
+            onresize: move |e| {
+                if let Ok(size) = e.get_content_box_size() {
+                    available.set(Some(size.width));
+                }
+            },
+            ontouchstart: move |e| {
+                if let Some((distance, mid_x, mid_y)) = two_finger_span(&e.touches()) {
+                    let (pan_x, pan_y) = pan();
+                    *pinch.write() = Some(Pinch { distance, mid_x, mid_y, zoom: zoom(), pan_x, pan_y });
+                }
+            },
+            ontouchmove: move |e| {
+                let Some(start) = pinch() else {
+                    return;
+                };
+                let Some((distance, mid_x, mid_y)) = two_finger_span(&e.touches()) else {
+                    return;
+                };
+                e.prevent_default();
+                let next_zoom = (start.zoom * distance / start.distance).clamp(MIN_ZOOM, MAX_ZOOM);
+                *zoom.write() = next_zoom;
+                *pan.write() = if next_zoom > MIN_ZOOM {
+                    (start.pan_x + mid_x - start.mid_x, start.pan_y + mid_y - start.mid_y)
+                } else {
+                    (0.0, 0.0)
+                };
+            },
+            ontouchend: move |e| {
+                if e.touches().len() < 2 {
+                    *pinch.write() = None;
+                }
+            },
+            div { class: "grid-zoom", style: viewport_style,
+                div { class: "effort-frame", style: "--cell-size: {cell_size_css};",
+                    if let Some((row_marks, col_marks)) = rails {
+                        div { class: "effort-corner" }
+                        div {
+                            class: "effort-rail effort-cols",
+                            style: "grid-template-columns: repeat({cols}, var(--cell-size));",
+                            for (style, title) in col_marks {
+                                div { class: "effort-mark", style, title }
+                            }
+                        }
+                        div {
+                            class: "effort-rail effort-rows",
+                            style: "grid-template-rows: repeat({rows}, var(--cell-size));",
+                            for (style, title) in row_marks {
+                                div { class: "effort-mark", style, title }
+                            }
+                        }
+                    }
+                    div { class: grid_class, style: grid_style,
+                        for (row, col, cell_style) in cells {
+                            div {
+                                class: match (marked == Some((row, col)), in_conflict(row, col)) {
+                                    (true, true) => "cell marked conflict",
+                                    (true, false) => "cell marked",
+                                    (false, true) => "cell conflict",
+                                    (false, false) => "cell",
+                                },
+                                style: cell_style,
+                                onclick: move |_| on_cell_click.call((row, col)),
+                            }
+                        }
+                    }
+                }
             }
         }
+        if zoomed {
+            button {
+                class: "grid-toggle zoom-reset",
+                r#type: "button",
+                onclick: move |_| {
+                    *zoom.write() = MIN_ZOOM;
+                    *pan.write() = (0.0, 0.0);
+                },
+                {locale.text(Msg::ResetZoom)}
+            }
+        }
+    }
+}
+
+/// A shade and tooltip for each line in `lines`, scaled so the most
+/// expensive row or column is fully shaded. Lines are weighed by time when
+/// the solve was timed, and by one-line solver calls otherwise.
+fn effort_marks(
+    rows: &[LineEffort],
+    cols: &[LineEffort],
+    lines: &[LineEffort],
+    locale: Locale,
+) -> Vec<(String, String)> {
+    let timed = rows.iter().chain(cols).any(|line| !line.elapsed.is_zero());
+    let weight = |line: &LineEffort| {
+        if timed {
+            line.elapsed.as_secs_f64()
+        } else {
+            line.solves as f64
+        }
+    };
+    let most = rows.iter().chain(cols).map(weight).fold(0.0, f64::max);
+    lines
+        .iter()
+        .map(|line| {
+            let share = if most > 0.0 { weight(line) / most } else { 0.0 };
+            let millis = format!("{:.2}", line.elapsed.as_secs_f64() * 1000.0);
+            (
+                format!("--effort: {share:.3};"),
+                locale.format(Msg::LineEffortTitle, &[&line.solves, &millis]),
+            )
+        })
+        .collect()
+}
+
+/// Average the palette colors weighted by probability, as a `#rrggbb` string.
+/// `None` if a palette entry is not a color.
+fn blend_colors(color_panel: &[String], probabilities: &[f64]) -> Option<String> {
+    let mut rgb = [0.0f64; 3];
+    for (color, p) in color_panel.iter().zip(probabilities) {
+        let color: Color = color.parse().ok()?;
+        for (value, channel) in rgb.iter_mut().zip(color.channels()) {
+            *value += f64::from(channel) * p;
+        }
     }
+    let [r, g, b] = rgb.map(|value| value.round().clamp(0.0, 255.0) as u8);
+    Some(Color::rgb(r, g, b).to_string())
 }
 
-fn cell_size_for_grid(rows: usize, cols: usize) -> usize {
-    let max_dim = rows.max(cols);
-    match max_dim {
-        0..=10 => 32,
-        11..=15 => 28,
-        16..=20 => 24,
-        21..=30 => 20,
-        31..=40 => 16,
-        41..=60 => 14,
-        61..=80 => 12,
-        81..=100 => 10,
-        _ => 8,
+/// Distance between the first two touches and their midpoint, if two fingers are down.
+fn two_finger_span(touches: &[TouchPoint]) -> Option<(f64, f64, f64)> {
+    let [a, b, ..] = touches else {
+        return None;
+    };
+    let a = a.client_coordinates();
+    let b = b.client_coordinates();
+    let distance = (a.x - b.x).hypot(a.y - b.y);
+    if distance <= f64::EPSILON {
+        return None;
     }
+    Some((distance, (a.x + b.x) / 2.0, (a.y + b.y) / 2.0))
 }
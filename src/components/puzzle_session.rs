@@ -0,0 +1,199 @@
+use dioxus::prelude::*;
+
+use crate::components::error_screen::{ErrorScreen, LoadError};
+use crate::components::puzzle_viewer::PuzzleViewer;
+use crate::components::solver_settings::SolverSettings;
+use crate::favorites::Favorite;
+use crate::i18n::{Msg, use_locale};
+use crate::offline::{self, use_pending};
+use crate::recent::{self, use_recent};
+use crate::{
+    CORS_PROXY, FetchFailure, fetch_puzzle_data, fetch_puzzle_via_proxy, puzzle_ref_from_input,
+    solve_puzzle_data,
+};
+use nonogram_solver::clue_text::{ClueTextError, parse_clue_text};
+use nonogram_solver::nonogram_solver::{
+    SolveError, SolvePreferences, SolveSteps, SolverOptions, solve_puzzle_steps_with,
+};
+use nonogram_solver::puzzle_crawler::{FetchedPuzzle, PuzzleData, PuzzleMeta, PuzzleRef};
+
+/// One loaded puzzle: fetches and solves `input`, then shows the viewer.
+///
+/// Input that is not a puzzle reference is tried as a pasted clue list and
+/// solved without a fetch. Without a connection, a puzzle opened before is
+/// served from its saved copy and any other is queued (see
+/// [`crate::offline`]).
+///
+/// Each open tab keeps its own session mounted, so switching tabs preserves
+/// the step position without refetching. `on_navigate` receives the input
+/// for the previous or next puzzle.
+///
+/// Clicking a palette swatch re-solves with that color as background, which
+/// helps pick apart a puzzle that stalls on one busy color. The solver
+/// options panel re-solves with another strategy, step density or time
+/// limit. Re-solves run in the background (see [`resolve`]); until one
+/// finishes, or if it fails, the default solve stays on screen.
+#[component]
+pub fn PuzzleSession(input: String, on_navigate: EventHandler<String>) -> Element {
+    let recent_list = use_recent();
+    let pending = use_pending();
+    let locale = use_locale();
+    let mut background = use_signal(Vec::<usize>::new);
+    let preferences = use_signal(SolvePreferences::default);
+    let puzzle_id = puzzle_ref_from_input(&input)
+        .map(|puzzle| puzzle.id)
+        .unwrap_or_default();
+    let mut puzzle = use_resource(use_reactive!(|(input,)| async move {
+        background.write().clear();
+        if input.trim().is_empty() {
+            return Ok(None);
+        }
+        let Some(puzzle) = puzzle_ref_from_input(&input) else {
+            let data = parse_clue_text(&input).map_err(|err| match err {
+                ClueTextError::NoSections => LoadError::InvalidInput,
+                _ => LoadError::BadClues(err.to_string()),
+            })?;
+            return solve(PuzzleMeta::default(), data);
+        };
+        let FetchedPuzzle { data, meta } = match download_puzzle(&puzzle).await {
+            Ok(fetched) => {
+                offline::remember(&puzzle, &fetched);
+                fetched
+            }
+            Err(err @ (LoadError::Network(_) | LoadError::Server(_))) => {
+                match offline::saved(&puzzle).await {
+                    Some(saved) => saved,
+                    None if !offline::is_online().await => {
+                        offline::queue(pending, &input);
+                        return Err(LoadError::Offline);
+                    }
+                    None => return Err(err),
+                }
+            }
+            Err(err) => return Err(err),
+        };
+        recent::record(recent_list, Favorite::from_meta(&meta));
+        solve(meta, data)
+    }));
+    let resolved = use_resource(move || async move {
+        let background = background();
+        let preferences = preferences();
+        if background.is_empty() && preferences == SolvePreferences::default() {
+            return None;
+        }
+        let data = match &*puzzle.read() {
+            Some(Ok(Some((_, data, _)))) => data.clone(),
+            _ => return None,
+        };
+        let merges: Vec<_> = background.iter().map(|&color| (color, 0)).collect();
+        // Merged clues solve the same as merging inside the solve.
+        let shown = data.recolor(&merges).ok()?;
+        Some(
+            resolve(shown.clone(), preferences)
+                .await
+                .map(|steps| (shown, steps)),
+        )
+    });
+    let on_toggle_color = move |color: usize| {
+        let mut background = background.write();
+        match background.iter().position(|&dropped| dropped == color) {
+            Some(pos) => {
+                background.remove(pos);
+            }
+            None => background.push(color),
+        }
+    };
+
+    match puzzle() {
+        None => rsx! { div { class: "status", {locale.text(Msg::LoadingPuzzle)} } },
+        Some(Err(error)) => {
+            rsx! {
+                ErrorScreen { error, puzzle_id, on_retry: move |_| puzzle.restart() }
+            }
+        }
+        Some(Ok(None)) => rsx! { div { class: "status", {locale.text(Msg::EnterPuzzle)} } },
+        Some(Ok(Some((meta, data, steps)))) => {
+            let (shown, error) = match resolved().flatten() {
+                Some(Ok(shown)) => (shown, None),
+                Some(Err(error)) => ((data, steps), Some(error)),
+                None => ((data, steps), None),
+            };
+            let (data, steps) = shown;
+            rsx! {
+                div { class: "grid-wrap",
+                    PuzzleViewer {
+                        meta,
+                        data,
+                        steps,
+                        background: background(),
+                        on_navigate,
+                        on_toggle_color,
+                    }
+                }
+                SolverSettings { preferences, error }
+            }
+        }
+    }
+}
+
+/// Fetch `puzzle` through the server, or through the CORS proxy on a static
+/// deployment.
+pub(crate) async fn download_puzzle(puzzle: &PuzzleRef) -> Result<FetchedPuzzle, LoadError> {
+    let fetched = match CORS_PROXY {
+        Some(proxy) => fetch_puzzle_via_proxy(proxy, puzzle).await,
+        None => fetch_puzzle_data(puzzle.kind.param().to_string(), puzzle.id.clone())
+            .await
+            .map_err(|err| LoadError::Server(err.to_string()))?,
+    };
+    fetched.map_err(|failure| match failure {
+        FetchFailure::Network(detail) => LoadError::Network(detail),
+        FetchFailure::NotFound => LoadError::NotFound,
+        FetchFailure::Parse {
+            detail,
+            snapshot,
+            bundle,
+        } => LoadError::Parse {
+            detail,
+            snapshot,
+            bundle,
+        },
+        FetchFailure::TooLarge(detail) => LoadError::TooLarge(detail),
+        FetchFailure::RateLimited { retry_after_secs } => LoadError::Busy(retry_after_secs),
+    })
+}
+
+/// Solve `data` again under `preferences`, off the render path: through the
+/// server's `/api/solve` when there is one, or else on a blocking thread,
+/// or in the browser after a yield so the page can paint first. The solve
+/// itself is not interruptible, so a static deployment still pauses for it.
+async fn resolve(data: PuzzleData, preferences: SolvePreferences) -> Result<SolveSteps, String> {
+    if CORS_PROXY.is_none() {
+        return solve_puzzle_data(data, Some(preferences))
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    let options = SolverOptions::web().with_preferences(&preferences);
+    let run = move || solve_puzzle_steps_with(data, &options).map_err(|err| err.to_string());
+    #[cfg(target_arch = "wasm32")]
+    {
+        gloo_timers::future::TimeoutFuture::new(0).await;
+        run()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::task::spawn_blocking(run)
+            .await
+            .map_err(|err| err.to_string())?
+    }
+}
+
+type Loaded = Option<(PuzzleMeta, PuzzleData, SolveSteps)>;
+
+fn solve(meta: PuzzleMeta, data: PuzzleData) -> Result<Loaded, LoadError> {
+    solve_puzzle_steps_with(data.clone(), &SolverOptions::web())
+        .map(|steps| Some((meta, data, steps)))
+        .map_err(|err| match err {
+            SolveError::LimitExceeded(_) => LoadError::TooLarge(err.to_string()),
+            _ => LoadError::Solver(err.to_string()),
+        })
+}
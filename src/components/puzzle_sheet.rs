@@ -0,0 +1,123 @@
+use dioxus::prelude::*;
+
+use nonogram_solver::color::Color;
+use nonogram_solver::puzzle_crawler::{Group, PuzzleData};
+
+/// Blank puzzle with its clues, laid out as a table for printing.
+///
+/// Column clues are bottom-aligned above the grid and row clues right-aligned
+/// to its left. Every fifth grid line is drawn bold.
+#[component]
+pub fn PuzzleSheet(title: String, data: PuzzleData) -> Element {
+    let rows = data.row_groups.len();
+    let cols = data.col_groups.len();
+    let clue_rows = data.col_groups.iter().map(Vec::len).max().unwrap_or(0);
+    let clue_cols = data.row_groups.iter().map(Vec::len).max().unwrap_or(0);
+    let palette = data.color_panel.clone();
+    let is_color = palette.len() > 2;
+
+    // Pad each clue list at the front so clues hug the grid.
+    let padded = |groups: &[Group], width: usize| -> Vec<Option<Group>> {
+        let mut cells = vec![None; width.saturating_sub(groups.len())];
+        cells.extend(groups.iter().cloned().map(Some));
+        cells
+    };
+    let col_clues: Vec<Vec<Option<Group>>> = data
+        .col_groups
+        .iter()
+        .map(|groups| padded(groups, clue_rows))
+        .collect();
+    let row_clues: Vec<Vec<Option<Group>>> = data
+        .row_groups
+        .iter()
+        .map(|groups| padded(groups, clue_cols))
+        .collect();
+
+    let clue_style = move |group: &Option<Group>| -> String {
+        match group {
+            Some(group) if is_color => {
+                let background = palette
+                    .get(group.color_id)
+                    .map_or("#ffffff", String::as_str);
+                format!(
+                    "background-color: {background}; color: {};",
+                    clue_text_color(background)
+                )
+            }
+            _ => String::new(),
+        }
+    };
+    let clue_label = |group: &Option<Group>| {
+        group
+            .as_ref()
+            .map(|g| g.len.to_string())
+            .unwrap_or_default()
+    };
+
+    rsx! {
+        section { class: "print-sheet",
+            h2 { class: "print-title", "{title}" }
+            div { class: "print-size", "{cols} × {rows}" }
+            table { class: "sheet-table",
+                for clue_row in 0..clue_rows {
+                    tr { key: "c{clue_row}",
+                        td { class: "sheet-corner", colspan: "{clue_cols}" }
+                        for (col, clues) in col_clues.iter().enumerate() {
+                            td {
+                                key: "{col}",
+                                class: clue_edge_class(&sheet_line_class("sheet-clue", col, cols, false), clue_row, clue_rows, true),
+                                style: clue_style(&clues[clue_row]),
+                                "{clue_label(&clues[clue_row])}"
+                            }
+                        }
+                    }
+                }
+                for (row, clues) in row_clues.iter().enumerate() {
+                    tr { key: "r{row}",
+                        for (idx, clue) in clues.iter().enumerate() {
+                            td {
+                                key: "{idx}",
+                                class: clue_edge_class(&sheet_line_class("sheet-clue", row, rows, true), idx, clue_cols, false),
+                                style: clue_style(clue),
+                                "{clue_label(clue)}"
+                            }
+                        }
+                        for col in 0..cols {
+                            td {
+                                key: "{col}",
+                                class: sheet_line_class(&sheet_line_class("sheet-cell", col, cols, false), row, rows, true),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Append `bold-right` / `bold-bottom` for every fifth line and the outer edge.
+fn sheet_line_class(base: &str, idx: usize, len: usize, horizontal: bool) -> String {
+    let bold = (idx + 1).is_multiple_of(5) || idx + 1 == len;
+    match (bold, horizontal) {
+        (true, true) => format!("{base} bold-bottom"),
+        (true, false) => format!("{base} bold-right"),
+        (false, _) => base.to_string(),
+    }
+}
+
+/// Bold edge between the innermost clue row/column and the grid.
+fn clue_edge_class(base: &str, idx: usize, len: usize, horizontal: bool) -> String {
+    match (idx + 1 == len, horizontal) {
+        (true, true) => format!("{base} bold-bottom"),
+        (true, false) => format!("{base} bold-right"),
+        (false, _) => base.to_string(),
+    }
+}
+
+/// Black or white text, whichever reads better on a palette background.
+fn clue_text_color(background: &str) -> &'static str {
+    match background.parse::<Color>() {
+        Ok(color) if color.is_dark() => "#ffffff",
+        _ => "#1f1f1f",
+    }
+}
@@ -1,49 +1,428 @@
 use dioxus::prelude::*;
 
+use crate::components::line_inspector::LineInspector;
+use crate::components::palette_editor::PaletteEditor;
 use crate::components::puzzle_grid::PuzzleGrid;
-use nonogram_solver::nonogram_solver::SolveSteps;
+use crate::components::puzzle_sheet::PuzzleSheet;
+use crate::components::stats_panel::StatsPanel;
+use crate::components::step_minimap::StepMinimap;
+use crate::favorites::{self, Favorite, use_favorites};
+use crate::i18n::{Msg, use_locale};
+use crate::puzzle_ref_from_input;
+use nonogram_solver::color::{Color, repaint};
+use nonogram_solver::nonogram_solver::{
+    Conflict, Contradiction, Grid, GridDiff, SolveSteps, SolverOptions, cell_probabilities,
+    expected_color_counts, mask_to_color_index, solve_puzzle_steps_traced,
+};
+use nonogram_solver::puzzle_crawler::{PuzzleData, PuzzleMeta};
 
+/// `on_navigate` receives the input for the previous or next puzzle;
+/// `on_toggle_color` a palette entry to solve as background, or stop solving
+/// as background, with `background` the ones currently dropped.
+///
+/// On the last step of a stalled solve, clicking an open cell lets the user
+/// try a color there and step through what line logic makes of the guess.
+/// A guess that contradicts the clues replays the propagation up to the
+/// contradiction and flashes the line or cell where it failed.
 #[component]
-pub fn PuzzleViewer(steps: SolveSteps) -> Element {
+pub fn PuzzleViewer(
+    meta: PuzzleMeta,
+    data: PuzzleData,
+    steps: SolveSteps,
+    background: Vec<usize>,
+    on_navigate: EventHandler<String>,
+    on_toggle_color: EventHandler<usize>,
+) -> Element {
     let total_steps = steps.steps.len();
     let mut current_step = use_signal(|| 0usize);
     let mut last_len = use_signal(|| 0usize);
+    let mut show_heat = use_signal(|| false);
+    let show_effort = use_signal(|| false);
+    // The open cell a color is being picked for, and the guess tried.
+    let mut picking = use_signal(|| None::<(usize, usize)>);
+    let mut what_if = use_signal(|| None::<WhatIf>);
+    // Display colors picked in the palette editor.
+    let mut repainted = use_signal(Vec::<(usize, Color)>::new);
+    let favorite_list = use_favorites();
+    let locale = use_locale();
     let steps_len = steps.steps.len();
     use_effect(move || {
         if last_len() != steps_len {
             *current_step.write() = 0;
             *last_len.write() = steps_len;
+            picking.set(None);
+            what_if.set(None);
+            repainted.write().clear();
         }
     });
 
     let step_idx = current_step().min(steps_len.saturating_sub(1));
     let grid = steps.steps.get(step_idx).cloned().unwrap_or_default();
-    let color_panel = steps.color_panel.clone();
+    let color_panel = repaint(&steps.color_panel, &repainted.read());
+    let inspected = (grid.clone(), color_panel.clone());
+    let stats = steps.stats.clone();
+    let effort = show_effort().then(|| (stats.row_effort.clone(), stats.col_effort.clone()));
+    let activity = step_activity(&steps.steps);
     let is_initial = step_idx == 0;
     let max_step = total_steps.saturating_sub(1);
+    let stalled = !steps.stats.solved;
+    let blank = data
+        .row_groups
+        .iter()
+        .chain(&data.col_groups)
+        .all(Vec::is_empty);
+    let exploring = stalled && step_idx == max_step;
+    let guess = what_if().filter(|_| exploring);
+    let heat = if exploring && show_heat() && guess.is_none() {
+        cell_probabilities(&data, &grid)
+    } else {
+        None
+    };
+    let guess_steps: &[Grid] = match guess.as_ref().map(|guess| &guess.outcome) {
+        Some(Outcome::Steps(steps)) => steps,
+        Some(Outcome::Contradiction(contradiction)) => &contradiction.trail,
+        _ => &[],
+    };
+    let guess_step = guess.as_ref().map_or(0, |guess| guess.step);
+    // The failing line flashes once the replay reaches the contradiction.
+    let conflict = match guess.as_ref().map(|guess| &guess.outcome) {
+        Some(Outcome::Contradiction(contradiction))
+            if guess_step + 1 >= contradiction.trail.len() =>
+        {
+            Some(contradiction.conflict)
+        }
+        _ => None,
+    };
+    let shown = guess_steps
+        .get(guess_step)
+        .cloned()
+        .unwrap_or_else(|| grid.clone());
+    let guess_note = guess.as_ref().map(|guess| match &guess.outcome {
+        Outcome::Steps(steps) => {
+            let moves = steps.len().saturating_sub(1);
+            let before = settled_cells(&grid);
+            let after = steps.last().map_or(before, |last| settled_cells(last));
+            if after == grid.iter().map(Vec::len).sum::<usize>() {
+                locale.format(Msg::WhatIfSolves, &[&moves])
+            } else {
+                // The guessed cell itself is not a consequence.
+                let more = after.saturating_sub(before + 1);
+                locale.format(Msg::WhatIfSettles, &[&more, &moves])
+            }
+        }
+        Outcome::Contradiction(contradiction) => {
+            let detail = match contradiction.conflict {
+                Conflict::Row(row) => locale.format(Msg::ConflictRow, &[&(row + 1)]),
+                Conflict::Column(col) => locale.format(Msg::ConflictColumn, &[&(col + 1)]),
+                Conflict::Cell { row, col } => {
+                    locale.format(Msg::ConflictCell, &[&(row + 1), &(col + 1)])
+                }
+                Conflict::Exhausted => locale.text(Msg::ConflictExhausted).to_string(),
+            };
+            format!("{} {detail}", locale.text(Msg::WhatIfContradiction))
+        }
+        Outcome::Failed(detail) => detail.clone(),
+    });
+    let guess_max = guess_steps.len().saturating_sub(1);
+    let marked = guess
+        .as_ref()
+        .map(|guess| (guess.row, guess.col))
+        .or(picking().filter(|_| exploring));
+    let choices: Vec<(usize, String)> = picking()
+        .filter(|_| exploring && guess.is_none())
+        .and_then(|(row, col)| grid.get(row)?.get(col).copied())
+        .map(|mask| {
+            color_panel
+                .iter()
+                .enumerate()
+                .filter(|&(color, _)| color < 64 && mask >> color & 1 == 1)
+                .map(|(color, hex)| (color, hex.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let on_cell_click = {
+        let grid = grid.clone();
+        move |(row, col): (usize, usize)| {
+            let open = grid
+                .get(row)
+                .and_then(|line| line.get(col))
+                .is_some_and(|&mask| mask_to_color_index(mask).is_none());
+            if exploring && what_if.read().is_none() && open {
+                picking.set(Some((row, col)));
+            }
+        }
+    };
+
+    let starred = favorites::is_favorite(&favorite_list.read(), &meta.source_url);
+    let favorite = Favorite::from_meta(&meta);
+    let [previous, next] =
+        [-1, 1].map(|delta| adjacent_input(&favorite_list.read(), &meta.source_url, delta));
+    // Pasted clue lists have no page to link to or star.
+    let pasted = meta.source_url.is_empty();
+    let title = meta.title.clone().unwrap_or_else(|| {
+        if pasted {
+            locale.text(Msg::PastedCluesTitle).to_string()
+        } else {
+            locale.format(Msg::PuzzleFallbackTitle, &[&meta.id])
+        }
+    });
 
     rsx! {
-        PuzzleGrid { color_panel, grid, is_initial }
-        div { class: "step-controls",
-            label { class: "sr-only", r#for: "step-slider", "Solve step slider" }
-            input {
-                id: "step-slider",
-                class: "step-slider",
-                r#type: "range",
-                min: "0",
-                max: "{max_step}",
-                value: "{step_idx}",
-                oninput: move |e| {
-                    if let Ok(value) = e.value().parse::<usize>() {
-                        *current_step.write() = value.min(max_step);
+        div { class: "puzzle-header",
+            h2 { class: "puzzle-title",
+                "{title}"
+                if !pasted {
+                    button {
+                        class: if starred { "favorite-star starred" } else { "favorite-star" },
+                        r#type: "button",
+                        "aria-pressed": "{starred}",
+                        title: if starred { locale.text(Msg::RemoveFavorite) } else { locale.text(Msg::AddFavorite) },
+                        onclick: move |_| favorites::toggle(favorite_list, favorite.clone()),
+                        if starred { "★" } else { "☆" }
+                    }
+                }
+            }
+            div { class: "puzzle-byline",
+                if let Some(author) = meta.author.clone() {
+                    span { class: "puzzle-author", {locale.format(Msg::ByAuthor, &[&author])} }
+                }
+                if let Some(rating) = meta.rating.clone() {
+                    span { class: "puzzle-rating", {locale.format(Msg::PuzzleRating, &[&rating])} }
+                }
+                button {
+                    class: "print-button",
+                    r#type: "button",
+                    onclick: move |_| {
+                        let _ = document::eval("window.print();");
+                    },
+                    {locale.text(Msg::PrintPuzzle)}
+                }
+                if let Some(input) = previous {
+                    button {
+                        class: "puzzle-nav",
+                        r#type: "button",
+                        onclick: move |_| on_navigate.call(input.clone()),
+                        {locale.text(Msg::PreviousPuzzle)}
+                    }
+                }
+                if let Some(input) = next {
+                    button {
+                        class: "puzzle-nav",
+                        r#type: "button",
+                        onclick: move |_| on_navigate.call(input.clone()),
+                        {locale.text(Msg::NextPuzzle)}
+                    }
+                }
+                if !pasted {
+                    a {
+                        class: "puzzle-source",
+                        href: meta.source_url.clone(),
+                        target: "_blank",
+                        rel: "noopener noreferrer",
+                        {locale.format(Msg::SourceLink, &[&meta.id])}
+                    }
+                }
+            }
+        }
+        PuzzleSheet {
+            title: title.clone(),
+            data: PuzzleData {
+                color_panel: color_panel.clone(),
+                ..data.clone()
+            },
+        }
+        PuzzleGrid {
+            color_panel,
+            grid: shown,
+            is_initial: is_initial && guess.is_none(),
+            heat,
+            expected: expected_color_counts(&data),
+            background,
+            on_toggle_color,
+            marked,
+            conflict,
+            on_cell_click,
+            effort,
+        }
+        if stalled {
+            div { class: "stall-note",
+                {locale.text(Msg::StallNote)}
+                " "
+                button {
+                    class: "grid-toggle heat-toggle",
+                    r#type: "button",
+                    onclick: move |_| {
+                        let next = !show_heat();
+                        *show_heat.write() = next;
+                        if next {
+                            *current_step.write() = max_step;
+                        }
+                    },
+                    if show_heat() { {locale.text(Msg::HideProbabilities)} } else { {locale.text(Msg::ShowProbabilities)} }
+                }
+            }
+            if exploring && marked.is_none() {
+                div { class: "step-note", {locale.text(Msg::WhatIfHint)} }
+            }
+            if let Some((row, col)) = marked.filter(|_| !choices.is_empty()) {
+                div { class: "what-if",
+                    {locale.format(Msg::WhatIfPrompt, &[&(row + 1), &(col + 1)])}
+                    for (color, hex) in choices {
+                        button {
+                            class: "what-if-color",
+                            r#type: "button",
+                            style: "background-color: {hex};",
+                            title: "{hex}",
+                            onclick: {
+                                let data = data.clone();
+                                let grid = grid.clone();
+                                move |_| {
+                                    what_if.set(Some(follow_guess(&data, &grid, (row, col), color)));
+                                    picking.set(None);
+                                }
+                            },
+                        }
+                    }
+                    button {
+                        class: "grid-toggle",
+                        r#type: "button",
+                        onclick: move |_| picking.set(None),
+                        {locale.text(Msg::WhatIfCancel)}
+                    }
+                }
+            }
+            if let Some(note) = guess_note {
+                div { class: "what-if",
+                    if guess_max > 0 {
+                        input {
+                            class: "step-slider",
+                            r#type: "range",
+                            "aria-label": locale.text(Msg::StepSliderLabel),
+                            min: "0",
+                            max: "{guess_max}",
+                            value: "{guess_step}",
+                            oninput: move |e| {
+                                if let (Ok(value), Some(guess)) = (e.value().parse::<usize>(), what_if.write().as_mut()) {
+                                    guess.step = value.min(guess_max);
+                                }
+                            },
+                        }
+                    }
+                    span { "{note}" }
+                    button {
+                        class: "grid-toggle",
+                        r#type: "button",
+                        onclick: move |_| what_if.set(None),
+                        {locale.text(Msg::WhatIfRevert)}
                     }
                 }
             }
-            div { class: "step-label", "Steps: {step_idx} / {max_step}" }
         }
+        if blank {
+            div { class: "step-note", {locale.text(Msg::BlankPuzzleNote)} }
+        }
+        if total_steps > 1 {
+            div { class: "step-controls",
+                label { class: "sr-only", r#for: "step-slider", {locale.text(Msg::StepSliderLabel)} }
+                input {
+                    id: "step-slider",
+                    class: "step-slider",
+                    r#type: "range",
+                    min: "0",
+                    max: "{max_step}",
+                    value: "{step_idx}",
+                    oninput: move |e| {
+                        if let Ok(value) = e.value().parse::<usize>() {
+                            *current_step.write() = value.min(max_step);
+                        }
+                    }
+                }
+                div { class: "step-label", {locale.format(Msg::StepLabel, &[&step_idx, &max_step])} }
+            }
+            StepMinimap {
+                activity,
+                current: step_idx,
+                on_select: move |step: usize| *current_step.write() = step.min(max_step),
+            }
+        }
+        StatsPanel { stats, show_effort }
+        PaletteEditor { palette: steps.color_panel.clone(), overrides: repainted }
+        LineInspector { data: data.clone(), grid: inspected.0, color_panel: inspected.1 }
         div { class: "step-note",
-            "Each step reflects human-style reasoning. "
-            a { href: "https://www.nonograms.org/methods", target: "_blank", rel: "noopener noreferrer", "Learn more" }
+            {locale.text(Msg::StepNote)}
+            " "
+            a { href: "https://www.nonograms.org/methods", target: "_blank", rel: "noopener noreferrer", {locale.text(Msg::LearnMore)} }
         }
     }
 }
+
+/// The input for the puzzle `delta` places away from the one at `url`:
+/// through the favorites list if it is starred, otherwise by id. `None` past
+/// either end of the list and for pasted clues.
+fn adjacent_input(favorites: &[Favorite], url: &str, delta: i64) -> Option<String> {
+    if let Some(pos) = favorites.iter().position(|favorite| favorite.url == url) {
+        let target = pos.checked_add_signed(isize::try_from(delta).ok()?)?;
+        return favorites.get(target).map(|favorite| favorite.url.clone());
+    }
+    Some(puzzle_ref_from_input(url)?.offset(delta)?.url())
+}
+
+/// Where line logic takes a guess.
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    /// The grids from the guess on.
+    Steps(Vec<Grid>),
+    /// Some line has no filling once the guess is in; the trail replays how
+    /// propagation got there.
+    Contradiction(Contradiction),
+    /// The solve stopped for another reason, such as a limit.
+    Failed(String),
+}
+
+/// A color tried in an open cell, and the step of its outcome on show.
+#[derive(Debug, Clone, PartialEq)]
+struct WhatIf {
+    row: usize,
+    col: usize,
+    outcome: Outcome,
+    step: usize,
+}
+
+/// Fix cell `(row, col)` of `grid` to `color` and propagate from there.
+fn follow_guess(
+    data: &PuzzleData,
+    grid: &Grid,
+    (row, col): (usize, usize),
+    color: usize,
+) -> WhatIf {
+    let mut start = grid.clone();
+    start[row][col] = 1 << color;
+    let outcome = match solve_puzzle_steps_traced(data.clone(), Some(start), &SolverOptions::web())
+    {
+        Ok(Ok(steps)) => Outcome::Steps(steps.steps),
+        Ok(Err(contradiction)) => Outcome::Contradiction(contradiction),
+        Err(err) => Outcome::Failed(err.to_string()),
+    };
+    WhatIf {
+        row,
+        col,
+        outcome,
+        step: 0,
+    }
+}
+
+fn settled_cells(grid: &Grid) -> usize {
+    grid.iter()
+        .flatten()
+        .filter(|&&mask| mask_to_color_index(mask).is_some())
+        .count()
+}
+
+/// Number of cells whose mask differs from the previous step (zero for step 0).
+fn step_activity(steps: &[Vec<Vec<u64>>]) -> Vec<usize> {
+    let mut activity = vec![0; steps.len()];
+    for (idx, pair) in steps.windows(2).enumerate() {
+        activity[idx + 1] = pair[0].diff(&pair[1]).len();
+    }
+    activity
+}
@@ -0,0 +1,87 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use nonogram_solver::nonogram_solver::{SolvePreferences, SolveStrategy};
+
+/// Advanced options for one puzzle's solve, collapsed by default: how far
+/// past line logic to go, how many steps to record and a time limit.
+/// `error` is why solving with the current choices failed, if it did.
+#[component]
+pub fn SolverSettings(preferences: Signal<SolvePreferences>, error: Option<String>) -> Element {
+    let locale = use_locale();
+    let current = preferences();
+    let seconds = current
+        .time_limit_ms
+        .map(|ms| (ms / 1000).to_string())
+        .unwrap_or_default();
+
+    rsx! {
+        details { class: "stats-panel solver-settings",
+            summary { class: "stats-summary", {locale.text(Msg::SolverSettings)} }
+            dl { class: "stats-list",
+                dt { {locale.text(Msg::SolverStrategy)} }
+                dd {
+                    select {
+                        "aria-label": locale.text(Msg::SolverStrategy),
+                        onchange: move |e| {
+                            preferences.write().strategy = match e.value().as_str() {
+                                "probing" => SolveStrategy::Probing,
+                                "full" => SolveStrategy::Full,
+                                _ => SolveStrategy::LineOnly,
+                            };
+                        },
+                        option {
+                            value: "line_only",
+                            selected: current.strategy == SolveStrategy::LineOnly,
+                            {locale.text(Msg::StrategyLineOnly)}
+                        }
+                        option {
+                            value: "probing",
+                            selected: current.strategy == SolveStrategy::Probing,
+                            {locale.text(Msg::StrategyProbing)}
+                        }
+                        option {
+                            value: "full",
+                            selected: current.strategy == SolveStrategy::Full,
+                            {locale.text(Msg::StrategyFull)}
+                        }
+                    }
+                }
+                dt { {locale.text(Msg::SolverSteps)} }
+                dd {
+                    label {
+                        input {
+                            r#type: "checkbox",
+                            checked: current.sparse_steps,
+                            onchange: move |e| preferences.write().sparse_steps = e.checked(),
+                        }
+                        {locale.format(Msg::SparseSteps, &[&SolvePreferences::SPARSE_EVERY])}
+                    }
+                }
+                dt { {locale.text(Msg::SolverTimeLimit)} }
+                dd {
+                    input {
+                        r#type: "number",
+                        "aria-label": locale.text(Msg::SolverTimeLimit),
+                        min: "1",
+                        max: "10",
+                        placeholder: "10",
+                        value: "{seconds}",
+                        onchange: move |e| {
+                            preferences.write().time_limit_ms = e
+                                .value()
+                                .trim()
+                                .parse::<u64>()
+                                .ok()
+                                .filter(|&secs| secs > 0)
+                                .map(|secs| secs * 1000);
+                        },
+                    }
+                }
+            }
+            if let Some(error) = error {
+                p { class: "settings-error", "{error}" }
+            }
+        }
+    }
+}
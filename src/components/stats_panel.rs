@@ -0,0 +1,43 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use nonogram_solver::nonogram_solver::SolveStats;
+
+/// The solver's counters, with a toggle for shading the grid's rows and
+/// columns by how much work each took.
+#[component]
+pub fn StatsPanel(stats: SolveStats, show_effort: Signal<bool>) -> Element {
+    let locale = use_locale();
+    let millis = format!("{:.1}", stats.elapsed.as_secs_f64() * 1000.0);
+    let outcome = if stats.solved {
+        locale.text(Msg::StatsSolved)
+    } else {
+        locale.text(Msg::StatsStalled)
+    };
+
+    rsx! {
+        details { class: "stats-panel",
+            summary { class: "stats-summary", {locale.text(Msg::StatsTitle)} }
+            dl { class: "stats-list",
+                dt { {locale.text(Msg::StatsOutcome)} }
+                dd { "{outcome}" }
+                dt { {locale.text(Msg::StatsPasses)} }
+                dd { "{stats.passes}" }
+                dt { {locale.text(Msg::StatsLineSolves)} }
+                dd { "{stats.line_solves}" }
+                dt { {locale.text(Msg::StatsTime)} }
+                dd { {locale.format(Msg::StatsMillis, &[&millis])} }
+                dt { {locale.text(Msg::StatsFromRows)} }
+                dd { "{stats.cells_from_rows}" }
+                dt { {locale.text(Msg::StatsFromCols)} }
+                dd { "{stats.cells_from_cols}" }
+            }
+            button {
+                class: "grid-toggle",
+                r#type: "button",
+                onclick: move |_| *show_effort.write() = !show_effort(),
+                if show_effort() { {locale.text(Msg::HideEffort)} } else { {locale.text(Msg::ShowEffort)} }
+            }
+        }
+    }
+}
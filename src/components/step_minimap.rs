@@ -0,0 +1,41 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+
+/// Activity strip for the step slider: one bar per step, height proportional
+/// to the number of cells that step changed. Clicking a bar jumps to it.
+#[component]
+pub fn StepMinimap(
+    activity: Vec<usize>,
+    current: usize,
+    on_select: EventHandler<usize>,
+) -> Element {
+    let locale = use_locale();
+    let peak = activity.iter().copied().max().unwrap_or(0).max(1);
+
+    rsx! {
+        div { class: "step-minimap", "aria-hidden": "true",
+            for (step, changed) in activity.into_iter().enumerate() {
+                div {
+                    key: "{step}",
+                    class: if step == current { "minimap-bar current" } else { "minimap-bar" },
+                    title: locale.format(Msg::StepActivity, &[&step, &changed]),
+                    onclick: move |_| on_select.call(step),
+                    div {
+                        class: "minimap-fill",
+                        style: "height: {bar_height(changed, peak)}%;",
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bar height as a percentage; any activity gets a visible sliver.
+fn bar_height(changed: usize, peak: usize) -> usize {
+    if changed == 0 {
+        0
+    } else {
+        (changed * 100 / peak).max(8)
+    }
+}
@@ -0,0 +1,24 @@
+use dioxus::prelude::*;
+
+use crate::i18n::{Msg, use_locale};
+use crate::theme::{self, Theme, use_theme};
+
+#[component]
+pub fn ThemeToggle() -> Element {
+    let current = use_theme();
+    let locale = use_locale();
+    let label = match current() {
+        Theme::Light => locale.text(Msg::DarkMode),
+        Theme::Dark => locale.text(Msg::LightMode),
+    };
+
+    rsx! {
+        button {
+            class: "theme-toggle",
+            r#type: "button",
+            "aria-label": locale.text(Msg::ToggleTheme),
+            onclick: move |_| theme::set(current, current().toggled()),
+            "{label}"
+        }
+    }
+}
@@ -0,0 +1,756 @@
+//! Keep a shared server polite towards nonograms.org: per-client and global
+//! rate limits, one fetch per puzzle no matter how many users ask for it at
+//! once, and spacing between requests that honours the site's `Crawl-delay`.
+//! Background prefetches take their turn behind all of that.
+//!
+//! The crawler names itself in its `User-Agent`, with a contact URL if one is
+//! configured, and skips any page the site's robots.txt disallows for it.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, OnceCell};
+use web_time::Instant;
+
+use crate::puzzle_cache::PuzzleCacheBackend;
+use crate::puzzle_crawler::{CrawlError, HtmlFetcher, PuzzleRef, RETRY_DELAY, has_puzzle_data};
+
+const ROBOTS_URL: &str = "https://www.nonograms.org/robots.txt";
+/// Matched against `User-agent:` lines in robots.txt.
+const USER_AGENT: &str = "nonogram-solver";
+/// How long a robots.txt that was read, or found missing, is trusted.
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long to wait before reading robots.txt again after a failed read.
+const ROBOTS_RETRY: Duration = Duration::from_secs(60);
+/// The least spacing between requests under [`Politeness::Strict`].
+pub const STRICT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The `User-Agent` the crawler sends: its robots.txt name and version, and
+/// where the site's operators can reach whoever runs it.
+///
+/// ```
+/// # use nonogram_solver::crawl_policy::user_agent;
+/// assert!(user_agent(Some("https://example.org/bot")).ends_with("(+https://example.org/bot)"));
+/// ```
+pub fn user_agent(contact: Option<&str>) -> String {
+    let name = format!("{USER_AGENT}/{}", env!("CARGO_PKG_VERSION"));
+    match contact {
+        Some(contact) => format!("{name} (+{contact})"),
+        None => name,
+    }
+}
+
+/// How [`PoliteCrawler`] treats a robots.txt it cannot read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Politeness {
+    /// Crawl with no rules beyond the crawler's own spacing.
+    #[default]
+    Standard,
+    /// For batch jobs: fetch nothing until robots.txt has been read, and keep
+    /// requests at least [`STRICT_INTERVAL`] apart.
+    Strict,
+}
+
+/// Token buckets keyed by client: `burst` requests at once, refilled at
+/// `per_second`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    burst: f64,
+    per_second: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        Self {
+            burst: f64::from(burst.max(1)),
+            per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `key`, or report how long until one is available.
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        // Full buckets carry no state, so forget them before the map grows.
+        if buckets.len() > 4096 {
+            let (burst, rate) = (self.burst, self.per_second);
+            buckets.retain(|_, bucket| bucket.refilled(now, burst, rate) < burst);
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        bucket.tokens = bucket.refilled(now, self.burst, self.per_second);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let missing = 1.0 - bucket.tokens;
+        Err(Duration::from_secs_f64(
+            missing / self.per_second.max(f64::EPSILON),
+        ))
+    }
+}
+
+impl Bucket {
+    fn refilled(&self, now: Instant, burst: f64, per_second: f64) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * per_second).min(burst)
+    }
+}
+
+/// The `Crawl-delay` that applies to `agent`, from the most specific
+/// matching `User-agent` group (falling back to `*`).
+pub fn crawl_delay(robots: &str, agent: &str) -> Option<Duration> {
+    let agent = agent.to_ascii_lowercase();
+    let mut group_agents: Vec<String> = Vec::new();
+    let mut in_rules = false;
+    let mut specific = None;
+    let mut wildcard = None;
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                if in_rules {
+                    group_agents.clear();
+                    in_rules = false;
+                }
+                group_agents.push(value.to_ascii_lowercase());
+            }
+            "crawl-delay" => {
+                in_rules = true;
+                let Some(delay) = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                    .map(|secs| Duration::from_secs_f64(secs.min(3600.0)))
+                else {
+                    continue;
+                };
+                for name in &group_agents {
+                    if name == "*" {
+                        wildcard = wildcard.or(Some(delay));
+                    } else if agent.contains(name.as_str()) {
+                        specific = specific.or(Some(delay));
+                    }
+                }
+            }
+            _ => in_rules = true,
+        }
+    }
+    specific.or(wildcard)
+}
+
+/// Whether robots.txt lets `agent` fetch `path`. Rules come from every
+/// `User-agent` group naming the agent, or from the `*` groups if none does;
+/// the longest matching `Allow` or `Disallow` pattern decides, `Allow` on a
+/// tie. Patterns may use `*` and a trailing `$` as in RFC 9309.
+pub fn robots_allow(robots: &str, agent: &str, path: &str) -> bool {
+    let agent = agent.to_ascii_lowercase();
+    // Each group's agents, and its rules as `(allow, pattern)`.
+    type RobotsGroup = (Vec<String>, Vec<(bool, String)>);
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    let mut in_rules = false;
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                if in_rules || groups.is_empty() {
+                    groups.push((Vec::new(), Vec::new()));
+                    in_rules = false;
+                }
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+            }
+            field @ ("allow" | "disallow") => {
+                in_rules = true;
+                if let Some((_, rules)) = groups.last_mut().filter(|_| !value.is_empty()) {
+                    rules.push((field == "allow", value.to_string()));
+                }
+            }
+            _ => in_rules = true,
+        }
+    }
+    let names = |name: &String| name != "*" && agent.contains(name.as_str());
+    let specific = groups.iter().any(|(agents, _)| agents.iter().any(names));
+    groups
+        .iter()
+        .filter(|(agents, _)| {
+            if specific {
+                agents.iter().any(names)
+            } else {
+                agents.iter().any(|name| name == "*")
+            }
+        })
+        .flat_map(|(_, rules)| rules)
+        .filter(|(_, pattern)| pattern_matches(pattern.as_bytes(), path.as_bytes()))
+        .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+        .is_none_or(|&(allow, _)| allow)
+}
+
+/// Whether a robots.txt path pattern matches the start of `path`.
+fn pattern_matches(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((b'$', [])) => path.is_empty(),
+        Some((b'*', rest)) => (0..=path.len()).any(|skip| pattern_matches(rest, &path[skip..])),
+        Some((byte, rest)) => path.first() == Some(byte) && pattern_matches(rest, &path[1..]),
+    }
+}
+
+/// The path and query of `url`, as robots.txt rules see it.
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.find('/').map_or("/", |start| &rest[start..])
+}
+
+/// Runs at most one future per key at a time; concurrent callers with the
+/// same key wait for that future and share its output.
+#[derive(Debug)]
+pub struct InFlight<K, V> {
+    calls: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> Default for InFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> InFlight<K, V> {
+    pub async fn run<F, Fut>(&self, key: K, call: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = {
+            let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+            calls.entry(key.clone()).or_default().clone()
+        };
+        let value = cell.get_or_init(call).await.clone();
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        if calls
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            calls.remove(&key);
+        }
+        value
+    }
+}
+
+/// Fetches puzzle pages one at a time, at least `min_interval` (or the
+/// site's `Crawl-delay`, if longer) apart, sharing concurrent fetches of the
+/// same puzzle. Pages robots.txt disallows fail with
+/// [`CrawlError::Disallowed`]; robots.txt is read on the first fetch and
+/// again once a day, or a minute after a read that failed.
+pub struct PoliteCrawler {
+    min_interval: Duration,
+    politeness: Politeness,
+    user_agent: String,
+    robots: tokio::sync::Mutex<Option<RobotsTxt>>,
+    next_slot: tokio::sync::Mutex<Option<Instant>>,
+    in_flight: InFlight<PuzzleRef, Result<String, CrawlError>>,
+    cache: Option<Box<dyn PuzzleCacheBackend>>,
+    fetcher: HtmlFetcher,
+}
+
+impl std::fmt::Debug for PoliteCrawler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoliteCrawler")
+            .field("min_interval", &self.min_interval)
+            .field("politeness", &self.politeness)
+            .field("user_agent", &self.user_agent)
+            .field("cached", &self.cache.is_some())
+            .field("fetcher", &self.fetcher)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PoliteCrawler {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            politeness: Politeness::default(),
+            user_agent: user_agent(None),
+            robots: tokio::sync::Mutex::new(None),
+            next_slot: tokio::sync::Mutex::new(None),
+            in_flight: InFlight::default(),
+            cache: None,
+            fetcher: HtmlFetcher::direct().with_user_agent(user_agent(None)),
+        }
+    }
+
+    /// Download pages with `fetcher` instead of straight from nonograms.org.
+    pub fn with_fetcher(mut self, fetcher: HtmlFetcher) -> Self {
+        self.fetcher = fetcher.with_user_agent(self.user_agent.clone());
+        self
+    }
+
+    /// Give `contact`, a URL or mail address, in the `User-Agent`.
+    pub fn with_contact(mut self, contact: &str) -> Self {
+        self.user_agent = user_agent(Some(contact));
+        self.fetcher = self.fetcher.with_user_agent(self.user_agent.clone());
+        self
+    }
+
+    pub fn with_politeness(mut self, politeness: Politeness) -> Self {
+        self.politeness = politeness;
+        self
+    }
+
+    /// Serve pages from `cache` when it has them, and store every page
+    /// fetched.
+    pub fn with_cache(mut self, cache: impl PuzzleCacheBackend + 'static) -> Self {
+        self.cache = Some(Box::new(cache));
+        self
+    }
+
+    /// The cached page, without touching the network.
+    pub fn cached_html(&self, puzzle: &PuzzleRef) -> Option<String> {
+        self.cache.as_ref()?.get(puzzle)
+    }
+
+    /// `fetch_html`, paced, deduplicated and cached. Pages without puzzle
+    /// data are returned but not cached.
+    pub async fn fetch_html(&self, puzzle: &PuzzleRef) -> Result<String, CrawlError> {
+        if let Some(html) = self.cached_html(puzzle) {
+            return Ok(html);
+        }
+        self.fetch_uncached(puzzle).await
+    }
+
+    /// `fetch_html`, but a page without puzzle data, which is usually an
+    /// anti-bot interstitial served in its place, is fetched once more
+    /// before it is handed back, as [`HtmlFetcher::fetch_puzzle_page`] does.
+    /// The second request is also paced like any other.
+    pub async fn fetch_puzzle_page(&self, puzzle: &PuzzleRef) -> Result<String, CrawlError> {
+        let html = self.fetch_html(puzzle).await?;
+        if has_puzzle_data(&html) {
+            return Ok(html);
+        }
+        tracing::info!(id = %puzzle.id, "page has no puzzle data, fetching it again");
+        tokio::time::sleep(RETRY_DELAY).await;
+        self.fetch_uncached_with(puzzle, &self.fetcher.browser_like())
+            .await
+    }
+
+    async fn fetch_uncached(&self, puzzle: &PuzzleRef) -> Result<String, CrawlError> {
+        self.fetch_uncached_with(puzzle, &self.fetcher).await
+    }
+
+    async fn fetch_uncached_with(
+        &self,
+        puzzle: &PuzzleRef,
+        fetcher: &HtmlFetcher,
+    ) -> Result<String, CrawlError> {
+        self.in_flight
+            .run(puzzle.clone(), || async {
+                let robots = self.robots(Instant::now()).await;
+                let allowed = match robots.as_deref() {
+                    Some(robots) => robots_allow(robots, USER_AGENT, url_path(&puzzle.url())),
+                    None => self.politeness == Politeness::Standard,
+                };
+                if !allowed {
+                    tracing::info!(id = %puzzle.id, "robots.txt disallows the page");
+                    return Err(CrawlError::Disallowed);
+                }
+                self.wait_for_slot(robots.as_deref()).await;
+                let html = fetcher.fetch_html(puzzle).await?;
+                if let Some(cache) = self.cache.as_ref().filter(|_| has_puzzle_data(&html)) {
+                    cache.put(puzzle, &html);
+                }
+                Ok(html)
+            })
+            .await
+    }
+
+    /// robots.txt as of `now`, read again if it has expired. `None` if it
+    /// has never been read; a failed read keeps the rules read before it.
+    async fn robots(&self, now: Instant) -> Option<Arc<str>> {
+        let mut robots = self.robots.lock().await;
+        if let Some(read) = robots.as_ref().filter(|read| read.expires > now) {
+            return read.rules.clone();
+        }
+        let read = match self.fetcher.fetch_text(ROBOTS_URL).await {
+            Ok(rules) => {
+                let delay = crawl_delay(&rules, USER_AGENT);
+                tracing::info!(?delay, "read robots.txt");
+                RobotsTxt {
+                    rules: Some(rules.into()),
+                    expires: now + ROBOTS_TTL,
+                }
+            }
+            Err(CrawlError::NotFound) => RobotsTxt {
+                rules: Some("".into()),
+                expires: now + ROBOTS_TTL,
+            },
+            Err(err) => {
+                tracing::warn!(%err, retry = ?ROBOTS_RETRY, "cannot read robots.txt");
+                RobotsTxt {
+                    rules: robots.take().and_then(|stale| stale.rules),
+                    expires: now + ROBOTS_RETRY,
+                }
+            }
+        };
+        robots.insert(read).rules.clone()
+    }
+
+    async fn wait_for_slot(&self, robots: Option<&str>) {
+        let floor = match self.politeness {
+            Politeness::Standard => self.min_interval,
+            Politeness::Strict => self.min_interval.max(STRICT_INTERVAL),
+        };
+        let delay = robots
+            .and_then(|robots| crawl_delay(robots, USER_AGENT))
+            .map_or(floor, |delay| delay.max(floor));
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        let start = next_slot.map_or(now, |slot| slot.max(now));
+        *next_slot = Some(start + delay);
+        drop(next_slot);
+        if start > now {
+            tracing::debug!(wait = ?(start - now), "pacing crawl");
+            tokio::time::sleep(start - now).await;
+        }
+    }
+}
+
+/// The last read of robots.txt, good until `expires`.
+struct RobotsTxt {
+    /// `None` if it could not be read; a missing one has no rules.
+    rules: Option<Arc<str>>,
+    expires: Instant,
+}
+
+/// A small queue of puzzles to fetch ahead of time, drained by one
+/// background task so that "next puzzle" is served from the cache.
+///
+/// Prefetches never wait for budget: a puzzle that comes up while the
+/// budget is spent is dropped, since a user may need that token.
+#[derive(Debug)]
+pub struct Prefetcher {
+    capacity: usize,
+    queue: Mutex<VecDeque<PuzzleRef>>,
+    wake: Notify,
+}
+
+impl Prefetcher {
+    /// Keep at most `capacity` puzzles waiting; older requests give way.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            queue: Mutex::new(VecDeque::new()),
+            wake: Notify::new(),
+        }
+    }
+
+    /// Queue `puzzles` after those already waiting, skipping any that are.
+    pub fn request(&self, puzzles: impl IntoIterator<Item = PuzzleRef>) {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        for puzzle in puzzles {
+            if queue.contains(&puzzle) {
+                continue;
+            }
+            if queue.len() == self.capacity {
+                queue.pop_front();
+            }
+            queue.push_back(puzzle);
+        }
+        drop(queue);
+        self.wake.notify_one();
+    }
+
+    /// The oldest waiting puzzle, once there is one.
+    pub async fn next(&self) -> PuzzleRef {
+        loop {
+            let next = self
+                .queue
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .pop_front();
+            if let Some(puzzle) = next {
+                return puzzle;
+            }
+            self.wake.notified().await;
+        }
+    }
+
+    /// Fetch queued puzzles into `crawler`'s cache for as long as the server
+    /// runs. Cached pages are skipped and each fetch takes a token from
+    /// `budget` under `key`.
+    pub async fn run(&self, crawler: &PoliteCrawler, budget: &RateLimiter, key: &str) {
+        loop {
+            let puzzle = self.next().await;
+            if crawler.cached_html(&puzzle).is_some() {
+                continue;
+            }
+            if budget.check(key).is_err() {
+                tracing::debug!(id = %puzzle.id, "no budget to prefetch");
+                continue;
+            }
+            if let Err(err) = crawler.fetch_html(&puzzle).await {
+                tracing::debug!(id = %puzzle.id, %err, "prefetch failed");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn rate_limiter_allows_bursts_then_refills() {
+        let limiter = RateLimiter::new(2, 0.5);
+        let start = Instant::now();
+        assert!(limiter.check_at("a", start).is_ok());
+        assert!(limiter.check_at("a", start).is_ok());
+        let wait = limiter.check_at("a", start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(2));
+        assert!(limiter.check_at("b", start).is_ok());
+        assert!(
+            limiter
+                .check_at("a", start + Duration::from_secs(2))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn reads_crawl_delay_for_matching_agent() {
+        let robots = "\
+User-agent: Googlebot
+Crawl-delay: 1
+
+# everyone else
+User-agent: *
+Disallow: /admin/
+Crawl-delay: 5
+";
+        assert_eq!(
+            crawl_delay(robots, "nonogram-solver"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            crawl_delay(robots, "Googlebot/2.1"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(crawl_delay("User-agent: *\nDisallow:", "x"), None);
+        assert_eq!(crawl_delay("Crawl-delay: nope", "x"), None);
+    }
+
+    #[test]
+    fn robots_rules_pick_the_longest_match_in_the_agents_group() {
+        let robots = "\
+User-agent: *
+Disallow: /
+
+User-agent: Googlebot
+User-agent: nonogram-solver
+Disallow: /nonograms2/
+Allow: /nonograms2/i/
+Disallow: /*.php$
+";
+        let allow = |path| robots_allow(robots, USER_AGENT, path);
+        assert!(allow("/nonograms2/i/19048"));
+        assert!(!allow("/nonograms2/"));
+        assert!(allow("/about"));
+        assert!(!allow("/search.php"));
+        assert!(allow("/search.php?q=cat"));
+        assert!(!robots_allow(robots, "OtherBot", "/about"));
+        assert!(robots_allow("", USER_AGENT, "/"));
+        assert!(robots_allow("User-agent: *\nDisallow:", USER_AGENT, "/"));
+        assert_eq!(
+            url_path("https://www.nonograms.org/nonograms/i/7"),
+            "/nonograms/i/7"
+        );
+        assert_eq!(url_path("https://www.nonograms.org"), "/");
+    }
+
+    #[tokio::test]
+    async fn strict_politeness_needs_robots_txt() {
+        use crate::puzzle_crawler::PuzzleKind;
+
+        // Nothing listens on port 1, so robots.txt cannot be read.
+        let crawler = PoliteCrawler::new(Duration::ZERO)
+            .with_fetcher(HtmlFetcher::via_proxy("http://127.0.0.1:1/"))
+            .with_contact("https://example.org/bot")
+            .with_politeness(Politeness::Strict);
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::Color, "1");
+        assert!(matches!(
+            crawler.fetch_html(&puzzle).await,
+            Err(CrawlError::Disallowed)
+        ));
+    }
+
+    /// Serve HTTP on a free local port, answering each request with the
+    /// status and body `respond` gives for its head (request line and
+    /// headers). Returns a proxy URL for [`HtmlFetcher::via_proxy`].
+    fn mock_site(mut respond: impl FnMut(&str) -> (u16, &'static str) + Send + 'static) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().map_while(Result::ok) {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                while reader.read_line(&mut head).is_ok_and(|read| read > 2) {}
+                let (status, body) = respond(&head);
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test]
+    async fn rereads_robots_txt_after_a_failed_read() {
+        use crate::puzzle_crawler::PuzzleKind;
+
+        // robots.txt fails once, then disallows everything.
+        let mut robots_reads = 0;
+        let proxy = mock_site(move |head| {
+            if head.contains("robots.txt") {
+                robots_reads += 1;
+                match robots_reads {
+                    1 => (500, ""),
+                    _ => (200, "User-agent: *\nDisallow: /"),
+                }
+            } else {
+                (200, "<script>var d=[[1,2,3,4]];</script>")
+            }
+        });
+        let crawler =
+            PoliteCrawler::new(Duration::ZERO).with_fetcher(HtmlFetcher::via_proxy(proxy));
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::Color, "1");
+
+        // Standard politeness crawls while robots.txt cannot be read...
+        let start = Instant::now();
+        assert!(crawler.robots(start).await.is_none());
+        assert!(crawler.fetch_html(&puzzle).await.is_ok());
+        // ...and reads it again once the retry delay has passed.
+        let later = start + ROBOTS_RETRY;
+        assert_eq!(
+            crawler.robots(later).await.as_deref(),
+            Some("User-agent: *\nDisallow: /")
+        );
+        assert!(matches!(
+            crawler.fetch_html(&puzzle).await,
+            Err(CrawlError::Disallowed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn refetches_pages_without_puzzle_data() {
+        use crate::puzzle_cache::MemoryCache;
+        use crate::puzzle_crawler::PuzzleKind;
+
+        // A site with no robots.txt that serves each puzzle page as an
+        // interstitial to the first request for it.
+        let page_agents = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&page_agents);
+        let proxy = mock_site(move |head| {
+            if head.contains("robots.txt") {
+                return (404, "");
+            }
+            let agent = head
+                .lines()
+                .find_map(|line| line.strip_prefix("user-agent: "))
+                .unwrap_or_default();
+            let mut seen = seen.lock().unwrap();
+            seen.push(agent.to_string());
+            match seen.len() % 2 {
+                1 => (200, "<p>Checking your browser...</p>"),
+                _ => (200, "<script>var d=[[1,2,3,4]];</script>"),
+            }
+        });
+        let agents = || std::mem::take(&mut *page_agents.lock().unwrap());
+
+        let crawler = PoliteCrawler::new(Duration::ZERO)
+            .with_fetcher(HtmlFetcher::via_proxy(proxy.clone()))
+            .with_cache(MemoryCache::new(4));
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::Color, "1");
+        let started = Instant::now();
+        let html = crawler.fetch_puzzle_page(&puzzle).await.unwrap();
+        assert!(has_puzzle_data(&html));
+        assert!(started.elapsed() >= RETRY_DELAY);
+        assert_eq!(crawler.cached_html(&puzzle), Some(html));
+        let own = user_agent(None);
+        assert_eq!(
+            agents(),
+            [own.clone(), format!("Mozilla/5.0 (compatible; {own})")]
+        );
+
+        let fetcher = HtmlFetcher::via_proxy(proxy).with_user_agent("probe/1");
+        assert!(has_puzzle_data(
+            &fetcher.fetch_puzzle_page(&puzzle).await.unwrap()
+        ));
+        assert_eq!(agents(), ["probe/1", "Mozilla/5.0 (compatible; probe/1)"]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_share_one_run() {
+        let in_flight = InFlight::<u32, u32>::default();
+        let runs = AtomicUsize::new(0);
+        let call = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            7
+        };
+        let (a, b) = tokio::join!(in_flight.run(1, call), in_flight.run(1, call));
+        assert_eq!((a, b), (7, 7));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(in_flight.run(1, call).await, 7);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn prefetch_queue_skips_duplicates_and_drops_the_oldest() {
+        use crate::puzzle_crawler::PuzzleKind;
+
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::Color, "10");
+        let prefetcher = Prefetcher::new(2);
+        prefetcher.request(
+            [-1, 1, 1]
+                .into_iter()
+                .filter_map(|delta| puzzle.offset(delta)),
+        );
+        assert_eq!(prefetcher.next().await.id, "9");
+        prefetcher.request([puzzle.offset(5).unwrap(), puzzle.offset(6).unwrap()]);
+        assert_eq!(prefetcher.next().await.id, "15");
+        assert_eq!(prefetcher.next().await.id, "16");
+        assert_eq!(puzzle.offset(-10), None);
+    }
+}
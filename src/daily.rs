@@ -0,0 +1,151 @@
+//! Puzzle of the day: the server picks one small, line-solvable puzzle per UTC
+//! day and caches it, so every visitor sees the same one.
+
+use dioxus::prelude::*;
+use nonogram_solver::puzzle_crawler::PuzzleMeta;
+#[cfg(feature = "server")]
+use nonogram_solver::puzzle_crawler::{PuzzleKind, PuzzleRef};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+/// Largest row or column count for a daily pick; it should fit a phone screen.
+const MAX_SIDE: usize = 30;
+#[cfg(feature = "server")]
+/// Page fetches to try before giving up on today.
+const MAX_ATTEMPTS: u64 = 6;
+#[cfg(feature = "server")]
+/// Puzzle ids are drawn from `1..=MAX_ID`; gaps in the site's numbering are skipped.
+const MAX_ID: u64 = 75_000;
+
+/// Today's puzzle, as the landing card needs it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyPick {
+    /// UTC date, `YYYY-MM-DD`.
+    pub date: String,
+    /// What to put in the input box to open it, e.g. `56215` or `bw:1822`.
+    pub input: String,
+    pub meta: PuzzleMeta,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// The puzzle of the day, or `None` if no candidate could be fetched today.
+#[get("/api/daily")]
+pub async fn daily_puzzle() -> Result<Option<DailyPick>, ServerFnError> {
+    use std::sync::LazyLock;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use nonogram_solver::nonogram_solver::{SolverOptions, solve_puzzle_steps_with};
+    use nonogram_solver::puzzle_crawler::{parse_puzzle, parse_puzzle_meta};
+
+    static TODAY: LazyLock<tokio::sync::Mutex<Option<(u64, DailyPick)>>> =
+        LazyLock::new(|| tokio::sync::Mutex::new(None));
+
+    let day = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86_400);
+    // Held across the fetches so concurrent visitors wait for one pick.
+    let mut today = TODAY.lock().await;
+    if let Some((cached_day, pick)) = &*today
+        && *cached_day == day
+    {
+        return Ok(Some(pick.clone()));
+    }
+
+    let options = SolverOptions {
+        max_side: MAX_SIDE,
+        ..SolverOptions::web()
+    };
+    for (kind, id) in candidates(day) {
+        let id = id.to_string();
+        let puzzle = PuzzleRef::nonograms_org(kind, id.clone());
+        let Ok(html) = crate::crawl_limits::CRAWLER
+            .fetch_puzzle_page(&puzzle)
+            .await
+        else {
+            continue;
+        };
+        let Ok(data) = parse_puzzle(kind, &html) else {
+            continue;
+        };
+        // Line logic alone must finish it: no guessing on a casual puzzle.
+        match solve_puzzle_steps_with(data.clone(), &options) {
+            Ok(steps) if steps.stats.solved => {}
+            _ => continue,
+        }
+        let pick = DailyPick {
+            date: date_label(day),
+            input: match kind {
+                PuzzleKind::BlackWhite => format!("bw:{id}"),
+                PuzzleKind::Color => id.clone(),
+            },
+            meta: parse_puzzle_meta(kind, &id, &html),
+            rows: data.row_groups.len(),
+            cols: data.col_groups.len(),
+        };
+        tracing::info!(day, input = %pick.input, "picked puzzle of the day");
+        *today = Some((day, pick.clone()));
+        return Ok(Some(pick));
+    }
+    tracing::warn!(day, "no puzzle of the day found");
+    Ok(None)
+}
+
+#[cfg(feature = "server")]
+/// Puzzles to try for `day`, in order; the same day always yields the same list.
+fn candidates(day: u64) -> impl Iterator<Item = (PuzzleKind, u64)> {
+    (0..MAX_ATTEMPTS).map(move |attempt| {
+        let hash = splitmix64(day.wrapping_mul(MAX_ATTEMPTS).wrapping_add(attempt));
+        let kind = if day.is_multiple_of(2) {
+            PuzzleKind::Color
+        } else {
+            PuzzleKind::BlackWhite
+        };
+        (kind, hash % MAX_ID + 1)
+    })
+}
+
+#[cfg(feature = "server")]
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(feature = "server")]
+/// `YYYY-MM-DD` for a count of days since 1970-01-01 (Howard Hinnant's
+/// `civil_from_days`).
+fn date_label(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_are_stable_and_in_range() {
+        let today: Vec<_> = candidates(20_000).collect();
+        assert_eq!(today, candidates(20_000).collect::<Vec<_>>());
+        assert_ne!(today, candidates(20_001).collect::<Vec<_>>());
+        assert_eq!(today.len() as u64, MAX_ATTEMPTS);
+        assert!(today.iter().all(|&(_, id)| (1..=MAX_ID).contains(&id)));
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(date_label(0), "1970-01-01");
+        assert_eq!(date_label(11_016), "2000-02-29");
+        assert_eq!(date_label(20_742), "2026-10-16");
+    }
+}
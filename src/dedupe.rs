@@ -0,0 +1,187 @@
+//! Recognizing the same puzzle under a different palette order or source.
+//!
+//! A puzzle is reduced to a canonical form of its clues: colors are
+//! renumbered in the order the clues first use them, so the palette order
+//! and unused entries do not matter, and optionally the form is the least of
+//! all eight turns and flips. [`PuzzleData::canonical_hash`] hashes that
+//! form; [`duplicate_groups`] compares the forms themselves, so hash
+//! collisions never merge different puzzles.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::color::Color;
+use crate::puzzle_crawler::{GroupLen, PuzzleData};
+
+/// What two puzzles may differ in and still count as the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalForm {
+    /// Compare the palette's colors, not just which groups share a color.
+    /// Off for archives whose sources draw the same puzzle in other shades.
+    pub keep_colors: bool,
+    /// Treat a puzzle, its transpose and their mirror images as one.
+    pub any_orientation: bool,
+}
+
+impl Default for CanonicalForm {
+    fn default() -> Self {
+        Self {
+            keep_colors: true,
+            any_orientation: false,
+        }
+    }
+}
+
+impl PuzzleData {
+    /// A hash of the clues in canonical `form`, stable across builds and
+    /// platforms: equal for puzzles that differ only in palette order (and,
+    /// as configured, in colors or orientation).
+    pub fn canonical_hash(&self, form: &CanonicalForm) -> u64 {
+        fnv1a(&canonical_words(self, form))
+    }
+}
+
+/// Keys of the puzzles that share a canonical form with another, one list
+/// per puzzle in the order the puzzles were first seen; puzzles without a
+/// duplicate are left out.
+pub fn duplicate_groups<'a, K>(
+    puzzles: impl IntoIterator<Item = (K, &'a PuzzleData)>,
+    form: &CanonicalForm,
+) -> Vec<Vec<K>> {
+    let mut index: BTreeMap<Vec<u64>, usize> = BTreeMap::new();
+    let mut groups: Vec<Vec<K>> = Vec::new();
+    for (key, data) in puzzles {
+        let slot = *index
+            .entry(canonical_words(data, form))
+            .or_insert(groups.len());
+        if slot == groups.len() {
+            groups.push(vec![key]);
+        } else {
+            groups[slot].push(key);
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// The canonical form as a word list; the least of every allowed
+/// orientation, so any one of them gives the same list.
+fn canonical_words(data: &PuzzleData, form: &CanonicalForm) -> Vec<u64> {
+    if !form.any_orientation {
+        return words(data, form.keep_colors);
+    }
+    let turned = data.transpose();
+    [data.clone(), turned]
+        .into_iter()
+        .flat_map(|data| {
+            let flipped = data.mirror_h();
+            [data.mirror_v(), flipped.mirror_v(), flipped, data]
+        })
+        .map(|data| words(&data, form.keep_colors))
+        .min()
+        .unwrap_or_default()
+}
+
+/// Sizes, then every clue as its group count and `(length, color)` pairs
+/// with colors renumbered by first use, then the renumbered palette.
+fn words(data: &PuzzleData, keep_colors: bool) -> Vec<u64> {
+    let mut renumbered: Vec<usize> = Vec::new();
+    let mut words = vec![data.row_groups.len() as u64, data.col_groups.len() as u64];
+    for clue in data.row_groups.iter().chain(&data.col_groups) {
+        words.push(clue.len() as u64);
+        for group in clue {
+            match group.len {
+                GroupLen::Exact(len) => words.extend([0, len as u64]),
+                GroupLen::Unknown => words.push(1),
+                GroupLen::Range(min, max) => words.extend([2, min as u64, max as u64]),
+            }
+            let color = match renumbered.iter().position(|&id| id == group.color_id) {
+                Some(color) => color,
+                None => {
+                    renumbered.push(group.color_id);
+                    renumbered.len() - 1
+                }
+            };
+            words.push(color as u64);
+        }
+    }
+    if keep_colors {
+        words.extend(renumbered.iter().map(|&id| {
+            let color = data.color_panel.get(id).map_or("", String::as_str);
+            match color.parse::<Color>() {
+                Ok(color) => {
+                    let [r, g, b] = color.channels();
+                    u64::from_be_bytes([0, 0, 0, 0, 0, r, g, b])
+                }
+                // Out of the RGB range, so it cannot match a parsed color.
+                Err(_) => fnv1a(&color.bytes().map(u64::from).collect::<Vec<_>>()) | 1 << 63,
+            }
+        }));
+    }
+    words
+}
+
+/// FNV-1a over the little-endian bytes of `words`.
+fn fnv1a(words: &[u64]) -> u64 {
+    words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Picture:  r r .
+    //           . b b
+    fn puzzle(palette: [&str; 3], red: usize, blue: usize) -> PuzzleData {
+        PuzzleData::builder()
+            .palette(palette)
+            .row([(2, red)])
+            .row([(2, blue)])
+            .col([(1, red)])
+            .col([(1, red), (1, blue)])
+            .col([(1, blue)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn palette_order_and_orientation_do_not_split_duplicates() {
+        let original = puzzle(["#ffffff", "#ff0000", "#0000ff"], 1, 2);
+        let reordered = puzzle(["#FFF", "#0000ff", "#ff0000"], 2, 1);
+        let reshaded = puzzle(["#ffffff", "#cc0000", "#0000cc"], 1, 2);
+        let form = CanonicalForm::default();
+        assert_eq!(
+            original.canonical_hash(&form),
+            reordered.canonical_hash(&form)
+        );
+        assert_ne!(
+            original.canonical_hash(&form),
+            reshaded.canonical_hash(&form)
+        );
+
+        let any = CanonicalForm {
+            keep_colors: false,
+            any_orientation: true,
+        };
+        let turned = original.transpose().mirror_h();
+        assert_ne!(original.canonical_hash(&form), turned.canonical_hash(&form));
+        assert_eq!(original.canonical_hash(&any), turned.canonical_hash(&any));
+        assert_eq!(original.canonical_hash(&any), reshaded.canonical_hash(&any));
+
+        let archive = [
+            ("a", &original),
+            ("b", &reshaded),
+            ("c", &reordered),
+            ("d", &turned),
+        ];
+        assert_eq!(duplicate_groups(archive, &form), [["a", "c"]]);
+        assert_eq!(duplicate_groups(archive, &any), [["a", "b", "c", "d"]]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Numbers describing how hard a puzzle is, for training difficulty models
+//! against a site's own ratings.
+//!
+//! [`difficulty_features`] flattens a puzzle's clue statistics and the
+//! counters of its solve into named numbers; [`features_csv`] and
+//! [`features_jsonl`] write a batch of them out with a key per puzzle, so
+//! the training itself can happen in whatever tool the reader prefers.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Write};
+
+use crate::nonogram_solver::SolveStats;
+use crate::puzzle_crawler::PuzzleData;
+use crate::puzzle_stats::puzzle_stats;
+
+/// Named features of one puzzle and its solve, in the order of
+/// [`FeatureVector::NAMES`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureVector {
+    pub values: [f64; FeatureVector::NAMES.len()],
+}
+
+impl FeatureVector {
+    /// Column names, stable across releases; new features are appended.
+    pub const NAMES: [&'static str; 15] = [
+        "rows",
+        "cols",
+        "colors",
+        "ink_density",
+        "color_entropy",
+        "line_entropy",
+        "mean_group_len",
+        "groups",
+        "max_groups_per_line",
+        "passes",
+        "line_solves",
+        "line_solves_per_line",
+        "max_line_solves",
+        "cells_from_cols_share",
+        "solved",
+    ];
+
+    /// The feature called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        let idx = Self::NAMES.iter().position(|&known| known == name)?;
+        Some(self.values[idx])
+    }
+
+    /// `(name, value)` pairs in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, f64)> + '_ {
+        Self::NAMES.into_iter().zip(self.values.iter().copied())
+    }
+}
+
+/// Features of `data` and of `stats`, the counters of its solve. `solved`
+/// is 1 when line logic alone finished, 0 when a guess was needed.
+pub fn difficulty_features(data: &PuzzleData, stats: &SolveStats) -> FeatureVector {
+    let clues = puzzle_stats(data);
+    let lines = clues.rows + clues.cols;
+    let groups: usize = data
+        .row_groups
+        .iter()
+        .chain(&data.col_groups)
+        .map(Vec::len)
+        .sum();
+    let max_line_solves = stats
+        .row_effort
+        .iter()
+        .chain(&stats.col_effort)
+        .map(|effort| effort.solves)
+        .max()
+        .unwrap_or(0);
+    let determined = stats.cells_from_rows + stats.cells_from_cols;
+    let ratio = |part: usize, whole: usize| {
+        if whole == 0 {
+            0.0
+        } else {
+            part as f64 / whole as f64
+        }
+    };
+    FeatureVector {
+        values: [
+            clues.rows as f64,
+            clues.cols as f64,
+            data.color_panel.len() as f64,
+            1.0 - clues.density.first().copied().unwrap_or(1.0),
+            clues.color_entropy,
+            clues.line_entropy,
+            clues.mean_group_len,
+            groups as f64,
+            clues.group_counts.len().saturating_sub(1) as f64,
+            stats.passes as f64,
+            stats.line_solves as f64,
+            ratio(stats.line_solves, lines),
+            max_line_solves as f64,
+            ratio(stats.cells_from_cols, determined),
+            if stats.solved { 1.0 } else { 0.0 },
+        ],
+    }
+}
+
+/// A header row of `key` and the feature names, then one row per puzzle.
+/// Keys with commas, quotes or line breaks are quoted.
+pub fn features_csv<K: Display>(rows: impl IntoIterator<Item = (K, FeatureVector)>) -> String {
+    let mut csv = String::from("key");
+    for name in FeatureVector::NAMES {
+        csv.push(',');
+        csv.push_str(name);
+    }
+    csv.push('\n');
+    for (key, features) in rows {
+        let key = format!("{key}");
+        if key.contains([',', '"', '\n', '\r']) {
+            let _ = write!(csv, "\"{}\"", key.replace('"', "\"\""));
+        } else {
+            csv.push_str(&key);
+        }
+        for value in features.values {
+            let _ = write!(csv, ",{value}");
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// One JSON object per line: `"key"` and every feature by name. Values that
+/// are not finite are written as `null`.
+pub fn features_jsonl<K: Display>(rows: impl IntoIterator<Item = (K, FeatureVector)>) -> String {
+    let mut jsonl = String::new();
+    for (key, features) in rows {
+        jsonl.push_str("{\"key\":\"");
+        for ch in format!("{key}").chars() {
+            match ch {
+                '"' => jsonl.push_str("\\\""),
+                '\\' => jsonl.push_str("\\\\"),
+                ch if u32::from(ch) < 0x20 => {
+                    let _ = write!(jsonl, "\\u{:04x}", u32::from(ch));
+                }
+                ch => jsonl.push(ch),
+            }
+        }
+        jsonl.push('"');
+        for (name, value) in features.iter() {
+            if value.is_finite() {
+                let _ = write!(jsonl, ",\"{name}\":{value}");
+            } else {
+                let _ = write!(jsonl, ",\"{name}\":null");
+            }
+        }
+        jsonl.push_str("}\n");
+    }
+    jsonl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle_steps;
+
+    #[test]
+    fn exports_features_of_a_solve() {
+        // Picture:  # .
+        //           # #
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .row([(2, 1)])
+            .col([(2, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let stats = solve_puzzle_steps(data.clone()).unwrap().stats;
+        let features = difficulty_features(&data, &stats);
+        assert_eq!(features.get("rows"), Some(2.0));
+        assert_eq!(features.get("ink_density"), Some(0.75));
+        assert_eq!(features.get("max_groups_per_line"), Some(1.0));
+        assert_eq!(features.get("solved"), Some(1.0));
+        assert_eq!(features.get("line_solves"), Some(stats.line_solves as f64));
+        assert_eq!(features.get("guesses"), None);
+
+        let batch = [("a,b", features.clone()), ("c\"d", features)];
+        let csv = features_csv(batch.clone());
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("key,rows,cols,colors,"));
+        assert!(lines.next().unwrap().starts_with("\"a,b\",2,2,2,0.75,"));
+        assert!(lines.next().unwrap().starts_with("\"c\"\"d\",2,"));
+
+        let jsonl = features_jsonl(batch);
+        assert_eq!(jsonl.lines().count(), 2);
+        assert!(jsonl.starts_with("{\"key\":\"a,b\",\"rows\":2,\"cols\":2,"));
+        assert!(jsonl.contains("{\"key\":\"c\\\"d\","));
+        assert!(jsonl.lines().all(|line| line.ends_with("\"solved\":1}")));
+    }
+}
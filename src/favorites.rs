@@ -0,0 +1,89 @@
+//! Starred puzzles, persisted in `localStorage`.
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{Locale, Msg};
+use crate::local_store;
+use nonogram_solver::puzzle_crawler::PuzzleMeta;
+
+const STORAGE_KEY: &str = "nonogram-solver.favorites";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub id: String,
+    pub title: Option<String>,
+    /// Page URL; pasting it into the input box reopens the puzzle.
+    pub url: String,
+}
+
+impl Favorite {
+    pub fn from_meta(meta: &PuzzleMeta) -> Self {
+        Self {
+            id: meta.id.clone(),
+            title: meta.title.clone(),
+            url: meta.source_url.clone(),
+        }
+    }
+
+    pub fn label(&self, locale: Locale) -> String {
+        self.title
+            .clone()
+            .unwrap_or_else(|| locale.format(Msg::PuzzleFallbackTitle, &[&self.id]))
+    }
+}
+
+/// Shared favorites list, loaded from storage once the app is mounted in the browser.
+pub fn use_favorites_provider() -> Signal<Vec<Favorite>> {
+    let mut favorites = use_context_provider(|| Signal::new(Vec::<Favorite>::new()));
+    use_effect(move || {
+        spawn(async move {
+            if let Some(saved) = local_store::load::<Vec<Favorite>>(STORAGE_KEY).await {
+                favorites.set(saved);
+            }
+        });
+    });
+    favorites
+}
+
+pub fn use_favorites() -> Signal<Vec<Favorite>> {
+    use_context::<Signal<Vec<Favorite>>>()
+}
+
+pub fn is_favorite(favorites: &[Favorite], url: &str) -> bool {
+    favorites.iter().any(|favorite| favorite.url == url)
+}
+
+pub fn toggle(mut favorites: Signal<Vec<Favorite>>, favorite: Favorite) {
+    {
+        let mut list = favorites.write();
+        if let Some(pos) = list.iter().position(|f| f.url == favorite.url) {
+            list.remove(pos);
+        } else {
+            list.push(favorite);
+        }
+    }
+    local_store::save(STORAGE_KEY, &*favorites.read());
+}
+
+/// Add imported entries that are not already starred, keeping existing order.
+pub fn merge(mut favorites: Signal<Vec<Favorite>>, imported: Vec<Favorite>) {
+    {
+        let mut list = favorites.write();
+        for favorite in imported {
+            if !is_favorite(&list, &favorite.url) {
+                list.push(favorite);
+            }
+        }
+    }
+    local_store::save(STORAGE_KEY, &*favorites.read());
+}
+
+pub fn remove(mut favorites: Signal<Vec<Favorite>>, url: &str) {
+    favorites.write().retain(|favorite| favorite.url != url);
+    local_store::save(STORAGE_KEY, &*favorites.read());
+}
+
+pub fn export(favorites: &[Favorite]) {
+    local_store::download_json("nonogram-favorites.json", &favorites);
+}
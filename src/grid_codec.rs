@@ -0,0 +1,336 @@
+//! Compact binary encoding of grids and of the changes between solve steps,
+//! for sending steps where JSON is too bulky: a 100×100 step is tens of
+//! kilobytes of mask numbers as JSON, but a bitmap and a few bytes per
+//! changed cell here.
+//!
+//! Numbers are unsigned LEB128 varints. A grid is its row and column
+//! counts, a bitmap of the determined cells (row-major, least significant
+//! bit first), the color ids of those cells and then the masks of the
+//! others. A delta is a bitmap of the cells that changed and, for each,
+//! its new color id shifted left by one, or its new mask shifted left by
+//! one with the low bit set if it is still undetermined.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::nonogram_solver::{Grid, mask_to_color_index};
+
+/// Why bytes do not decode to a grid or delta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes end in the middle of a value.
+    Truncated,
+    /// A varint runs past 64 bits.
+    Overflow,
+    /// A color id or mask that no cell mask can hold.
+    BadCell,
+    /// Bytes are left over after the last value.
+    TrailingBytes,
+    /// A grid has rows but no columns, or columns but no rows.
+    BadShape,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "encoded grid is truncated"),
+            Self::Overflow => write!(f, "number in encoded grid is too large"),
+            Self::BadCell => write!(f, "encoded grid has an invalid cell"),
+            Self::TrailingBytes => write!(f, "bytes left over after encoded grid"),
+            Self::BadShape => write!(f, "encoded grid has rows or columns but not both"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Encode a rectangular `grid`, as the solver produces them. Rows without
+/// columns do not decode.
+pub fn encode_grid(grid: &[Vec<u64>]) -> Vec<u8> {
+    let cols = grid.first().map_or(0, Vec::len);
+    let mut out = Vec::new();
+    push_varint(&mut out, grid.len() as u64);
+    push_varint(&mut out, cols as u64);
+    let cells = || grid.iter().flatten().copied();
+    out.extend(bitmap(
+        cells().map(|mask| mask_to_color_index(mask).is_some()),
+    ));
+    for color in cells().filter_map(mask_to_color_index) {
+        push_varint(&mut out, color as u64);
+    }
+    for mask in cells().filter(|&mask| mask_to_color_index(mask).is_none()) {
+        push_varint(&mut out, mask);
+    }
+    out
+}
+
+pub fn decode_grid(bytes: &[u8]) -> Result<Grid, DecodeError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let rows = reader.size()?;
+    let cols = reader.size()?;
+    // Empty rows would cost nothing to send but a `Vec` each to decode, and
+    // again for every step in `decode_steps`.
+    if (rows == 0) != (cols == 0) {
+        return Err(DecodeError::BadShape);
+    }
+    let cells = rows.checked_mul(cols).ok_or(DecodeError::Truncated)?;
+    let determined = reader.bitmap(cells)?;
+    let mut masks = vec![0u64; cells];
+    for (mask, _) in masks.iter_mut().zip(&determined).filter(|(_, set)| **set) {
+        *mask = color_mask(reader.varint()?)?;
+    }
+    for (mask, _) in masks.iter_mut().zip(&determined).filter(|(_, set)| !**set) {
+        *mask = reader.varint()?;
+        if mask_to_color_index(*mask).is_some() {
+            return Err(DecodeError::BadCell);
+        }
+    }
+    reader.finish()?;
+    if cols == 0 {
+        return Ok(Vec::new());
+    }
+    Ok(masks.chunks(cols).map(<[u64]>::to_vec).collect())
+}
+
+/// Encode the cells that differ from `before` in `after`, two grids of the
+/// same shape.
+pub fn encode_delta(before: &[Vec<u64>], after: &[Vec<u64>]) -> Vec<u8> {
+    let pairs = || before.iter().flatten().zip(after.iter().flatten());
+    let mut out = bitmap(pairs().map(|(old, new)| old != new));
+    for (_, &mask) in pairs().filter(|(old, new)| old != new) {
+        push_varint(&mut out, cell_code(mask));
+    }
+    out
+}
+
+/// Apply a delta from [`encode_delta`] to `grid`, which must be the grid it
+/// was taken from. On error `grid` is left unchanged.
+pub fn apply_delta(grid: &mut Grid, bytes: &[u8]) -> Result<(), DecodeError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let cells = grid.iter().map(Vec::len).sum();
+    let changed = reader.bitmap(cells)?;
+    let mut updates = Vec::new();
+    for (idx, _) in changed.iter().enumerate().filter(|(_, set)| **set) {
+        updates.push((idx, decode_cell(reader.varint()?)?));
+    }
+    reader.finish()?;
+    let mut cells: Vec<&mut u64> = grid.iter_mut().flatten().collect();
+    for (idx, mask) in updates {
+        *cells[idx] = mask;
+    }
+    Ok(())
+}
+
+/// Encode every step: the first as a grid, each later one as a delta from
+/// the one before, each part prefixed by its length.
+pub fn encode_steps(steps: &[Grid]) -> Vec<u8> {
+    let mut out = Vec::new();
+    push_varint(&mut out, steps.len() as u64);
+    let mut previous: Option<&Grid> = None;
+    for step in steps {
+        let part = match previous {
+            None => encode_grid(step),
+            Some(before) => encode_delta(before, step),
+        };
+        push_varint(&mut out, part.len() as u64);
+        out.extend(part);
+        previous = Some(step);
+    }
+    out
+}
+
+pub fn decode_steps(bytes: &[u8]) -> Result<Vec<Grid>, DecodeError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let count = reader.size()?;
+    let mut steps: Vec<Grid> = Vec::new();
+    for _ in 0..count {
+        let len = reader.size()?;
+        let part = reader.take(len)?;
+        let step = match steps.last() {
+            None => decode_grid(part)?,
+            Some(before) => {
+                let mut step = before.clone();
+                apply_delta(&mut step, part)?;
+                step
+            }
+        };
+        steps.push(step);
+    }
+    reader.finish()?;
+    Ok(steps)
+}
+
+fn push_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn bitmap(bits: impl Iterator<Item = bool>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (idx, bit) in bits.enumerate() {
+        if idx % 8 == 0 {
+            out.push(0);
+        }
+        out[idx / 8] |= u8::from(bit) << (idx % 8);
+    }
+    out
+}
+
+fn cell_code(mask: u64) -> u64 {
+    match mask_to_color_index(mask) {
+        Some(color) => (color as u64) << 1,
+        None => mask << 1 | 1,
+    }
+}
+
+fn decode_cell(code: u64) -> Result<u64, DecodeError> {
+    if code & 1 == 0 {
+        color_mask(code >> 1)
+    } else {
+        Ok(code >> 1)
+    }
+}
+
+fn color_mask(color: u64) -> Result<u64, DecodeError> {
+    u32::try_from(color)
+        .ok()
+        .and_then(|color| 1u64.checked_shl(color))
+        .ok_or(DecodeError::BadCell)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.pos).ok_or(DecodeError::Truncated)?;
+            self.pos += 1;
+            let bits = u64::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                return Err(DecodeError::Overflow);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Overflow)
+    }
+
+    /// A count or length; anything past the input could not be backed by
+    /// data, which keeps hostile input from forcing a huge allocation.
+    fn size(&mut self) -> Result<usize, DecodeError> {
+        let value = self.varint()?;
+        let bound = (self.bytes.len() as u64).saturating_mul(8).max(1 << 16);
+        usize::try_from(value)
+            .ok()
+            .filter(|_| value <= bound)
+            .ok_or(DecodeError::Truncated)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError::Truncated)?;
+        let part = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(DecodeError::Truncated)?;
+        self.pos = end;
+        Ok(part)
+    }
+
+    fn bitmap(&mut self, bits: usize) -> Result<Vec<bool>, DecodeError> {
+        let bytes = self.take(bits.div_ceil(8))?;
+        Ok((0..bits)
+            .map(|idx| bytes[idx / 8] >> (idx % 8) & 1 != 0)
+            .collect())
+    }
+
+    fn finish(&self) -> Result<(), DecodeError> {
+        if self.pos == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle_steps;
+    use crate::puzzle_crawler::PuzzleData;
+
+    #[test]
+    fn steps_round_trip_and_shrink() {
+        // Picture:  r r . b
+        //           . r b b
+        //           b . r r
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(2, 1), (1, 2)])
+            .row([(1, 1), (2, 2)])
+            .row([(1, 2), (2, 1)])
+            .col([(1, 1), (1, 2)])
+            .col([(2, 1)])
+            .col([(1, 2), (1, 1)])
+            .col([(2, 2), (1, 1)])
+            .build()
+            .unwrap();
+        let steps = solve_puzzle_steps(data).unwrap().steps;
+        assert!(steps.len() > 1);
+        for step in &steps {
+            assert_eq!(decode_grid(&encode_grid(step)).as_ref(), Ok(step));
+        }
+        let encoded = encode_steps(&steps);
+        assert_eq!(decode_steps(&encoded), Ok(steps.clone()));
+
+        let mut grid = steps[0].clone();
+        apply_delta(&mut grid, &encode_delta(&steps[0], &steps[1])).unwrap();
+        assert_eq!(grid, steps[1]);
+
+        let big = vec![vec![0b111u64; 100]; 100];
+        let mut solved = big.clone();
+        solved[50] = vec![0b010; 100];
+        // One byte per eight cells, then one per changed cell.
+        assert_eq!(encode_delta(&big, &solved).len(), 1250 + 100);
+        assert_eq!(encode_grid(&big).len(), 2 + 1250 + 10_000);
+
+        assert_eq!(decode_grid(&[]), Err(DecodeError::Truncated));
+        assert_eq!(decode_grid(&[1, 1, 0, 0x80]), Err(DecodeError::Truncated));
+        assert_eq!(decode_grid(&[1, 1, 1, 64]), Err(DecodeError::BadCell));
+        assert_eq!(
+            decode_grid(&[1, 1, 1, 2, 0]),
+            Err(DecodeError::TrailingBytes)
+        );
+        assert_eq!(
+            decode_grid(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            decode_grid(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            Err(DecodeError::Overflow)
+        );
+        // 65536 empty rows, then a thousand empty deltas from them.
+        let mut hollow = Vec::new();
+        push_varint(&mut hollow, 1001);
+        hollow.extend([4, 0x80, 0x80, 0x04, 0]);
+        hollow.extend([0; 1000]);
+        assert_eq!(decode_steps(&hollow), Err(DecodeError::BadShape));
+        assert_eq!(decode_grid(&[0, 3]), Err(DecodeError::BadShape));
+        assert_eq!(decode_grid(&[0, 0]), Ok(Vec::new()));
+
+        let mut unchanged = steps[0].clone();
+        assert_eq!(
+            apply_delta(&mut unchanged, &[0xff]),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(unchanged, steps[0]);
+    }
+}
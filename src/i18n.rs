@@ -0,0 +1,543 @@
+//! UI message catalog with English and Russian locales.
+//!
+//! The locale starts from the saved choice, else the browser language, and can
+//! be switched at runtime. Templates use `{}` placeholders filled in order by
+//! [`Locale::format`].
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::local_store;
+
+const STORAGE_KEY: &str = "nonogram-solver.locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Ru,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ru];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ru => "ru",
+        }
+    }
+
+    /// The locale's own name, for the language picker.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::Ru => "Русский",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code() == primary)
+    }
+
+    pub fn text(self, msg: Msg) -> &'static str {
+        match self {
+            Self::En => en(msg),
+            Self::Ru => ru(msg),
+        }
+    }
+
+    /// Fill the `{}` placeholders of `msg` with `args`, in order.
+    pub fn format(self, msg: Msg, args: &[&dyn std::fmt::Display]) -> String {
+        let mut out = String::new();
+        let mut args = args.iter();
+        let mut parts = self.text(msg).split("{}");
+        if let Some(first) = parts.next() {
+            out.push_str(first);
+        }
+        for part in parts {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+            out.push_str(part);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    AppTitle,
+    ViewSolver,
+    ViewFavorites,
+    NewPuzzleTab,
+    CloseTab,
+    OpenTab,
+    InputPlaceholder,
+    HintIntro,
+    HintColor,
+    HintBlackWhite,
+    HintClues,
+    InputDetected,
+    InputClues,
+    KindColor,
+    KindBlackWhite,
+    InputInvalid,
+    LoadingPuzzle,
+    EnterPuzzle,
+    PuzzleFallbackTitle,
+    PastedCluesTitle,
+    ByAuthor,
+    PuzzleRating,
+    SourceLink,
+    PrintPuzzle,
+    PreviousPuzzle,
+    NextPuzzle,
+    ToggleBackground,
+    PaletteEditor,
+    PaletteEntry,
+    PaletteReset,
+    AddFavorite,
+    RemoveFavorite,
+    StallNote,
+    WhatIfHint,
+    WhatIfPrompt,
+    WhatIfCancel,
+    WhatIfSettles,
+    WhatIfSolves,
+    WhatIfContradiction,
+    ConflictRow,
+    ConflictColumn,
+    ConflictCell,
+    ConflictExhausted,
+    WhatIfRevert,
+    BlankPuzzleNote,
+    ShowProbabilities,
+    HideProbabilities,
+    StepSliderLabel,
+    StepLabel,
+    StepNote,
+    LearnMore,
+    StepActivity,
+    PaletteLabel,
+    ShowGrid,
+    HideGrid,
+    ResetZoom,
+    StatsTitle,
+    StatsOutcome,
+    StatsSolved,
+    StatsStalled,
+    StatsPasses,
+    StatsLineSolves,
+    StatsTime,
+    StatsMillis,
+    StatsFromRows,
+    StatsFromCols,
+    ShowEffort,
+    HideEffort,
+    LineEffortTitle,
+    SolverSettings,
+    SolverStrategy,
+    StrategyLineOnly,
+    StrategyProbing,
+    StrategyFull,
+    SolverSteps,
+    SparseSteps,
+    SolverTimeLimit,
+    InspectLine,
+    LineAxis,
+    LineRow,
+    LineColumn,
+    LineNumber,
+    SolveThisLine,
+    LineClue,
+    LineCurrent,
+    LineLeftmost,
+    LineRightmost,
+    LineAfter,
+    LineNarrowed,
+    LineNoFilling,
+    FavoritesExport,
+    FavoritesImport,
+    DailyHeading,
+    DailyOpen,
+    FavoritesImportFailed,
+    FavoritesEmpty,
+    DarkMode,
+    LightMode,
+    ToggleTheme,
+    Language,
+    ViewSource,
+    Retry,
+    DownloadSnapshot,
+    BugReportId,
+    ErrorInvalidTitle,
+    ErrorInvalidBody,
+    ErrorCluesBody,
+    ErrorNetworkTitle,
+    ErrorNetworkBody,
+    ErrorNotFoundTitle,
+    ErrorNotFoundBody,
+    ErrorParseTitle,
+    ErrorParseBody,
+    ErrorSolverTitle,
+    ErrorSolverBody,
+    ErrorTooLargeTitle,
+    ErrorTooLargeBody,
+    ErrorBusyTitle,
+    ErrorBusyBody,
+    ErrorOfflineTitle,
+    ErrorOfflineBody,
+    RetryAfter,
+}
+
+fn en(msg: Msg) -> &'static str {
+    match msg {
+        Msg::AppTitle => "Nonogram Solver",
+        Msg::ViewSolver => "Solver",
+        Msg::ViewFavorites => "Favorites ({})",
+        Msg::NewPuzzleTab => "New puzzle",
+        Msg::CloseTab => "Close tab",
+        Msg::OpenTab => "Open a new tab",
+        Msg::InputPlaceholder => "Paste a nonograms.org URL or puzzle ID",
+        Msg::HintIntro => "Paste a nonograms.org URL or enter an ID.",
+        Msg::HintColor => "Color: {}",
+        Msg::HintBlackWhite => "Black & white: {} (or prefix with bw:)",
+        Msg::HintClues => "Or paste a clue list from another app: {}",
+        Msg::InputDetected => "nonograms.org · {} puzzle #{}",
+        Msg::InputClues => "Clue list · {} × {}",
+        Msg::KindColor => "color",
+        Msg::KindBlackWhite => "black & white",
+        Msg::InputInvalid => "Not a nonograms.org puzzle URL or numeric ID",
+        Msg::LoadingPuzzle => "Loading puzzle...",
+        Msg::EnterPuzzle => "Enter a nonograms.org URL or ID",
+        Msg::PuzzleFallbackTitle => "Puzzle #{}",
+        Msg::PastedCluesTitle => "Pasted clues",
+        Msg::ByAuthor => "by {}",
+        Msg::PuzzleRating => "rated {}",
+        Msg::SourceLink => "#{} on nonograms.org",
+        Msg::PrintPuzzle => "Print puzzle",
+        Msg::PreviousPuzzle => "‹ Previous",
+        Msg::NextPuzzle => "Next ›",
+        Msg::ToggleBackground => "Click to solve this color as background",
+        Msg::PaletteEditor => "Edit colors",
+        Msg::PaletteEntry => "Color {}",
+        Msg::PaletteReset => "Reset colors",
+        Msg::AddFavorite => "Add to favorites",
+        Msg::RemoveFavorite => "Remove from favorites",
+        Msg::StallNote => "Line logic stalls on this puzzle.",
+        Msg::WhatIfHint => "Click an open cell to try a color in it.",
+        Msg::WhatIfPrompt => "Try a color in row {}, column {}:",
+        Msg::WhatIfCancel => "Cancel",
+        Msg::WhatIfSettles => "The guess settles {} more cells in {} steps.",
+        Msg::WhatIfSolves => "The guess solves the puzzle in {} steps.",
+        Msg::WhatIfContradiction => "This color contradicts the clues, so the cell cannot be it.",
+        Msg::ConflictRow => "Row {} is left with no way to fit its clue.",
+        Msg::ConflictColumn => "Column {} is left with no way to fit its clue.",
+        Msg::ConflictCell => "The cell in row {}, column {} is left with no color.",
+        Msg::ConflictExhausted => "Every guess from here runs into a contradiction.",
+        Msg::WhatIfRevert => "Revert guess",
+        Msg::BlankPuzzleNote => "Every clue is empty, so the solution is a blank grid.",
+        Msg::ShowProbabilities => "Show probabilities",
+        Msg::HideProbabilities => "Hide probabilities",
+        Msg::StepSliderLabel => "Solve step slider",
+        Msg::StepLabel => "Steps: {} / {}",
+        Msg::StepNote => "Each step reflects human-style reasoning.",
+        Msg::LearnMore => "Learn more",
+        Msg::StepActivity => "Step {}: {} cells changed",
+        Msg::PaletteLabel => "colors",
+        Msg::ShowGrid => "Show grid",
+        Msg::HideGrid => "Hide grid",
+        Msg::ResetZoom => "Reset zoom",
+        Msg::StatsTitle => "Solver statistics",
+        Msg::StatsOutcome => "Outcome",
+        Msg::StatsSolved => "Solved by line logic alone",
+        Msg::StatsStalled => "Stalled: a guess is needed to continue",
+        Msg::StatsPasses => "Passes",
+        Msg::StatsLineSolves => "Line solves",
+        Msg::StatsTime => "Time",
+        Msg::StatsMillis => "{} ms",
+        Msg::StatsFromRows => "Cells from rows",
+        Msg::StatsFromCols => "Cells from columns",
+        Msg::ShowEffort => "Shade lines by effort",
+        Msg::HideEffort => "Hide line effort",
+        Msg::LineEffortTitle => "{} line solves, {} ms",
+        Msg::SolverSettings => "Solver options",
+        Msg::SolverStrategy => "Strategy",
+        Msg::StrategyLineOnly => "Line logic only",
+        Msg::StrategyProbing => "Line logic and probing",
+        Msg::StrategyFull => "Full search",
+        Msg::SolverSteps => "Steps",
+        Msg::SparseSteps => "Record one change in {}",
+        Msg::SolverTimeLimit => "Time limit, s",
+        Msg::InspectLine => "Inspect a line",
+        Msg::LineAxis => "Row or column",
+        Msg::LineRow => "Row",
+        Msg::LineColumn => "Column",
+        Msg::LineNumber => "Line number",
+        Msg::SolveThisLine => "Solve this line",
+        Msg::LineClue => "Clue",
+        Msg::LineCurrent => "Current",
+        Msg::LineLeftmost => "Leftmost",
+        Msg::LineRightmost => "Rightmost",
+        Msg::LineAfter => "After",
+        Msg::LineNarrowed => "{} cells narrowed",
+        Msg::LineNoFilling => "No filling fits this line.",
+        Msg::FavoritesExport => "Export",
+        Msg::FavoritesImport => "Import",
+        Msg::DailyHeading => "Puzzle of the day · {}",
+        Msg::DailyOpen => "Solve it",
+        Msg::FavoritesImportFailed => "Could not import favorites: {}",
+        Msg::FavoritesEmpty => "No favorites yet. Star a puzzle from the solver view.",
+        Msg::DarkMode => "Dark mode",
+        Msg::LightMode => "Light mode",
+        Msg::ToggleTheme => "Toggle dark mode",
+        Msg::Language => "Language",
+        Msg::ViewSource => "View source on GitHub",
+        Msg::Retry => "Try again",
+        Msg::DownloadSnapshot => "Download page snapshot for a bug report",
+        Msg::BugReportId => "report id {}",
+        Msg::ErrorInvalidTitle => "That doesn't look like a puzzle",
+        Msg::ErrorInvalidBody => "Paste a nonograms.org puzzle URL or a numeric puzzle ID.",
+        Msg::ErrorCluesBody => {
+            "This looks like a clue list, but it could not be read. It needs a rows: and a cols: section with one clue per line or separated by semicolons."
+        }
+        Msg::ErrorNetworkTitle => "Couldn't reach nonograms.org",
+        Msg::ErrorNetworkBody => {
+            "The puzzle page could not be downloaded. Check your connection and try again."
+        }
+        Msg::ErrorNotFoundTitle => "Puzzle not found",
+        Msg::ErrorNotFoundBody => {
+            "nonograms.org has no puzzle with this ID. Check the number, or whether it is a black & white puzzle (prefix with bw:)."
+        }
+        Msg::ErrorParseTitle => "Couldn't read the puzzle page",
+        Msg::ErrorParseBody => {
+            "The page was downloaded but its puzzle data could not be decoded. The site format may have changed; a page snapshot helps us fix it."
+        }
+        Msg::ErrorSolverTitle => "The solver couldn't finish",
+        Msg::ErrorSolverBody => "The puzzle was loaded but its clues could not be solved.",
+        Msg::ErrorTooLargeTitle => "This puzzle is too big to solve here",
+        Msg::ErrorTooLargeBody => {
+            "To keep the page responsive, puzzles are limited in size, colors and solving time."
+        }
+        Msg::ErrorBusyTitle => "Too many requests",
+        Msg::ErrorBusyBody => {
+            "Puzzles are fetched from nonograms.org at a gentle pace. Please wait a moment and try again."
+        }
+        Msg::ErrorOfflineTitle => "You're offline",
+        Msg::ErrorOfflineBody => {
+            "This puzzle hasn't been opened on this device before. It will be fetched when the connection returns and then appear under recent puzzles."
+        }
+        Msg::RetryAfter => "Try again in {} s.",
+    }
+}
+
+fn ru(msg: Msg) -> &'static str {
+    match msg {
+        Msg::AppTitle => "Решатель японских кроссвордов",
+        Msg::ViewSolver => "Решатель",
+        Msg::ViewFavorites => "Избранное ({})",
+        Msg::NewPuzzleTab => "Новый кроссворд",
+        Msg::CloseTab => "Закрыть вкладку",
+        Msg::OpenTab => "Открыть новую вкладку",
+        Msg::InputPlaceholder => "Вставьте ссылку nonograms.org или номер кроссворда",
+        Msg::HintIntro => "Вставьте ссылку nonograms.org или введите номер.",
+        Msg::HintColor => "Цветной: {}",
+        Msg::HintBlackWhite => "Чёрно-белый: {} (или префикс bw:)",
+        Msg::HintClues => "Или вставьте список подсказок из другого приложения: {}",
+        Msg::InputDetected => "nonograms.org · {} кроссворд №{}",
+        Msg::InputClues => "Список подсказок · {} × {}",
+        Msg::KindColor => "цветной",
+        Msg::KindBlackWhite => "чёрно-белый",
+        Msg::InputInvalid => "Это не ссылка на кроссворд nonograms.org и не номер",
+        Msg::LoadingPuzzle => "Загрузка кроссворда...",
+        Msg::EnterPuzzle => "Введите ссылку nonograms.org или номер",
+        Msg::PuzzleFallbackTitle => "Кроссворд №{}",
+        Msg::PastedCluesTitle => "Вставленные подсказки",
+        Msg::ByAuthor => "автор: {}",
+        Msg::PuzzleRating => "рейтинг: {}",
+        Msg::SourceLink => "№{} на nonograms.org",
+        Msg::PrintPuzzle => "Печать",
+        Msg::PreviousPuzzle => "‹ Предыдущий",
+        Msg::NextPuzzle => "Следующий ›",
+        Msg::ToggleBackground => "Нажмите, чтобы решать этот цвет как фон",
+        Msg::PaletteEditor => "Изменить цвета",
+        Msg::PaletteEntry => "Цвет {}",
+        Msg::PaletteReset => "Сбросить цвета",
+        Msg::AddFavorite => "Добавить в избранное",
+        Msg::RemoveFavorite => "Убрать из избранного",
+        Msg::StallNote => "Логика по линиям здесь заходит в тупик.",
+        Msg::WhatIfHint => "Нажмите на открытую клетку, чтобы попробовать в ней цвет.",
+        Msg::WhatIfPrompt => "Попробовать цвет в строке {}, столбце {}:",
+        Msg::WhatIfCancel => "Отмена",
+        Msg::WhatIfSettles => "Догадка определяет ещё {} клеток за {} шагов.",
+        Msg::WhatIfSolves => "Догадка решает головоломку за {} шагов.",
+        Msg::WhatIfContradiction => {
+            "Этот цвет противоречит подсказкам, значит, клетка не может быть им."
+        }
+        Msg::ConflictRow => "Строке {} не остаётся способа вместить подсказку.",
+        Msg::ConflictColumn => "Столбцу {} не остаётся способа вместить подсказку.",
+        Msg::ConflictCell => "Клетке в строке {}, столбце {} не остаётся цвета.",
+        Msg::ConflictExhausted => "Любая догадка отсюда ведёт к противоречию.",
+        Msg::WhatIfRevert => "Отменить догадку",
+        Msg::BlankPuzzleNote => "Все подсказки пусты, поэтому решение — пустая сетка.",
+        Msg::ShowProbabilities => "Показать вероятности",
+        Msg::HideProbabilities => "Скрыть вероятности",
+        Msg::StepSliderLabel => "Ползунок шагов решения",
+        Msg::StepLabel => "Шаги: {} / {}",
+        Msg::StepNote => "Каждый шаг повторяет рассуждения человека.",
+        Msg::LearnMore => "Подробнее",
+        Msg::StepActivity => "Шаг {}: изменено клеток: {}",
+        Msg::PaletteLabel => "цвета",
+        Msg::ShowGrid => "Показать сетку",
+        Msg::HideGrid => "Скрыть сетку",
+        Msg::ResetZoom => "Сбросить масштаб",
+        Msg::StatsTitle => "Статистика решателя",
+        Msg::StatsOutcome => "Итог",
+        Msg::StatsSolved => "Решено одной логикой по линиям",
+        Msg::StatsStalled => "Тупик: чтобы продолжить, нужна догадка",
+        Msg::StatsPasses => "Проходы",
+        Msg::StatsLineSolves => "Решения линий",
+        Msg::StatsTime => "Время",
+        Msg::StatsMillis => "{} мс",
+        Msg::StatsFromRows => "Клеток из строк",
+        Msg::StatsFromCols => "Клеток из столбцов",
+        Msg::ShowEffort => "Показать трудность линий",
+        Msg::HideEffort => "Скрыть трудность линий",
+        Msg::LineEffortTitle => "Решений линии: {}, {} мс",
+        Msg::SolverSettings => "Настройки решателя",
+        Msg::SolverStrategy => "Стратегия",
+        Msg::StrategyLineOnly => "Только логика линий",
+        Msg::StrategyProbing => "Логика линий и пробы",
+        Msg::StrategyFull => "Полный перебор",
+        Msg::SolverSteps => "Шаги",
+        Msg::SparseSteps => "Записывать одно изменение из {}",
+        Msg::SolverTimeLimit => "Лимит времени, с",
+        Msg::InspectLine => "Разбор линии",
+        Msg::LineAxis => "Строка или столбец",
+        Msg::LineRow => "Строка",
+        Msg::LineColumn => "Столбец",
+        Msg::LineNumber => "Номер линии",
+        Msg::SolveThisLine => "Решить эту линию",
+        Msg::LineClue => "Подсказка",
+        Msg::LineCurrent => "Сейчас",
+        Msg::LineLeftmost => "Крайняя левая",
+        Msg::LineRightmost => "Крайняя правая",
+        Msg::LineAfter => "После",
+        Msg::LineNarrowed => "Сужено клеток: {}",
+        Msg::LineNoFilling => "Ни одна расстановка не подходит.",
+        Msg::FavoritesExport => "Экспорт",
+        Msg::FavoritesImport => "Импорт",
+        Msg::DailyHeading => "Кроссворд дня · {}",
+        Msg::DailyOpen => "Решить",
+        Msg::FavoritesImportFailed => "Не удалось импортировать избранное: {}",
+        Msg::FavoritesEmpty => "Избранного пока нет. Отметьте кроссворд звёздочкой в решателе.",
+        Msg::DarkMode => "Тёмная тема",
+        Msg::LightMode => "Светлая тема",
+        Msg::ToggleTheme => "Переключить тёмную тему",
+        Msg::Language => "Язык",
+        Msg::ViewSource => "Исходный код на GitHub",
+        Msg::Retry => "Повторить",
+        Msg::DownloadSnapshot => "Скачать копию страницы для отчёта об ошибке",
+        Msg::BugReportId => "код отчёта {}",
+        Msg::ErrorInvalidTitle => "Это не похоже на кроссворд",
+        Msg::ErrorInvalidBody => "Вставьте ссылку на кроссворд nonograms.org или его номер.",
+        Msg::ErrorCluesBody => {
+            "Это похоже на список подсказок, но его не удалось прочитать. Нужны разделы rows: и cols:, по одной подсказке на строку или через точку с запятой."
+        }
+        Msg::ErrorNetworkTitle => "Не удалось связаться с nonograms.org",
+        Msg::ErrorNetworkBody => {
+            "Страницу кроссворда не удалось загрузить. Проверьте подключение и повторите попытку."
+        }
+        Msg::ErrorNotFoundTitle => "Кроссворд не найден",
+        Msg::ErrorNotFoundBody => {
+            "На nonograms.org нет кроссворда с таким номером. Проверьте номер или не чёрно-белый ли он (префикс bw:)."
+        }
+        Msg::ErrorParseTitle => "Не удалось прочитать страницу кроссворда",
+        Msg::ErrorParseBody => {
+            "Страница загружена, но данные кроссворда не удалось расшифровать. Возможно, формат сайта изменился; копия страницы поможет это исправить."
+        }
+        Msg::ErrorSolverTitle => "Решатель не смог закончить",
+        Msg::ErrorSolverBody => "Кроссворд загружен, но его условия не удалось решить.",
+        Msg::ErrorTooLargeTitle => "Этот кроссворд слишком большой",
+        Msg::ErrorTooLargeBody => {
+            "Чтобы страница не зависала, размер кроссворда, число цветов и время решения ограничены."
+        }
+        Msg::ErrorBusyTitle => "Слишком много запросов",
+        Msg::ErrorBusyBody => {
+            "Кроссворды загружаются с nonograms.org не спеша. Подождите немного и попробуйте снова."
+        }
+        Msg::ErrorOfflineTitle => "Нет подключения к сети",
+        Msg::ErrorOfflineBody => {
+            "Этот кроссворд ещё не открывали на этом устройстве. Он загрузится, когда связь вернётся, и появится в недавних."
+        }
+        Msg::RetryAfter => "Повторите через {} с.",
+    }
+}
+
+/// Shared locale signal, also mirrored to `<html lang>`.
+pub fn use_locale_provider() -> Signal<Locale> {
+    let mut locale = use_context_provider(|| Signal::new(Locale::En));
+    use_effect(move || {
+        spawn(async move {
+            let saved = local_store::load::<Locale>(STORAGE_KEY).await;
+            let detected = match saved {
+                Some(saved) => Some(saved),
+                None => browser_locale().await,
+            };
+            if let Some(detected) = detected {
+                locale.set(detected);
+            }
+        });
+    });
+    use_effect(move || {
+        let script = format!("document.documentElement.lang = \"{}\";", locale().code());
+        let _ = document::eval(&script);
+    });
+    locale
+}
+
+/// The current locale; read it inside a component to re-render on switches.
+pub fn use_locale() -> Locale {
+    use_context::<Signal<Locale>>()()
+}
+
+pub fn set(mut locale: Signal<Locale>, next: Locale) {
+    locale.set(next);
+    local_store::save(STORAGE_KEY, &next);
+}
+
+async fn browser_locale() -> Option<Locale> {
+    let languages = document::eval("return navigator.languages || [navigator.language];")
+        .join::<Vec<String>>()
+        .await
+        .ok()?;
+    languages.iter().find_map(|code| Locale::from_code(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_in_order() {
+        assert_eq!(
+            Locale::En.format(Msg::StepLabel, &[&3, &12]),
+            "Steps: 3 / 12"
+        );
+        assert_eq!(
+            Locale::Ru.format(Msg::StepLabel, &[&3, &12]),
+            "Шаги: 3 / 12"
+        );
+    }
+
+    #[test]
+    fn detects_locale_from_language_tags() {
+        assert_eq!(Locale::from_code("ru-RU"), Some(Locale::Ru));
+        assert_eq!(Locale::from_code("en_GB"), Some(Locale::En));
+        assert_eq!(Locale::from_code("de"), None);
+    }
+}
@@ -1,3 +1,36 @@
+//! Colored nonogram solver with a nonograms.org page parser.
+//!
+//! The solver core and parser only need `alloc`, so the crate is `no_std`
+//! unless the `std` feature is on (`crawler` and `ui` both enable it).
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod booklet;
+pub mod clue_text;
+pub mod color;
+#[cfg(feature = "crawler")]
+pub mod crawl_policy;
+pub mod dedupe;
+pub mod difficulty;
+pub mod grid_codec;
 pub mod nonogram_solver;
-pub mod one_line_solver;
+mod one_line_solver;
+pub mod prelude;
+pub mod proof;
+pub mod puzzle_builder;
+#[cfg(feature = "crawler")]
+pub mod puzzle_cache;
 pub mod puzzle_crawler;
+pub mod puzzle_stats;
+pub mod repair;
+pub mod report;
+pub mod silhouette;
+pub mod social_card;
+#[cfg(feature = "stream")]
+pub mod solve_stream;
+pub mod step_filter;
+pub mod symmetry;
+pub mod thumbnail;
+pub mod transform;
@@ -0,0 +1,119 @@
+//! Browser `localStorage` and IndexedDB access through `document::eval`.
+//!
+//! Values are stored as JSON. Reads return `None` outside the browser (for
+//! example during server rendering) or when the key is missing or malformed.
+//! Small settings go to `localStorage`; `db_load` and `db_save` keep values
+//! that could outgrow its few megabytes, such as whole puzzles, in IndexedDB.
+
+use dioxus::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub async fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let script = format!(
+        "const raw = localStorage.getItem({}); return raw === null ? null : JSON.parse(raw);",
+        js_string(key)
+    );
+    document::eval(&script)
+        .join::<Option<T>>()
+        .await
+        .ok()
+        .flatten()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let script = format!(
+        "const value = await dioxus.recv(); localStorage.setItem({}, JSON.stringify(value));",
+        js_string(key)
+    );
+    let eval = document::eval(&script);
+    let _ = eval.send(value);
+}
+
+/// Opens (and on first use creates) the IndexedDB store behind `db_load`
+/// and `db_save`.
+const OPEN_DB: &str = r#"const db = await new Promise((resolve, reject) => {
+    const request = indexedDB.open("nonogram-solver", 1);
+    request.onupgradeneeded = () => request.result.createObjectStore("values");
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+});"#;
+
+pub async fn db_load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let script = format!(
+        r#"{OPEN_DB}
+        const raw = await new Promise((resolve) => {{
+            const get = db.transaction("values").objectStore("values").get({});
+            get.onsuccess = () => resolve(get.result ?? null);
+            get.onerror = () => resolve(null);
+        }});
+        return raw === null ? null : JSON.parse(raw);"#,
+        js_string(key)
+    );
+    document::eval(&script)
+        .join::<Option<T>>()
+        .await
+        .ok()
+        .flatten()
+}
+
+pub fn db_save<T: Serialize>(key: &str, value: &T) {
+    let script = format!(
+        r#"const value = await dioxus.recv();
+        {OPEN_DB}
+        db.transaction("values", "readwrite").objectStore("values").put(JSON.stringify(value), {});"#,
+        js_string(key)
+    );
+    let eval = document::eval(&script);
+    let _ = eval.send(value);
+}
+
+/// Offer `value` to the user as a downloaded JSON file.
+pub fn download_json<T: Serialize>(filename: &str, value: &T) {
+    let script = format!(
+        r#"const value = await dioxus.recv();
+        const blob = new Blob([JSON.stringify(value, null, 2)], {{ type: "application/json" }});
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = {};
+        link.click();
+        URL.revokeObjectURL(link.href);"#,
+        js_string(filename)
+    );
+    let eval = document::eval(&script);
+    let _ = eval.send(value);
+}
+
+/// Offer `contents` to the user as a downloaded file of the given MIME type.
+pub fn download_text(filename: &str, mime: &str, contents: &str) {
+    let script = format!(
+        r#"const contents = await dioxus.recv();
+        const blob = new Blob([contents], {{ type: {} }});
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = {};
+        link.click();
+        URL.revokeObjectURL(link.href);"#,
+        js_string(mime),
+        js_string(filename)
+    );
+    let eval = document::eval(&script);
+    let _ = eval.send(contents);
+}
+
+/// Quote `text` as a JavaScript string literal.
+fn js_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '<' => out.push_str("\\u003c"),
+            _ => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
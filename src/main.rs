@@ -1,95 +1,589 @@
 use dioxus::prelude::*;
 
 mod components;
+mod daily;
+mod favorites;
+mod i18n;
+mod local_store;
+mod offline;
+mod recent;
+mod theme;
 
+use components::daily_card::DailyCard;
+use components::favorites_page::FavoritesPage;
 use components::github_corner::GithubCorner;
-use components::puzzle_viewer::PuzzleViewer;
-use nonogram_solver::nonogram_solver::solve_puzzle_steps;
-use nonogram_solver::puzzle_crawler::{
-    PuzzleData, PuzzleKind, fetch_puzzle as fetch_remote_puzzle,
-};
+use components::puzzle_session::PuzzleSession;
+use components::theme_toggle::ThemeToggle;
+use i18n::{Locale, Msg};
+use nonogram_solver::clue_text::{ClueTextError, parse_clue_text};
+use nonogram_solver::nonogram_solver::{SolvePreferences, SolveSteps};
+use nonogram_solver::puzzle_crawler::{FetchedPuzzle, PuzzleData, PuzzleKind, PuzzleRef};
+use nonogram_solver::social_card::{CARD_HEIGHT, CARD_WIDTH};
+use serde::{Deserialize, Serialize};
 
 fn main() {
+    #[cfg(feature = "server")]
+    init_tracing();
     dioxus::launch(App);
 }
 
+/// Log to stdout, filtered by `RUST_LOG` (e.g. `RUST_LOG=nonogram_solver=debug`).
+///
+/// Installed before launch so Dioxus keeps it instead of its default logger.
+#[cfg(feature = "server")]
+fn init_tracing() {
+    use tracing_subscriber::EnvFilter;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Solver,
+    Favorites,
+}
+
+/// An open puzzle tab; `key` stays stable while tabs are opened and closed.
+#[derive(Debug, Clone, PartialEq)]
+struct Tab {
+    key: usize,
+    input: String,
+}
+
 #[component]
 fn App() -> Element {
-    let mut input_url = use_signal(|| "56215".to_string());
+    let mut tabs = use_signal(|| {
+        vec![Tab {
+            key: 0,
+            input: "56215".to_string(),
+        }]
+    });
+    let mut active = use_signal(|| 0usize);
+    let mut next_key = use_signal(|| 1usize);
+    let mut view = use_signal(|| View::Solver);
+    let favorites = favorites::use_favorites_provider();
+    let recent = recent::use_recent_provider();
+    offline::use_offline_provider();
+    theme::use_theme_provider();
+    let locale_signal = i18n::use_locale_provider();
+    let locale = locale_signal();
 
-    let puzzle = use_resource(move || {
-        let url = input_url();
-        async move {
-            if url.trim().is_empty() {
-                return Ok(None);
-            }
-            let (kind, puzzle_id) = puzzle_id_from_input(&url)
-                .ok_or_else(|| "Invalid nonogram URL or ID".to_string())?;
-            let data = fetch_puzzle_data(puzzle_kind_param(kind), puzzle_id)
-                .await
-                .map_err(|err| err.to_string())?;
-            solve_puzzle_steps(data)
-                .map(Some)
-                .map_err(|err| err.to_string())
+    let active_idx = active().min(tabs.read().len().saturating_sub(1));
+    let active_input = tabs
+        .read()
+        .get(active_idx)
+        .map(|tab| tab.input.clone())
+        .unwrap_or_default();
+    let mut set_active_input = move |value: String| {
+        if let Some(tab) = tabs.write().get_mut(active_idx) {
+            tab.input = value;
         }
-    });
+    };
 
     rsx! {
         // document::Meta { content: "text/html;charset=utf-8" }
         document::Stylesheet { href: asset!("/assets/style.css") }
         document::Link { rel: "icon", href: asset!("/assets/favicon.ico") }
-        document::Title { "Nonogram Solver" }
+        document::Link { rel: "manifest", href: "/manifest.webmanifest" }
+        document::Meta { name: "theme-color", content: "#f6f6f4" }
+        document::Title { {locale.text(Msg::AppTitle)} }
         document::Meta { name: "author", content: "Jiaye Will Wang" }
         document::Meta {
             name: "description",
             content: "Solve color and black-and-white nonograms online with step-by-step visualization.",
         }
         document::Meta { name: "viewport", content: "width=device-width, initial-scale=1" }
+        document::Meta { property: "og:type", content: "website" }
+        document::Meta { property: "og:title", content: locale.text(Msg::AppTitle) }
+        document::Meta {
+            property: "og:description",
+            content: "Solve color and black-and-white nonograms online with step-by-step visualization.",
+        }
+        if let Some(puzzle) = puzzle_ref_from_input(&active_input) {
+            document::Meta { property: "og:image", content: card_url(&puzzle) }
+            document::Meta { property: "og:image:width", content: "{CARD_WIDTH}" }
+            document::Meta { property: "og:image:height", content: "{CARD_HEIGHT}" }
+            document::Meta { name: "twitter:card", content: "summary_large_image" }
+        }
         document::Meta { content: "viewtext/html;charset=utf-8port", http_equiv: "Content-Type" }
 
         main { class: "page",
             div { class: "card",
                 GithubCorner { href: "https://github.com/willwang-io/NonogramSolver".to_string() }
-                h1 { class: "title", "Nonogram Solver" }
-                div { class: "input-row",
-                    input {
-                        class: "input",
-                        r#type: "text",
-                        value: input_url,
-                        placeholder: "Paste a nonograms.org URL or puzzle ID",
-                        oninput: move |e| *input_url.write() = e.value(),
+                h1 { class: "title", {locale.text(Msg::AppTitle)} }
+                nav { class: "view-tabs",
+                    button {
+                        class: if view() == View::Solver { "view-tab active" } else { "view-tab" },
+                        r#type: "button",
+                        onclick: move |_| view.set(View::Solver),
+                        {locale.text(Msg::ViewSolver)}
+                    }
+                    button {
+                        class: if view() == View::Favorites { "view-tab active" } else { "view-tab" },
+                        r#type: "button",
+                        onclick: move |_| view.set(View::Favorites),
+                        {locale.format(Msg::ViewFavorites, &[&favorites.read().len()])}
+                    }
+                    ThemeToggle {}
+                    select {
+                        class: "locale-select",
+                        "aria-label": locale.text(Msg::Language),
+                        value: locale.code(),
+                        onchange: move |e| {
+                            if let Some(next) = Locale::ALL.into_iter().find(|l| l.code() == e.value()) {
+                                i18n::set(locale_signal, next);
+                            }
+                        },
+                        for option_locale in Locale::ALL {
+                            option {
+                                value: option_locale.code(),
+                                selected: option_locale == locale,
+                                {option_locale.native_name()}
+                            }
+                        }
                     }
                 }
-                div { class: "hint",
-                    "Paste a nonograms.org URL or enter an ID."
-                    br {}
-                    "Color: https://www.nonograms.org/nonograms2/i/56215"
-                    br {}
-                    "Black & white: https://www.nonograms.org/nonograms/i/1822 (or prefix with bw:)"
+                if view() == View::Favorites {
+                    FavoritesPage {
+                        on_open: move |url: String| {
+                            set_active_input(url);
+                            view.set(View::Solver);
+                        },
+                    }
+                } else {
+                    div { class: "tab-strip", role: "tablist",
+                        for (idx, tab) in tabs().into_iter().enumerate() {
+                            div {
+                                key: "{tab.key}",
+                                class: if idx == active_idx { "tab active" } else { "tab" },
+                                button {
+                                    class: "tab-label",
+                                    r#type: "button",
+                                    role: "tab",
+                                    "aria-selected": "{idx == active_idx}",
+                                    onclick: move |_| active.set(idx),
+                                    {tab_label(locale, &tab.input)}
+                                }
+                                if tabs.read().len() > 1 {
+                                    button {
+                                        class: "tab-close",
+                                        r#type: "button",
+                                        "aria-label": locale.text(Msg::CloseTab),
+                                        onclick: move |_| {
+                                            tabs.write().remove(idx);
+                                            if active() >= idx && active() > 0 {
+                                                active -= 1;
+                                            }
+                                        },
+                                        "×"
+                                    }
+                                }
+                            }
+                        }
+                        button {
+                            class: "tab-add",
+                            r#type: "button",
+                            "aria-label": locale.text(Msg::OpenTab),
+                            onclick: move |_| {
+                                let key = next_key();
+                                next_key += 1;
+                                tabs.write().push(Tab { key, input: String::new() });
+                                active.set(tabs.read().len() - 1);
+                            },
+                            "+"
+                        }
+                    }
+                    div { class: "input-row",
+                        input {
+                            class: "input",
+                            r#type: "text",
+                            value: "{active_input}",
+                            list: "puzzle-suggestions",
+                            placeholder: locale.text(Msg::InputPlaceholder),
+                            "aria-describedby": "input-status",
+                            oninput: move |e| set_active_input(e.value()),
+                        }
+                        datalist { id: "puzzle-suggestions",
+                            for entry in suggestions(&favorites.read(), &recent.read()) {
+                                option { key: "{entry.url}", value: "{entry.url}", {entry.label(locale)} }
+                            }
+                        }
+                    }
+                    {input_status(locale, &active_input)}
+                    DailyCard { on_open: move |input: String| set_active_input(input) }
+                    div { class: "hint",
+                        {locale.text(Msg::HintIntro)}
+                        br {}
+                        {locale.format(Msg::HintColor, &[&"https://www.nonograms.org/nonograms2/i/56215"])}
+                        br {}
+                        {locale.format(Msg::HintBlackWhite, &[&"https://www.nonograms.org/nonograms/i/1822"])}
+                        br {}
+                        {locale.format(Msg::HintClues, &[&"rows: 1; 2 cols: 2; 1"])}
+                    }
+                    for (idx, tab) in tabs().into_iter().enumerate() {
+                        div {
+                            key: "{tab.key}",
+                            class: "session",
+                            hidden: idx != active_idx,
+                            PuzzleSession {
+                                input: tab.input,
+                                on_navigate: move |input: String| set_active_input(input),
+                            }
+                        }
+                    }
                 }
-                {match puzzle() {
-                    None => rsx! { div { class: "status", "Loading puzzle..." } },
-                    Some(Err(err)) => rsx! { div { class: "status", "Failed to load puzzle: {err}" } },
-                    Some(Ok(None)) => rsx! { div { class: "status", "Enter a nonograms.org URL or ID" } },
-                    Some(Ok(Some(steps))) => rsx! { div { class: "grid-wrap", PuzzleViewer { steps } } },
-                }}
             }
         }
     }
 }
 
-#[get("/api/puzzle/:kind/:puzzle_id")]
-async fn fetch_puzzle_data(kind: String, puzzle_id: String) -> Result<PuzzleData, ServerFnError> {
+/// Why the server could not provide a puzzle, kept structured for the error screens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FetchFailure {
+    Network(String),
+    NotFound,
+    /// The page was fetched but could not be decoded; `snapshot` is its HTML.
+    Parse {
+        detail: String,
+        snapshot: String,
+        /// `DiagnosticBundle` id, to quote in a bug report.
+        bundle: String,
+    },
+    /// The page decoded, but the puzzle exceeds `SolverOptions::web()`.
+    TooLarge(String),
+    /// This client, or the server as a whole, is over its crawl budget.
+    RateLimited {
+        retry_after_secs: u64,
+    },
+}
+
+/// Shared crawl budget and pacing for every client of this server.
+#[cfg(feature = "server")]
+pub(crate) mod crawl_limits {
+    use std::sync::{LazyLock, Once};
+    use std::time::Duration;
+
+    use nonogram_solver::crawl_policy::{PoliteCrawler, Politeness, Prefetcher, RateLimiter};
+    use nonogram_solver::puzzle_cache::{FileCache, MemoryCache};
+    use nonogram_solver::puzzle_crawler::{HtmlFetcher, PuzzleRef};
+
+    /// A handful of puzzles at once, then one every 10 seconds per client.
+    pub static PER_CLIENT: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(5, 0.1));
+    /// Across all clients: one new puzzle per second on average.
+    pub static GLOBAL: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(20, 1.0));
+    /// Pages are cached on disk under `NONOGRAM_CACHE_DIR` if it is set, so
+    /// they survive restarts; otherwise the most recent ones stay in memory.
+    /// `NONOGRAM_FETCH_PROXY` sends page fetches through a proxy, as for
+    /// `HtmlFetcher::via_proxy`; the smoke tests point it at a mock site.
+    /// `NONOGRAM_CRAWLER_CONTACT` goes in the `User-Agent`, and
+    /// `NONOGRAM_STRICT_POLITENESS` stops fetching while robots.txt is unread.
+    pub static CRAWLER: LazyLock<PoliteCrawler> = LazyLock::new(|| {
+        let mut crawler = PoliteCrawler::new(Duration::from_millis(500));
+        if let Ok(proxy) = std::env::var("NONOGRAM_FETCH_PROXY") {
+            crawler = crawler.with_fetcher(HtmlFetcher::via_proxy(proxy));
+        }
+        if let Ok(contact) = std::env::var("NONOGRAM_CRAWLER_CONTACT") {
+            crawler = crawler.with_contact(&contact);
+        }
+        if std::env::var_os("NONOGRAM_STRICT_POLITENESS").is_some() {
+            crawler = crawler.with_politeness(Politeness::Strict);
+        }
+        let dir = std::env::var_os("NONOGRAM_CACHE_DIR");
+        match dir.map(FileCache::new) {
+            Some(Ok(cache)) => crawler.with_cache(cache),
+            Some(Err(err)) => {
+                tracing::warn!(%err, "cannot use NONOGRAM_CACHE_DIR, caching in memory");
+                crawler.with_cache(MemoryCache::new(512))
+            }
+            None => crawler.with_cache(MemoryCache::new(512)),
+        }
+    });
+
+    /// Queue the puzzles around `puzzle` to be fetched in the background, if
+    /// `NONOGRAM_PREFETCH` is set, so moving to the next id hits the cache.
+    /// Prefetches draw on the global budget and are dropped when it is spent.
+    pub fn prefetch_around(puzzle: &PuzzleRef) {
+        static PREFETCHER: LazyLock<Prefetcher> = LazyLock::new(|| Prefetcher::new(16));
+        static STARTED: Once = Once::new();
+
+        if std::env::var_os("NONOGRAM_PREFETCH").is_none() {
+            return;
+        }
+        STARTED.call_once(|| {
+            tokio::spawn(PREFETCHER.run(&CRAWLER, &GLOBAL, ""));
+        });
+        PREFETCHER.request(
+            [-1, 1, 2]
+                .into_iter()
+                .filter_map(|delta| puzzle.offset(delta)),
+        );
+    }
+
+    /// The client address as reported by the reverse proxy in front of the app.
+    ///
+    /// Assumes exactly one proxy, which appends the address it sees to
+    /// `X-Forwarded-For` (nginx's `$proxy_add_x_forwarded_for`, Caddy, most
+    /// load balancers) or overwrites `X-Real-IP`. Only the last
+    /// `X-Forwarded-For` entry comes from that proxy; anything before it is
+    /// whatever the client sent. Without a proxy the headers are the
+    /// client's own, so the app must not be exposed directly.
+    pub fn client_key(headers: &dioxus::fullstack::HeaderMap) -> String {
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .last()
+            .or_else(|| headers.get("x-real-ip"))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .map(|ip| ip.trim().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[get("/api/puzzle/:kind/:puzzle_id", headers: dioxus::fullstack::HeaderMap)]
+async fn fetch_puzzle_data(
+    kind: String,
+    puzzle_id: String,
+) -> Result<Result<FetchedPuzzle, FetchFailure>, ServerFnError> {
     let kind = match kind.as_str() {
         "bw" => PuzzleKind::BlackWhite,
         _ => PuzzleKind::Color,
     };
-    fetch_remote_puzzle(kind, &puzzle_id)
+    let puzzle = PuzzleRef::nonograms_org(kind, puzzle_id);
+    let fetched = crawl_page(&puzzle, &headers)
         .await
-        .map_err(|err| ServerFnError::new(err.to_string()))
+        .and_then(|html| puzzle_from_html(&puzzle, html));
+    if fetched.is_ok() {
+        crawl_limits::prefetch_around(&puzzle);
+    }
+    Ok(fetched)
+}
+
+/// The page for `puzzle`, from the cache or within the crawl budget.
+#[cfg(feature = "server")]
+async fn crawl_page(
+    puzzle: &PuzzleRef,
+    headers: &dioxus::fullstack::HeaderMap,
+) -> Result<String, FetchFailure> {
+    use nonogram_solver::puzzle_crawler::CrawlError;
+
+    // Cached pages cost nonograms.org nothing, so they skip the budget.
+    if let Some(html) = crawl_limits::CRAWLER.cached_html(puzzle) {
+        return Ok(html);
+    }
+    let client = crawl_limits::client_key(headers);
+    let budget = crawl_limits::PER_CLIENT
+        .check(&client)
+        .and_then(|()| crawl_limits::GLOBAL.check(""));
+    if let Err(wait) = budget {
+        tracing::info!(%client, ?wait, "rate limited");
+        let retry_after_secs = wait.as_secs() + 1;
+        return Err(FetchFailure::RateLimited { retry_after_secs });
+    }
+    match crawl_limits::CRAWLER.fetch_puzzle_page(puzzle).await {
+        Ok(html) => Ok(html),
+        Err(CrawlError::NotFound) => Err(FetchFailure::NotFound),
+        Err(err) => Err(FetchFailure::Network(err.to_string())),
+    }
 }
 
-fn puzzle_id_from_input(input: &str) -> Option<(PuzzleKind, String)> {
+/// Decode a fetched page and check it against the web solver limits.
+fn puzzle_from_html(puzzle: &PuzzleRef, html: String) -> Result<FetchedPuzzle, FetchFailure> {
+    use nonogram_solver::nonogram_solver::SolverOptions;
+    use nonogram_solver::puzzle_crawler::{DiagnosticBundle, parse_puzzle, parse_puzzle_meta};
+
+    match parse_puzzle(puzzle.kind, &html) {
+        Ok(data) => {
+            if let Err(err) = SolverOptions::web().check(&data) {
+                return Err(FetchFailure::TooLarge(err.to_string()));
+            }
+            let meta = parse_puzzle_meta(puzzle.kind, &puzzle.id, &html);
+            Ok(FetchedPuzzle { data, meta })
+        }
+        Err(err) => {
+            let bundle = DiagnosticBundle::capture(puzzle, &html, &err);
+            tracing::warn!(
+                bundle = %bundle.id,
+                kind = puzzle.kind.param(),
+                id = %puzzle.id,
+                error = %bundle.error,
+                numbers = bundle.numbers.as_ref().map_or(0, Vec::len),
+                "puzzle page did not parse"
+            );
+            #[cfg(feature = "server")]
+            save_diagnostics(&bundle);
+            Err(FetchFailure::Parse {
+                detail: bundle.error,
+                snapshot: html,
+                bundle: bundle.id,
+            })
+        }
+    }
+}
+
+/// Write `bundle` to `$NONOGRAM_DIAGNOSTICS_DIR/<id>.json` if that is set.
+/// Failing to write is logged and otherwise ignored.
+#[cfg(feature = "server")]
+fn save_diagnostics(bundle: &nonogram_solver::puzzle_crawler::DiagnosticBundle) {
+    let Some(dir) = std::env::var_os("NONOGRAM_DIAGNOSTICS_DIR") else {
+        return;
+    };
+    let path = std::path::Path::new(&dir).join(format!("{}.json", bundle.id));
+    let written = std::fs::create_dir_all(&dir)
+        .and_then(|()| serde_json::to_vec_pretty(bundle).map_err(std::io::Error::other))
+        .and_then(|json| std::fs::write(&path, json));
+    if let Err(err) = written {
+        tracing::warn!(%err, path = %path.display(), "cannot save diagnostics");
+    }
+}
+
+/// CORS proxy for static deployments, set at build time. When present the
+/// app fetches puzzle pages itself instead of calling `fetch_puzzle_data`, so
+/// it works without a server (and without the server's rate limits).
+const CORS_PROXY: Option<&str> = option_env!("NONOGRAM_CORS_PROXY");
+
+/// `fetch_puzzle_data`, from the app itself through `proxy`.
+async fn fetch_puzzle_via_proxy(
+    proxy: &str,
+    puzzle: &PuzzleRef,
+) -> Result<FetchedPuzzle, FetchFailure> {
+    use nonogram_solver::puzzle_crawler::{CrawlError, HtmlFetcher};
+
+    let html = match HtmlFetcher::via_proxy(proxy)
+        .fetch_puzzle_page(puzzle)
+        .await
+    {
+        Ok(html) => html,
+        Err(CrawlError::NotFound) => return Err(FetchFailure::NotFound),
+        Err(err) => return Err(FetchFailure::Network(err.to_string())),
+    };
+    puzzle_from_html(puzzle, html)
+}
+
+/// Solve clues posted by another frontend, under the same limits as the web app.
+/// `preferences` picks the strategy, step density and a tighter time limit.
+/// Strategies past line logic can use the whole time limit, so they draw on
+/// the client's crawl budget.
+///
+/// The app solves each puzzle in the browser and calls this to re-solve it
+/// with other options. This and `fetch_puzzle_data` make up the public API
+/// described in `public/openapi.json`.
+#[post("/api/solve", headers: dioxus::fullstack::HeaderMap)]
+async fn solve_puzzle_data(
+    data: PuzzleData,
+    preferences: Option<SolvePreferences>,
+) -> Result<Result<SolveSteps, String>, ServerFnError> {
+    use nonogram_solver::nonogram_solver::{SolveStrategy, SolverOptions, solve_puzzle_steps_with};
+
+    let options = SolverOptions::web().with_preferences(&preferences.unwrap_or_default());
+    if options.strategy != SolveStrategy::LineOnly {
+        let client = crawl_limits::client_key(&headers);
+        if let Err(wait) = crawl_limits::PER_CLIENT.check(&client) {
+            tracing::info!(%client, ?wait, "solve rate limited");
+            let secs = wait.as_secs() + 1;
+            return Ok(Err(format!(
+                "too many solves past line logic; try again in {secs} seconds"
+            )));
+        }
+    }
+    // Solving is CPU-bound for up to the time limit; keep it off the
+    // threads that serve requests.
+    tokio::task::spawn_blocking(move || {
+        solve_puzzle_steps_with(data, &options).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(ServerFnError::new)
+}
+
+/// The link preview image for a puzzle: `id` is what goes in the input box
+/// plus `.png`, e.g. `56215.png` or `bw:1822.png`. See `card_url`.
+///
+/// Failures are plain HTTP statuses, since unfurlers only look at the image.
+#[get("/api/card/:id", headers: dioxus::fullstack::HeaderMap)]
+async fn social_card(id: String) -> Result<dioxus::fullstack::response::Response, ServerFnError> {
+    use dioxus::fullstack::StatusCode;
+    use dioxus::fullstack::body::Body;
+    use dioxus::fullstack::http::header;
+    use dioxus::fullstack::response::{IntoResponse, Response};
+    use nonogram_solver::nonogram_solver::{SolvedPuzzle, SolverOptions, solve_puzzle_steps_with};
+    use nonogram_solver::social_card::render_card;
+
+    let Some(puzzle) = id.strip_suffix(".png").and_then(puzzle_ref_from_input) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    // A cached page skips the crawl budget, but the solve behind its card
+    // is not free; charge it so that one client cannot keep solving.
+    if crawl_limits::CRAWLER.cached_html(&puzzle).is_some() {
+        let client = crawl_limits::client_key(&headers);
+        if let Err(wait) = crawl_limits::PER_CLIENT.check(&client) {
+            tracing::info!(%client, ?wait, "card rate limited");
+            let retry_after = [(header::RETRY_AFTER, (wait.as_secs() + 1).to_string())];
+            return Ok((StatusCode::TOO_MANY_REQUESTS, retry_after).into_response());
+        }
+    }
+    let fetched = match crawl_page(&puzzle, &headers)
+        .await
+        .and_then(|html| puzzle_from_html(&puzzle, html))
+    {
+        Ok(fetched) => fetched,
+        Err(FetchFailure::NotFound) => return Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(FetchFailure::RateLimited { retry_after_secs }) => {
+            let retry_after = [(header::RETRY_AFTER, retry_after_secs.to_string())];
+            return Ok((StatusCode::TOO_MANY_REQUESTS, retry_after).into_response());
+        }
+        Err(FetchFailure::Network(_)) => return Ok(StatusCode::BAD_GATEWAY.into_response()),
+        Err(FetchFailure::Parse { .. } | FetchFailure::TooLarge(_)) => {
+            return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+        }
+    };
+    // The pixel font only has ASCII; most nonograms.org titles are Russian.
+    let title = fetched
+        .meta
+        .title
+        .filter(|title| title.chars().all(|c| c == ' ' || c.is_ascii_graphic()))
+        .unwrap_or_else(|| format!("Nonogram {}", puzzle.id));
+    // Solving and encoding are CPU-bound, as in `solve_puzzle_data`.
+    let rendered = tokio::task::spawn_blocking(move || {
+        let solved = solve_puzzle_steps_with(fetched.data, &SolverOptions::web()).ok()?;
+        Some(render_card(
+            &title,
+            &SolvedPuzzle {
+                color_panel: solved.color_panel,
+                grid: solved.steps.last().cloned().unwrap_or_default(),
+            },
+        ))
+    })
+    .await
+    .map_err(ServerFnError::new)?;
+    let Some(png) = rendered else {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    };
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "image/png")
+        .header(header::CACHE_CONTROL, "public, max-age=86400")
+        .body(Body::from(png))
+        .unwrap_or_default())
+}
+
+/// Public origin of the deployment, set at build time, e.g.
+/// `https://nonogram.example`. Link previews need absolute image URLs, so
+/// without it the card URL is relative and most unfurlers ignore it.
+const PUBLIC_URL: &str = match option_env!("NONOGRAM_PUBLIC_URL") {
+    Some(url) => url,
+    None => "",
+};
+
+/// Where `social_card` serves the preview image for `puzzle`.
+fn card_url(puzzle: &PuzzleRef) -> String {
+    let origin = PUBLIC_URL.trim_end_matches('/');
+    match puzzle.kind {
+        PuzzleKind::BlackWhite => format!("{origin}/api/card/bw:{}.png", puzzle.id),
+        PuzzleKind::Color => format!("{origin}/api/card/{}.png", puzzle.id),
+    }
+}
+
+fn puzzle_ref_from_input(input: &str) -> Option<PuzzleRef> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return None;
@@ -100,7 +594,7 @@ fn puzzle_id_from_input(input: &str) -> Option<(PuzzleKind, String)> {
         return if id.is_empty() {
             None
         } else {
-            Some((PuzzleKind::BlackWhite, id))
+            Some(PuzzleRef::nonograms_org(PuzzleKind::BlackWhite, id))
         };
     }
     if lower.contains("/nonograms2/") {
@@ -110,12 +604,12 @@ fn puzzle_id_from_input(input: &str) -> Option<(PuzzleKind, String)> {
         return extract_id_with_kind(trimmed, PuzzleKind::BlackWhite);
     }
     if trimmed.chars().all(|c| c.is_ascii_digit()) {
-        return Some((PuzzleKind::Color, trimmed.to_string()));
+        return Some(PuzzleRef::nonograms_org(PuzzleKind::Color, trimmed));
     }
     None
 }
 
-fn extract_id_with_kind(input: &str, kind: PuzzleKind) -> Option<(PuzzleKind, String)> {
+fn extract_id_with_kind(input: &str, kind: PuzzleKind) -> Option<PuzzleRef> {
     let marker = "/i/";
     let start = input.find(marker)? + marker.len();
     let rest = &input[start..];
@@ -123,13 +617,68 @@ fn extract_id_with_kind(input: &str, kind: PuzzleKind) -> Option<(PuzzleKind, St
     if id.is_empty() {
         None
     } else {
-        Some((kind, id))
+        Some(PuzzleRef::nonograms_org(kind, id))
+    }
+}
+
+/// Live feedback for the input box, shown before any fetch happens.
+fn input_status(locale: Locale, input: &str) -> Element {
+    if input.trim().is_empty() {
+        return rsx! {};
+    }
+    match puzzle_ref_from_input(input) {
+        Some(PuzzleRef { kind, id, .. }) => {
+            let kind = match kind {
+                PuzzleKind::Color => locale.text(Msg::KindColor),
+                PuzzleKind::BlackWhite => locale.text(Msg::KindBlackWhite),
+            };
+            rsx! {
+                div { id: "input-status", class: "input-status valid",
+                    {locale.format(Msg::InputDetected, &[&kind, &id])}
+                }
+            }
+        }
+        None => match parse_clue_text(input) {
+            Ok(data) => rsx! {
+                div { id: "input-status", class: "input-status valid",
+                    {locale.format(Msg::InputClues, &[&data.row_groups.len(), &data.col_groups.len()])}
+                }
+            },
+            Err(ClueTextError::NoSections) => rsx! {
+                div { id: "input-status", class: "input-status invalid",
+                    {locale.text(Msg::InputInvalid)}
+                }
+            },
+            Err(err) => rsx! {
+                div { id: "input-status", class: "input-status invalid", "{err}" }
+            },
+        },
+    }
+}
+
+/// Favorites first, then recently loaded puzzles that are not already starred.
+fn suggestions(
+    favorites: &[favorites::Favorite],
+    recent: &[favorites::Favorite],
+) -> Vec<favorites::Favorite> {
+    let mut entries = favorites.to_vec();
+    for entry in recent {
+        if !favorites::is_favorite(&entries, &entry.url) {
+            entries.push(entry.clone());
+        }
     }
+    entries
 }
 
-fn puzzle_kind_param(kind: PuzzleKind) -> String {
-    match kind {
-        PuzzleKind::BlackWhite => "bw".to_string(),
-        PuzzleKind::Color => "color".to_string(),
+fn tab_label(locale: Locale, input: &str) -> String {
+    match puzzle_ref_from_input(input) {
+        Some(PuzzleRef {
+            kind: PuzzleKind::BlackWhite,
+            id,
+            ..
+        }) => format!("bw #{id}"),
+        Some(PuzzleRef { id, .. }) => format!("#{id}"),
+        None if parse_clue_text(input).is_ok() => locale.text(Msg::PastedCluesTitle).to_string(),
+        None => locale.text(Msg::NewPuzzleTab).to_string(),
     }
 }
@@ -1,36 +1,411 @@
-use crate::one_line_solver::OneLineSolver;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::time::Duration;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use web_time::Instant;
+
+pub use crate::one_line_solver::LineRules;
+use crate::one_line_solver::{
+    LineGroup, OneLineSolver, filling_counts, group_owners, group_start_bounds,
+};
+use crate::proof::{Proof, ProofRule};
+use crate::puzzle_builder::{ValidationError, check_color_totals, check_line_lengths};
 use crate::puzzle_crawler::{Group, PuzzleData};
+use crate::symmetry::{Symmetry, detect_symmetry};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Cell masks indexed `[row][col]`; bit `i` set means color `i` is still possible.
+pub type Grid = Vec<Vec<u64>>;
+
+/// How one cell's mask changed between two grids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// The cell went from several candidate colors to exactly this one.
+    Determined(usize),
+    /// Some colors were ruled out, but more than one remains.
+    Narrowed,
+    /// Colors came back, as when stepping backwards or undoing.
+    Widened,
+    /// No color is left, or none of the new colors was possible before.
+    Contradiction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellChange {
+    pub row: usize,
+    pub col: usize,
+    pub before: u64,
+    pub after: u64,
+    pub kind: ChangeKind,
+}
+
+/// Cell-by-cell comparison of two grids, e.g. consecutive solve steps.
+pub trait GridDiff {
+    /// Changed cells in row-major order; cells outside the area both grids
+    /// cover are ignored.
+    fn diff(&self, other: &[Vec<u64>]) -> Vec<CellChange>;
+}
+
+impl GridDiff for [Vec<u64>] {
+    fn diff(&self, other: &[Vec<u64>]) -> Vec<CellChange> {
+        let mut changes = Vec::new();
+        for (row, (before_line, after_line)) in self.iter().zip(other).enumerate() {
+            for (col, (&before, &after)) in before_line.iter().zip(after_line).enumerate() {
+                if before == after {
+                    continue;
+                }
+                let kind = if after & before == 0 {
+                    ChangeKind::Contradiction
+                } else if after & !before != 0 {
+                    ChangeKind::Widened
+                } else if let Some(color) = mask_to_color_index(after) {
+                    ChangeKind::Determined(color)
+                } else {
+                    ChangeKind::Narrowed
+                };
+                changes.push(CellChange {
+                    row,
+                    col,
+                    before,
+                    after,
+                    kind,
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// Cell range of each group in a line, in clue order.
+pub type Placements = Vec<Range<usize>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SolvedPuzzle {
     pub color_panel: Vec<String>,
-    pub grid: Vec<Vec<u64>>,
+    pub grid: Grid,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SolveSteps {
     pub color_panel: Vec<String>,
-    pub steps: Vec<Vec<Vec<u64>>>,
+    pub steps: Vec<Grid>,
+    pub stats: SolveStats,
+}
+
+/// Counters collected while propagating a puzzle to its fixpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SolveStats {
+    /// Full row-then-column sweeps, including the final one that changed nothing.
+    pub passes: usize,
+    /// Calls into the one-line solver; dead lines and lines with no new
+    /// information since their last solve are skipped and not counted.
+    pub line_solves: usize,
+    /// Wall time spent in propagation; always zero without the `std` feature.
+    pub elapsed: Duration,
+    /// Cells first determined while solving rows.
+    pub cells_from_rows: usize,
+    /// Cells first determined while solving columns.
+    pub cells_from_cols: usize,
+    /// Whether every cell was determined; `false` means the strategy stalled
+    /// and, under [`SolveStrategy::LineOnly`], that a guess is needed.
+    pub solved: bool,
+    /// One-line solver work on each row, to see which lines made the puzzle hard.
+    #[serde(default)]
+    pub row_effort: Vec<LineEffort>,
+    /// One-line solver work on each column.
+    #[serde(default)]
+    pub col_effort: Vec<LineEffort>,
+}
+
+/// The one-line solver's work on a single row or column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LineEffort {
+    /// Calls into the one-line solver for this line.
+    pub solves: usize,
+    /// Wall time spent in those calls; always zero without the `std` feature.
+    pub elapsed: Duration,
+}
+
+/// Resource caps for one solve. The default only enforces what the mask
+/// representation needs; `web()` is what the app applies to untrusted puzzles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolverOptions {
+    /// Largest allowed row or column count.
+    pub max_side: usize,
+    /// Largest palette, white included; never more than 63.
+    pub max_colors: usize,
+    /// Most grids recorded in `SolveSteps::steps`, the initial one included.
+    pub max_steps: usize,
+    /// Wall-time budget for propagation; only enforced with the `std` feature.
+    pub time_limit: Option<Duration>,
+    /// How often [`solve_puzzle_steps_watched`] hands out the current grid;
+    /// `None` only snapshots when the time limit is hit. Needs `std`.
+    pub snapshot_every: Option<Duration>,
+    /// Copy each deduction to its mirror cells when the clues are symmetric
+    /// (see [`crate::symmetry`]). Off by default so steps show line logic only.
+    pub use_symmetry: bool,
+    /// Color merges applied to the clues before solving, as for
+    /// [`PuzzleData::recolor`]; `(id, 0)` turns a color into background.
+    pub recolor: Vec<(usize, usize)>,
+    /// How far apart groups must be, for variant puzzles. The line tools
+    /// outside the solve itself keep to the standard rule.
+    pub line_rules: LineRules,
+    /// What to do once line logic stalls. Proved solves always stop there.
+    pub strategy: SolveStrategy,
+    /// Record one changed grid in this many as a step; `1` records every
+    /// pass. The first and final grids are always recorded.
+    pub record_every: usize,
+}
+
+/// How far a solve goes past line logic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolveStrategy {
+    /// Stop where no row or column can be narrowed on its own.
+    #[default]
+    LineOnly,
+    /// Then rule out each color of an open cell that line logic, run from
+    /// that cell, shows to contradict the clues, and carry on.
+    Probing,
+    /// Then guess and backtrack until every cell is determined, settling on
+    /// the first solution found.
+    Full,
+}
+
+/// Choices a user makes about one solve, as sent to `/api/solve`. Applied
+/// on top of the limits in force with [`SolverOptions::with_preferences`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolvePreferences {
+    pub strategy: SolveStrategy,
+    /// Record one changed grid in [`SolvePreferences::SPARSE_EVERY`] instead
+    /// of every one, for long solves.
+    pub sparse_steps: bool,
+    /// Wall-time budget in milliseconds; never beyond the limit in force.
+    pub time_limit_ms: Option<u64>,
+}
+
+impl SolvePreferences {
+    pub const SPARSE_EVERY: usize = 5;
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            max_side: usize::MAX,
+            max_colors: 63,
+            max_steps: usize::MAX,
+            time_limit: None,
+            snapshot_every: None,
+            use_symmetry: false,
+            recolor: Vec::new(),
+            line_rules: LineRules::STANDARD,
+            strategy: SolveStrategy::LineOnly,
+            record_every: 1,
+        }
+    }
+}
+
+impl SolverOptions {
+    /// Limits for puzzles solved on behalf of the web app: at most 100x100
+    /// cells and 300 recorded steps (about 24 MB of masks), within 10 seconds.
+    pub fn web() -> Self {
+        Self {
+            max_side: 100,
+            max_colors: 32,
+            max_steps: 300,
+            time_limit: Some(Duration::from_secs(10)),
+            ..Self::default()
+        }
+    }
+
+    /// These options with `preferences` applied. A requested time limit only
+    /// ever tightens the one already set.
+    pub fn with_preferences(mut self, preferences: &SolvePreferences) -> Self {
+        self.strategy = preferences.strategy;
+        if preferences.sparse_steps {
+            self.record_every = SolvePreferences::SPARSE_EVERY;
+        }
+        if let Some(ms) = preferences.time_limit_ms {
+            let requested = Duration::from_millis(ms);
+            self.time_limit = Some(
+                self.time_limit
+                    .map_or(requested, |limit| limit.min(requested)),
+            );
+        }
+        self
+    }
+
+    /// Reject puzzles whose size alone breaks these limits, before any solving.
+    pub fn check(&self, data: &PuzzleData) -> Result<(), SolveError> {
+        let color_count = data.color_panel.len();
+        if color_count == 0 || color_count > 63 {
+            return Err(SolveError::TooManyColors(color_count));
+        }
+        if color_count > self.max_colors {
+            return Err(SolveError::LimitExceeded(SolveLimit::Colors {
+                count: color_count,
+                max: self.max_colors,
+            }));
+        }
+        let rows = data.row_groups.len();
+        let cols = data.col_groups.len();
+        if rows > self.max_side || cols > self.max_side {
+            return Err(SolveError::LimitExceeded(SolveLimit::GridSide {
+                rows,
+                cols,
+                max: self.max_side,
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// Which `SolverOptions` cap a solve ran into.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveLimit {
+    GridSide {
+        rows: usize,
+        cols: usize,
+        max: usize,
+    },
+    Colors {
+        count: usize,
+        max: usize,
+    },
+    Steps(usize),
+    Time(Duration),
 }
 
 #[derive(Debug)]
 pub enum SolveError {
     TooManyColors(usize),
     Unsolvable,
+    LimitExceeded(SolveLimit),
+    /// The clues are malformed, so there is nothing to propagate.
+    Invalid(ValidationError),
+    /// The starting grid's size or colors do not match the puzzle.
+    StartMismatch,
+    /// A proved solve asked for options [`crate::proof::verify_proof`]
+    /// cannot check.
+    NotProvable,
 }
 
-impl std::fmt::Display for SolveError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::TooManyColors(count) => {
                 write!(f, "too many colors to fit in a u64 mask: {count}")
             }
             Self::Unsolvable => write!(f, "puzzle cannot be solved with current constraints"),
+            Self::Invalid(err) => write!(f, "invalid puzzle: {err}"),
+            Self::StartMismatch => write!(f, "the starting grid does not match the puzzle"),
+            Self::NotProvable => write!(
+                f,
+                "proofs only cover line logic under the standard rules and no color merges"
+            ),
+            Self::LimitExceeded(SolveLimit::GridSide { rows, cols, max }) => write!(
+                f,
+                "the grid is {cols}x{rows}; puzzles here can be at most {max} cells per side"
+            ),
+            Self::LimitExceeded(SolveLimit::Colors { count, max }) => write!(
+                f,
+                "the puzzle uses {count} colors; at most {max} are supported here"
+            ),
+            Self::LimitExceeded(SolveLimit::Steps(max)) => {
+                write!(f, "the solve needed more than {max} steps")
+            }
+            Self::LimitExceeded(SolveLimit::Time(limit)) => write!(
+                f,
+                "the solve took longer than {} seconds",
+                limit.as_secs_f64()
+            ),
         }
     }
 }
 
-impl std::error::Error for SolveError {}
+impl core::error::Error for SolveError {}
+
+/// How propagation ran into a contradiction, from
+/// [`solve_puzzle_steps_traced`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contradiction {
+    /// The recorded steps from the start, then the grid as it stood when
+    /// propagation failed.
+    pub trail: Vec<Grid>,
+    pub conflict: Conflict,
+}
+
+/// Where a contradiction showed up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Conflict {
+    /// No filling of this row's clue fits its cells.
+    Row(usize),
+    /// No filling of this column's clue fits its cells.
+    Column(usize),
+    /// No color is left for this cell.
+    Cell { row: usize, col: usize },
+    /// Every guess of a full search ran into a contradiction.
+    Exhausted,
+}
+
+/// Why the private `solve` stopped: a contradiction keeps its trail for
+/// [`solve_puzzle_steps_traced`] and is [`SolveError::Unsolvable`] elsewhere.
+enum Stop {
+    Error(SolveError),
+    Contradiction(Contradiction),
+    /// The step callback asked to stop, as when a stream's reader went away.
+    Cancelled,
+}
+
+impl Stop {
+    /// The contradiction `conflict`, after `steps` were recorded and the two
+    /// copies of the grid reached `row_masks` and `col_masks`.
+    fn contradiction(
+        mut steps: Vec<Grid>,
+        row_masks: &[Vec<u64>],
+        col_masks: &[Vec<u64>],
+        conflict: Conflict,
+    ) -> Self {
+        let current: Grid = row_masks
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                line.iter()
+                    .enumerate()
+                    .map(|(col, &mask)| mask & col_masks[col][row])
+                    .collect()
+            })
+            .collect();
+        if steps.last() != Some(&current) {
+            steps.push(current);
+        }
+        Self::Contradiction(Contradiction {
+            trail: steps,
+            conflict,
+        })
+    }
+}
+
+impl From<SolveError> for Stop {
+    fn from(err: SolveError) -> Self {
+        Self::Error(err)
+    }
+}
+
+impl From<Stop> for SolveError {
+    fn from(stop: Stop) -> Self {
+        match stop {
+            Stop::Error(err) => err,
+            Stop::Contradiction(_) => Self::Unsolvable,
+            // Only `solve_stream` cancels, and then no one reads the result.
+            Stop::Cancelled => Self::Unsolvable,
+        }
+    }
+}
 
 pub fn solve_puzzle(data: PuzzleData) -> Result<SolvedPuzzle, SolveError> {
     let steps = solve_puzzle_steps(data)?;
@@ -42,10 +417,141 @@ pub fn solve_puzzle(data: PuzzleData) -> Result<SolvedPuzzle, SolveError> {
 }
 
 pub fn solve_puzzle_steps(data: PuzzleData) -> Result<SolveSteps, SolveError> {
-    let color_count = data.color_panel.len();
-    if color_count == 0 || color_count > 63 {
-        return Err(SolveError::TooManyColors(color_count));
+    solve_puzzle_steps_with(data, &SolverOptions::default())
+}
+
+/// `solve_puzzle_steps` under the caps in `options`.
+#[tracing::instrument(
+    skip_all,
+    fields(rows = data.row_groups.len(), cols = data.col_groups.len(), colors = data.color_panel.len())
+)]
+pub fn solve_puzzle_steps_with(
+    data: PuzzleData,
+    options: &SolverOptions,
+) -> Result<SolveSteps, SolveError> {
+    solve_puzzle_steps_watched(data, options, |_: &Grid, _: &SolveStats| {})
+}
+
+/// `solve_puzzle_steps_with`, passing the grid so far to `on_snapshot` every
+/// `options.snapshot_every` and once more before giving up on the time
+/// limit, so a caller can persist partial progress of a long solve.
+pub fn solve_puzzle_steps_watched(
+    data: PuzzleData,
+    options: &SolverOptions,
+    on_snapshot: impl FnMut(&Grid, &SolveStats),
+) -> Result<SolveSteps, SolveError> {
+    Ok(solve(
+        data,
+        options,
+        None,
+        on_snapshot,
+        &mut |_| true,
+        None,
+    )?)
+}
+
+/// `solve_puzzle_steps_with`, handing each recorded step to `on_step` as it
+/// is taken; returning `false` abandons the solve.
+#[cfg(feature = "stream")]
+pub(crate) fn solve_puzzle_steps_until(
+    data: PuzzleData,
+    options: &SolverOptions,
+    mut on_step: impl FnMut(&Grid) -> bool,
+) -> Result<SolveSteps, SolveError> {
+    let no_snapshots = |_: &Grid, _: &SolveStats| {};
+    Ok(solve(
+        data,
+        options,
+        None,
+        no_snapshots,
+        &mut on_step,
+        None,
+    )?)
+}
+
+/// `solve_puzzle_steps_with`, starting from `start` instead of a blank grid,
+/// for following a guess or a partly solved grid to its consequences.
+///
+/// The first step is `start` itself. Fails with [`SolveError::Unsolvable`]
+/// if `start` contradicts the clues.
+pub fn solve_puzzle_steps_from(
+    data: PuzzleData,
+    start: Grid,
+    options: &SolverOptions,
+) -> Result<SolveSteps, SolveError> {
+    Ok(solve(
+        data,
+        options,
+        Some(start),
+        |_: &Grid, _: &SolveStats| {},
+        &mut |_| true,
+        None,
+    )?)
+}
+
+/// `solve_puzzle_steps_from`, or `solve_puzzle_steps_with` if `start` is
+/// `None`, that explains a contradiction instead of failing with
+/// [`SolveError::Unsolvable`]: the inner error replays the propagation that
+/// led to it and names the line or cell where it failed.
+pub fn solve_puzzle_steps_traced(
+    data: PuzzleData,
+    start: Option<Grid>,
+    options: &SolverOptions,
+) -> Result<Result<SolveSteps, Contradiction>, SolveError> {
+    let no_snapshots = |_: &Grid, _: &SolveStats| {};
+    match solve(data, options, start, no_snapshots, &mut |_| true, None) {
+        Ok(steps) => Ok(Ok(steps)),
+        Err(Stop::Contradiction(contradiction)) => Ok(Err(contradiction)),
+        Err(stop) => Err(stop.into()),
     }
+}
+
+/// `solve_puzzle_steps_with`, also logging every deduction with the rule
+/// behind it so [`crate::proof::verify_proof`] can check the solve.
+///
+/// The checker only knows the standard rule, checks against the clues as
+/// given and has no rule for a guess, so other `line_rules`, any `recolor`
+/// or a `strategy` other than [`SolveStrategy::LineOnly`] fail with
+/// [`SolveError::NotProvable`].
+pub fn solve_puzzle_steps_proved(
+    data: PuzzleData,
+    options: &SolverOptions,
+) -> Result<(SolveSteps, Proof), SolveError> {
+    if options.line_rules != LineRules::STANDARD
+        || !options.recolor.is_empty()
+        || options.strategy != SolveStrategy::LineOnly
+    {
+        return Err(SolveError::NotProvable);
+    }
+    let mut proof = Proof::default();
+    let steps = solve(
+        data,
+        options,
+        None,
+        |_: &Grid, _: &SolveStats| {},
+        &mut |_| true,
+        Some(&mut proof),
+    )?;
+    Ok((steps, proof))
+}
+
+fn solve(
+    data: PuzzleData,
+    options: &SolverOptions,
+    start: Option<Grid>,
+    #[cfg_attr(not(feature = "std"), allow(unused_mut, unused_variables))]
+    mut on_snapshot: impl FnMut(&Grid, &SolveStats),
+    on_step: &mut dyn FnMut(&Grid) -> bool,
+    mut proof: Option<&mut Proof>,
+) -> Result<SolveSteps, Stop> {
+    options.check(&data)?;
+    let data = if options.recolor.is_empty() {
+        data
+    } else {
+        data.recolor(&options.recolor)
+            .map_err(SolveError::Invalid)?
+    };
+    let color_count = data.color_panel.len();
     let full_mask = (1u64 << color_count) - 1;
 
     let row_groups = convert_groups(&data.row_groups);
@@ -53,76 +559,877 @@ pub fn solve_puzzle_steps(data: PuzzleData) -> Result<SolveSteps, SolveError> {
 
     let m = row_groups.len();
     let n = col_groups.len();
+    if m == 0 || n == 0 {
+        return Err(SolveError::Invalid(ValidationError::EmptyGrid).into());
+    }
+    check_line_lengths(&data, &options.line_rules).map_err(SolveError::Invalid)?;
+    check_color_totals(&data).map_err(SolveError::Invalid)?;
+    if let Some(start) = &start {
+        let fits =
+            |line: &Vec<u64>| line.len() == n && line.iter().all(|&mask| mask & !full_mask == 0);
+        if start.len() != m || !start.iter().all(fits) {
+            return Err(SolveError::StartMismatch.into());
+        }
+        let empty = start.iter().enumerate().find_map(|(row, line)| {
+            let col = line.iter().position(|&mask| mask == 0)?;
+            Some(Conflict::Cell { row, col })
+        });
+        if let Some(conflict) = empty {
+            return Err(Stop::Contradiction(Contradiction {
+                trail: vec![start.clone()],
+                conflict,
+            }));
+        }
+    }
+    if start.is_none() && row_groups.iter().chain(&col_groups).all(Vec::is_empty) {
+        // Nothing to deduce: the only filling is blank, so skip the sweeps.
+        if let Some(proof) = proof {
+            for row in 0..m {
+                proof.record_line(ProofRule::Row(row), &vec![full_mask; n], &vec![1; n]);
+            }
+        }
+        let blank = vec![vec![1; n]; m];
+        if !on_step(&blank) {
+            return Err(Stop::Cancelled);
+        }
+        return Ok(SolveSteps {
+            color_panel: data.color_panel,
+            steps: vec![blank],
+            stats: SolveStats {
+                solved: true,
+                row_effort: vec![LineEffort::default(); m],
+                col_effort: vec![LineEffort::default(); n],
+                ..SolveStats::default()
+            },
+        });
+    }
 
-    let mut row_masks = vec![vec![full_mask; n]; m];
-    let mut col_masks = vec![vec![full_mask; m]; n];
+    let symmetry = if options.use_symmetry {
+        detect_symmetry(&data)
+    } else {
+        Symmetry::default()
+    };
+    let mut row_masks = start.unwrap_or_else(|| vec![vec![full_mask; n]; m]);
+    let mut col_masks: Grid = (0..n)
+        .map(|col| row_masks.iter().map(|line| line[col]).collect())
+        .collect();
 
-    let mut dead_rows = vec![false; m];
-    let mut dead_cols = vec![false; n];
-    let mut solver = OneLineSolver::new(m.max(n));
+    let mut row_lines = LineFlags::new(m);
+    let mut col_lines = LineFlags::new(n);
+    let mut solver = OneLineSolver::new(m.max(n)).with_rules(options.line_rules);
 
     let mut steps = Vec::new();
-    steps.push(row_masks.clone());
+    record_step(&mut steps, &row_masks, usize::MAX, on_step)?;
+    let mut previous = row_masks.clone();
+    let mut unrecorded = 0;
+    let escalate = options.strategy != SolveStrategy::LineOnly;
+
+    #[cfg(feature = "std")]
+    let started = Instant::now();
+    #[cfg(feature = "std")]
+    let mut last_snapshot = started;
+    let mut stats = SolveStats {
+        row_effort: vec![LineEffort::default(); m],
+        col_effort: vec![LineEffort::default(); n],
+        ..SolveStats::default()
+    };
+    let mut determined: Vec<Vec<bool>> = row_masks
+        .iter()
+        .map(|line| line.iter().map(|&mask| is_single_bit(mask)).collect())
+        .collect();
 
-    let mut prev_sum = u64::MAX;
     loop {
-        if !update_groups_state(&mut solver, &mut dead_rows, &row_groups, &mut row_masks) {
-            return Err(SolveError::Unsolvable);
+        stats.passes += 1;
+        if let Err(row) = update_groups_state(
+            &mut solver,
+            &mut row_lines,
+            &row_groups,
+            &mut row_masks,
+            &mut stats.row_effort,
+            proof.as_deref_mut(),
+            ProofRule::Row,
+        ) {
+            tracing::debug!(pass = stats.passes, row, "row contradiction");
+            return Err(Stop::contradiction(
+                steps,
+                &row_masks,
+                &col_masks,
+                Conflict::Row(row),
+            ));
         }
-        if !update_groups_state(&mut solver, &mut dead_cols, &col_groups, &mut col_masks) {
-            return Err(SolveError::Unsolvable);
+        stats.cells_from_rows += mark_determined(&row_masks, &mut determined, false);
+        if let Err(col) = update_groups_state(
+            &mut solver,
+            &mut col_lines,
+            &col_groups,
+            &mut col_masks,
+            &mut stats.col_effort,
+            proof.as_deref_mut(),
+            ProofRule::Column,
+        ) {
+            tracing::debug!(pass = stats.passes, col, "column contradiction");
+            return Err(Stop::contradiction(
+                steps,
+                &row_masks,
+                &col_masks,
+                Conflict::Column(col),
+            ));
         }
+        stats.cells_from_cols += mark_determined(&col_masks, &mut determined, true);
+        stats.line_solves = stats
+            .row_effort
+            .iter()
+            .chain(&stats.col_effort)
+            .map(|effort| effort.solves)
+            .sum();
 
-        let cur_sum = update_cell_values(&mut row_masks, &mut col_masks);
-        if cur_sum == prev_sum {
-            break;
+        let mut changed = update_cell_values(
+            &mut row_masks,
+            &mut col_masks,
+            &mut row_lines,
+            &mut col_lines,
+        );
+        if symmetry.any()
+            && let Err((row, col)) = mirror_cells(
+                symmetry,
+                &mut row_masks,
+                &mut col_masks,
+                &mut row_lines,
+                &mut col_lines,
+                &mut changed,
+                proof.as_deref_mut(),
+            )
+        {
+            tracing::debug!(pass = stats.passes, row, col, "symmetry contradiction");
+            return Err(Stop::contradiction(
+                steps,
+                &row_masks,
+                &col_masks,
+                Conflict::Cell { row, col },
+            ));
         }
-        prev_sum = cur_sum;
-        steps.push(row_masks.clone());
+        tracing::debug!(
+            pass = stats.passes,
+            line_solves = stats.line_solves,
+            determined = stats.cells_from_rows + stats.cells_from_cols,
+            changed = changed.len(),
+            "finished pass"
+        );
+        // Both copies can reach the same answer independently, so an empty
+        // intersection diff alone does not mean the pass was idle.
+        let mut grid_changed = previous != row_masks;
+        if changed.is_empty() && !grid_changed {
+            if !escalate || determined.iter().flatten().all(|&cell| cell) {
+                break;
+            }
+            #[cfg(feature = "std")]
+            let out_of_time = || {
+                options
+                    .time_limit
+                    .is_some_and(|limit| started.elapsed() > limit)
+            };
+            #[cfg(not(feature = "std"))]
+            let out_of_time = || false;
+            let probed = probe_cells(
+                &row_groups,
+                &col_groups,
+                &mut row_masks,
+                &mut solver,
+                &out_of_time,
+            );
+            let narrowed = match probed {
+                Ok(narrowed) => narrowed,
+                Err((row, col)) => {
+                    return Err(Stop::contradiction(
+                        steps,
+                        &row_masks,
+                        &col_masks,
+                        Conflict::Cell { row, col },
+                    ));
+                }
+            };
+            // Out of time, fall through to the limit check below.
+            if !narrowed && !out_of_time() {
+                if options.strategy != SolveStrategy::Full {
+                    break;
+                }
+                match search(
+                    &row_groups,
+                    &col_groups,
+                    row_masks.clone(),
+                    &mut solver,
+                    &out_of_time,
+                ) {
+                    Some(solution) => row_masks = solution,
+                    None if out_of_time() => {}
+                    None => {
+                        return Err(Stop::contradiction(
+                            steps,
+                            &row_masks,
+                            &col_masks,
+                            Conflict::Exhausted,
+                        ));
+                    }
+                }
+            }
+            resync_columns(&row_masks, &mut col_masks, &mut row_lines, &mut col_lines);
+            // Cells settled by a probe or a guess are credited to neither
+            // rows nor columns.
+            mark_determined(&row_masks, &mut determined, false);
+            grid_changed = previous != row_masks;
+        }
+        if grid_changed {
+            previous.clone_from(&row_masks);
+            unrecorded += 1;
+            if unrecorded >= options.record_every {
+                record_step(&mut steps, &row_masks, options.max_steps, on_step)?;
+                unrecorded = 0;
+            }
+        }
+        #[cfg(feature = "std")]
+        if let Some(limit) = options.time_limit
+            && started.elapsed() > limit
+        {
+            stats.elapsed = started.elapsed();
+            on_snapshot(&row_masks, &stats);
+            return Err(SolveError::LimitExceeded(SolveLimit::Time(limit)).into());
+        }
+        #[cfg(feature = "std")]
+        if let Some(every) = options.snapshot_every
+            && last_snapshot.elapsed() >= every
+        {
+            stats.elapsed = started.elapsed();
+            on_snapshot(&row_masks, &stats);
+            last_snapshot = Instant::now();
+        }
+    }
+    if steps.last() != Some(&row_masks) {
+        record_step(&mut steps, &row_masks, options.max_steps, on_step)?;
+    }
+    #[cfg(feature = "std")]
+    {
+        stats.elapsed = started.elapsed();
     }
+    stats.solved = determined.iter().flatten().all(|&cell| cell);
+    tracing::info!(
+        passes = stats.passes,
+        line_solves = stats.line_solves,
+        elapsed_ms = stats.elapsed.as_secs_f64() * 1000.0,
+        solved = stats.solved,
+        "solve finished"
+    );
 
     Ok(SolveSteps {
         color_panel: data.color_panel,
         steps,
+        stats,
     })
 }
 
-fn convert_groups(groups: &[Vec<Group>]) -> Vec<Vec<(usize, usize)>> {
+/// Record `grid` as the next step and hand it to `on_step`, which stops
+/// the solve by returning `false`.
+fn record_step(
+    steps: &mut Vec<Grid>,
+    grid: &Grid,
+    max_steps: usize,
+    on_step: &mut dyn FnMut(&Grid) -> bool,
+) -> Result<(), Stop> {
+    if steps.len() >= max_steps {
+        return Err(SolveError::LimitExceeded(SolveLimit::Steps(max_steps)).into());
+    }
+    steps.push(grid.clone());
+    if on_step(grid) {
+        Ok(())
+    } else {
+        Err(Stop::Cancelled)
+    }
+}
+
+/// Copy cells narrowed in `row_masks` outside the line sweeps into
+/// `col_masks`, marking their lines dirty.
+fn resync_columns(
+    row_masks: &[Vec<u64>],
+    col_masks: &mut [Vec<u64>],
+    rows: &mut LineFlags,
+    cols: &mut LineFlags,
+) {
+    for (row, line) in row_masks.iter().enumerate() {
+        for (col, &mask) in line.iter().enumerate() {
+            if col_masks[col][row] != mask {
+                col_masks[col][row] = mask;
+                rows.dirty[row] = true;
+                cols.dirty[col] = true;
+            }
+        }
+    }
+}
+
+/// Flag newly single-colored cells and return how many there were.
+///
+/// `transposed` is set when `masks` is indexed column-first.
+fn mark_determined(masks: &[Vec<u64>], determined: &mut [Vec<bool>], transposed: bool) -> usize {
+    let mut count = 0;
+    for (line_idx, line) in masks.iter().enumerate() {
+        for (cell_idx, mask) in line.iter().enumerate() {
+            let (row, col) = if transposed {
+                (cell_idx, line_idx)
+            } else {
+                (line_idx, cell_idx)
+            };
+            if !determined[row][col] && is_single_bit(*mask) {
+                determined[row][col] = true;
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+fn convert_groups(groups: &[Vec<Group>]) -> Vec<Vec<LineGroup>> {
+    groups.iter().map(|line| line_groups(line)).collect()
+}
+
+pub(crate) fn line_groups(groups: &[Group]) -> Vec<LineGroup> {
     groups
         .iter()
-        .map(|row| row.iter().map(|g| (g.len, g.color_id)).collect())
+        .map(|g| LineGroup {
+            min: g.len.min(),
+            max: g.len.max().unwrap_or(usize::MAX),
+            color: g.color_id,
+        })
         .collect()
 }
 
+/// Per-line bookkeeping for one direction (all rows or all columns).
+struct LineFlags {
+    /// Every cell in this line's own copy has a single color; it is never re-solved.
+    dead: Vec<bool>,
+    /// Some cell changed since the line was last solved.
+    dirty: Vec<bool>,
+    /// Dead and already intersected, so both copies of its cells agree for good.
+    settled: Vec<bool>,
+}
+
+impl LineFlags {
+    fn new(len: usize) -> Self {
+        Self {
+            dead: vec![false; len],
+            dirty: vec![true; len],
+            settled: vec![false; len],
+        }
+    }
+}
+
 fn update_groups_state(
     solver: &mut OneLineSolver,
-    dead: &mut [bool],
-    groups: &[Vec<(usize, usize)>],
+    lines: &mut LineFlags,
+    groups: &[Vec<LineGroup>],
     masks: &mut [Vec<u64>],
-) -> bool {
+    effort: &mut [LineEffort],
+    mut proof: Option<&mut Proof>,
+    rule: fn(usize) -> ProofRule,
+) -> Result<(), usize> {
     for (idx, group) in groups.iter().enumerate() {
-        if dead[idx] {
+        if lines.dead[idx] || !lines.dirty[idx] {
             continue;
         }
-        if !solver.update_state(group, &mut masks[idx]) {
-            return false;
+        lines.dirty[idx] = false;
+        effort[idx].solves += 1;
+        let before = proof.is_some().then(|| masks[idx].clone());
+        #[cfg(feature = "std")]
+        let started = Instant::now();
+        let fits = solver.update_state(group, &mut masks[idx]);
+        #[cfg(feature = "std")]
+        {
+            effort[idx].elapsed += started.elapsed();
+        }
+        if !fits {
+            return Err(idx);
         }
-        dead[idx] = masks[idx].iter().all(|mask| is_single_bit(*mask));
+        if let (Some(proof), Some(before)) = (proof.as_deref_mut(), before) {
+            proof.record_line(rule(idx), &before, &masks[idx]);
+        }
+        lines.dead[idx] = masks[idx].iter().all(|mask| is_single_bit(*mask));
     }
+    Ok(())
+}
+
+/// Intersect the row and column copies of every cell that can still change.
+///
+/// Returns the `(row, col)` cells that narrowed and marks their lines dirty
+/// for the next pass; cells on settled lines are skipped.
+fn update_cell_values(
+    row_masks: &mut [Vec<u64>],
+    col_masks: &mut [Vec<u64>],
+    rows: &mut LineFlags,
+    cols: &mut LineFlags,
+) -> Vec<(usize, usize)> {
+    let mut changed = Vec::new();
+    for (row, row_line) in row_masks.iter_mut().enumerate() {
+        if rows.settled[row] {
+            continue;
+        }
+        for (col, row_cell) in row_line.iter_mut().enumerate() {
+            if cols.settled[col] {
+                continue;
+            }
+            let col_cell = &mut col_masks[col][row];
+            let combined = *row_cell & *col_cell;
+            if combined != *row_cell {
+                rows.dirty[row] = true;
+            }
+            if combined != *col_cell {
+                cols.dirty[col] = true;
+            }
+            if combined != *row_cell || combined != *col_cell {
+                changed.push((row, col));
+            }
+            *row_cell = combined;
+            *col_cell = combined;
+        }
+    }
+    for (settled, dead) in rows.settled.iter_mut().zip(&rows.dead) {
+        *settled |= *dead;
+    }
+    for (settled, dead) in cols.settled.iter_mut().zip(&cols.dead) {
+        *settled |= *dead;
+    }
+    changed
+}
+
+/// Narrow every cell to what its mirror cells allow, after
+/// `update_cell_values` has made both copies of each cell agree.
+///
+/// Narrowed cells are added to `changed`, their lines marked dirty and, if
+/// `proof` is given, logged there. Fails with the cell if it and its mirror
+/// have no color in common.
+fn mirror_cells(
+    symmetry: Symmetry,
+    row_masks: &mut [Vec<u64>],
+    col_masks: &mut [Vec<u64>],
+    rows: &mut LineFlags,
+    cols: &mut LineFlags,
+    changed: &mut Vec<(usize, usize)>,
+    mut proof: Option<&mut Proof>,
+) -> Result<(), (usize, usize)> {
+    let (m, n) = (row_masks.len(), col_masks.len());
+    for row in 0..m {
+        for col in 0..n {
+            for (mirror_row, mirror_col) in symmetry.images(row, col, m, n) {
+                let combined = row_masks[row][col] & row_masks[mirror_row][mirror_col];
+                if combined == 0 {
+                    return Err((row, col));
+                }
+                let (cell, mirror) = ((row, col), (mirror_row, mirror_col));
+                for ((r, c), from) in [(cell, mirror), (mirror, cell)] {
+                    if row_masks[r][c] != combined {
+                        if let Some(proof) = proof.as_deref_mut() {
+                            proof.record_mirror((r, c), from, combined);
+                        }
+                        row_masks[r][c] = combined;
+                        col_masks[c][r] = combined;
+                        rows.dirty[r] = true;
+                        cols.dirty[c] = true;
+                        changed.push((r, c));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Leftmost and rightmost placement of each group in a line, as cell ranges.
+///
+/// `cells` holds the line's current masks; each range spans the group's cells
+/// when it is packed as far left (first list) or right (second list) as any
+/// valid filling allows. Cells covered by both ranges of a group are that
+/// group's color in every filling. Returns `None` if the line has no valid filling.
+///
+/// Groups without an exact length are given their shortest length, so only
+/// their start positions are exact.
+pub fn line_extremes(groups: &[Group], cells: &[u64]) -> Option<(Placements, Placements)> {
+    let bounds = group_start_bounds(&line_groups(groups), cells)?;
+    Some(
+        bounds
+            .iter()
+            .zip(groups)
+            .map(|(&(left, right), group)| {
+                let len = group.len.min();
+                (left..left + len, right..right + len)
+            })
+            .unzip(),
+    )
+}
+
+/// For each cell of a line, the index of the clue group that covers it in
+/// every valid filling, or `None` if it may be white or belong to different
+/// groups. Returns `None` if the line has no valid filling.
+pub fn group_ownership(groups: &[Group], cells: &[u64]) -> Option<Vec<Option<usize>>> {
+    group_owners(&line_groups(groups), cells)
+}
+
+/// Narrow one line's masks in place to the colors some valid filling allows.
+///
+/// Returns `false`, leaving `cells` untouched, if no filling fits.
+pub fn solve_line(groups: &[Group], cells: &mut [u64]) -> bool {
+    let mut solved = cells.to_vec();
+    if !OneLineSolver::new(cells.len()).update_state(&line_groups(groups), &mut solved) {
+        return false;
+    }
+    cells.copy_from_slice(&solved);
     true
 }
 
-fn update_cell_values(row_masks: &mut [Vec<u64>], col_masks: &mut [Vec<u64>]) -> u64 {
-    let mut total: u64 = 0;
-    for row in 0..row_masks.len() {
-        for col in 0..row_masks[row].len() {
-            let combined = row_masks[row][col] & col_masks[col][row];
-            row_masks[row][col] = combined;
-            col_masks[col][row] = combined;
-            total = total.wrapping_add(combined);
+/// Cells that become single-color after re-solving just row `row` and column
+/// `col`, as `(row, col, color)`; meant for an assist that runs after each move.
+///
+/// Cells already solved in `grid` are not reported. Returns `None` if either
+/// line has no valid filling or `grid` does not match the puzzle.
+pub fn forced_cells(
+    data: &PuzzleData,
+    grid: &[Vec<u64>],
+    row: usize,
+    col: usize,
+) -> Option<Vec<(usize, usize, usize)>> {
+    let row_groups = data.row_groups.get(row)?;
+    let col_groups = data.col_groups.get(col)?;
+    let cols = data.col_groups.len();
+    if grid.len() != data.row_groups.len() || grid.iter().any(|line| line.len() != cols) {
+        return None;
+    }
+
+    let newly_solved = |before: u64, after: u64| {
+        mask_to_color_index(before)
+            .is_none()
+            .then(|| mask_to_color_index(after))
+            .flatten()
+    };
+    let mut forced = Vec::new();
+    let mut row_cells = grid[row].clone();
+    if !solve_line(row_groups, &mut row_cells) {
+        return None;
+    }
+    for (c, (&before, &after)) in grid[row].iter().zip(&row_cells).enumerate() {
+        if let Some(color) = newly_solved(before, after) {
+            forced.push((row, c, color));
+        }
+    }
+    let before_col: Vec<u64> = grid.iter().map(|line| line[col]).collect();
+    let mut col_cells = before_col.clone();
+    // The row pass may already have narrowed the crossing cell.
+    col_cells[row] = row_cells[col];
+    if !solve_line(col_groups, &mut col_cells) {
+        return None;
+    }
+    for (r, (&before, &after)) in before_col.iter().zip(&col_cells).enumerate() {
+        if r == row && forced.iter().any(|&(_, c, _)| c == col) {
+            continue;
+        }
+        if let Some(color) = newly_solved(before, after) {
+            forced.push((r, col, color));
+        }
+    }
+    Some(forced)
+}
+
+/// Which clue groups are already fully placed in a grid, per line and in clue order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClueStatus {
+    pub rows: Vec<Vec<bool>>,
+    pub cols: Vec<Vec<bool>>,
+}
+
+/// Mark each group done once its position is forced and its cells are solved.
+///
+/// Groups without an exact length are never marked done: a forced start and
+/// solved cells do not say where they end.
+///
+/// Groups in a line with no valid filling stay open. Returns `None` if `grid`
+/// does not match the puzzle's dimensions.
+pub fn clue_status(data: &PuzzleData, grid: &[Vec<u64>]) -> Option<ClueStatus> {
+    let rows = grid.len();
+    let cols = grid.first().map_or(0, Vec::len);
+    if data.row_groups.len() != rows
+        || data.col_groups.len() != cols
+        || grid.iter().any(|row| row.len() != cols)
+    {
+        return None;
+    }
+
+    let line_status = |groups: &[Group], cells: &[u64]| -> Vec<bool> {
+        let Some((left, right)) = line_extremes(groups, cells) else {
+            return vec![false; groups.len()];
+        };
+        groups
+            .iter()
+            .zip(left.into_iter().zip(right))
+            .map(|(group, (left, right))| {
+                group.len.exact().is_some()
+                    && left == right
+                    && cells[left]
+                        .iter()
+                        .all(|&mask| mask_to_color_index(mask) == Some(group.color_id))
+            })
+            .collect()
+    };
+
+    let row_status = data
+        .row_groups
+        .iter()
+        .zip(grid)
+        .map(|(groups, cells)| line_status(groups, cells))
+        .collect();
+    let col_status = data
+        .col_groups
+        .iter()
+        .enumerate()
+        .map(|(col, groups)| {
+            let cells: Vec<u64> = grid.iter().map(|row| row[col]).collect();
+            line_status(groups, &cells)
+        })
+        .collect();
+    Some(ClueStatus {
+        rows: row_status,
+        cols: col_status,
+    })
+}
+
+/// Estimate `probabilities[row][col][color]` for every cell of a partially solved grid.
+///
+/// Each line's filling counts give a per-cell color distribution; the row and
+/// column estimates are multiplied and renormalized, treating them as independent.
+/// Returns `None` if some line has no valid filling under `grid`.
+pub fn cell_probabilities(data: &PuzzleData, grid: &[Vec<u64>]) -> Option<Vec<Vec<Vec<f64>>>> {
+    let color_count = data.color_panel.len();
+    let row_groups = convert_groups(&data.row_groups);
+    let col_groups = convert_groups(&data.col_groups);
+    let rows = grid.len();
+    let cols = grid.first().map_or(0, |row| row.len());
+    if row_groups.len() != rows || col_groups.len() != cols {
+        return None;
+    }
+
+    let line_distribution = |groups: &[LineGroup], cells: &[u64]| {
+        let filling = filling_counts(groups, cells)?;
+        Some(
+            filling
+                .counts
+                .into_iter()
+                .map(|counts| {
+                    let mut dist = vec![0.0; color_count];
+                    for (color, count) in counts.into_iter().enumerate().take(color_count) {
+                        dist[color] = count / filling.total;
+                    }
+                    dist
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let mut probabilities = Vec::with_capacity(rows);
+    for (row, cells) in grid.iter().enumerate() {
+        probabilities.push(line_distribution(&row_groups[row], cells)?);
+    }
+    for (col, groups) in col_groups.iter().enumerate() {
+        let cells: Vec<u64> = grid.iter().map(|line| line[col]).collect();
+        let col_dist = line_distribution(groups, &cells)?;
+        for (row, dist) in col_dist.into_iter().enumerate() {
+            let cell = &mut probabilities[row][col];
+            for (p, q) in cell.iter_mut().zip(dist) {
+                *p *= q;
+            }
+            let norm: f64 = cell.iter().sum();
+            if norm == 0.0 {
+                return None;
+            }
+            for p in cell.iter_mut() {
+                *p /= norm;
+            }
+        }
+    }
+    Some(probabilities)
+}
+
+/// A cell and color to try when line logic stalls, from [`best_guess`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuessSuggestion {
+    pub row: usize,
+    pub col: usize,
+    pub color: usize,
+    /// Estimated chance the guess is right, from [`cell_probabilities`].
+    pub probability: f64,
+    /// Other cells that line logic determines once the guess is made.
+    pub cells_determined: usize,
+    /// `probability * cells_determined`: what trying this guess is expected
+    /// to settle.
+    pub expected_gain: f64,
+}
+
+/// Probe every open cell and color of a stalled grid and suggest the guess
+/// expected to determine the most cells.
+///
+/// Each probe fixes one cell to one color and propagates line logic to a
+/// fixpoint, so this costs one propagation per open cell and candidate
+/// color. Colors that lead to a contradiction are not guesses (line logic
+/// with a lookahead would rule them out) and are skipped. Returns `None` if
+/// `grid` is solved, does not match the puzzle, or has no consistent guess.
+pub fn best_guess(data: &PuzzleData, grid: &[Vec<u64>]) -> Option<GuessSuggestion> {
+    let probabilities = cell_probabilities(data, grid)?;
+    let row_groups = convert_groups(&data.row_groups);
+    let col_groups = convert_groups(&data.col_groups);
+    let mut solver = OneLineSolver::new(grid.len().max(col_groups.len()));
+    let determined = |grid: &[Vec<u64>]| {
+        grid.iter()
+            .flatten()
+            .filter(|&&mask| is_single_bit(mask))
+            .count()
+    };
+    let already = determined(grid);
+
+    let mut best: Option<GuessSuggestion> = None;
+    for (row, line) in grid.iter().enumerate() {
+        for (col, &mask) in line.iter().enumerate() {
+            if is_single_bit(mask) {
+                continue;
+            }
+            for color in (0..64).filter(|&color| mask >> color & 1 == 1) {
+                let probability = probabilities[row][col].get(color).copied().unwrap_or(0.0);
+                let mut trial = grid.to_vec();
+                trial[row][col] = 1 << color;
+                if !propagate(&row_groups, &col_groups, &mut trial, &mut solver) {
+                    continue;
+                }
+                let cells_determined = determined(&trial) - already - 1;
+                let expected_gain = probability * cells_determined as f64;
+                if best
+                    .as_ref()
+                    .is_none_or(|best| expected_gain > best.expected_gain)
+                {
+                    best = Some(GuessSuggestion {
+                        row,
+                        col,
+                        color,
+                        probability,
+                        cells_determined,
+                        expected_gain,
+                    });
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Rule out every color of an open cell that [`propagate`], run from the
+/// cell set to that color, shows to contradict the clues.
+///
+/// Returns whether anything was ruled out, or fails with a cell that has no
+/// color left, i.e. the puzzle has no solution. Once `out_of_time` says to
+/// give up it stops early, keeping what it ruled out so far.
+fn probe_cells(
+    row_groups: &[Vec<LineGroup>],
+    col_groups: &[Vec<LineGroup>],
+    grid: &mut Grid,
+    solver: &mut OneLineSolver,
+    out_of_time: &dyn Fn() -> bool,
+) -> Result<bool, (usize, usize)> {
+    let mut narrowed = false;
+    for row in 0..grid.len() {
+        for col in 0..grid[row].len() {
+            let mask = grid[row][col];
+            if out_of_time() {
+                return Ok(narrowed);
+            }
+            if is_single_bit(mask) {
+                continue;
+            }
+            for color in (0..64).filter(|&color| mask >> color & 1 == 1) {
+                let mut trial = grid.clone();
+                trial[row][col] = 1 << color;
+                if !propagate(row_groups, col_groups, &mut trial, solver) {
+                    grid[row][col] &= !(1 << color);
+                    narrowed = true;
+                }
+            }
+            if grid[row][col] == 0 {
+                return Err((row, col));
+            }
+        }
+    }
+    Ok(narrowed)
+}
+
+/// Depth-first search for a filling of `grid`, guessing at an open cell with
+/// the fewest colors left and propagating after each guess. Returns `None`
+/// if there is none, or once `out_of_time` says to give up.
+fn search(
+    row_groups: &[Vec<LineGroup>],
+    col_groups: &[Vec<LineGroup>],
+    mut grid: Grid,
+    solver: &mut OneLineSolver,
+    out_of_time: &dyn Fn() -> bool,
+) -> Option<Grid> {
+    if out_of_time() || !propagate(row_groups, col_groups, &mut grid, solver) {
+        return None;
+    }
+    let open = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .map(move |(col, &mask)| (row, col, mask))
+        })
+        .filter(|&(_, _, mask)| !is_single_bit(mask))
+        .min_by_key(|&(_, _, mask)| mask.count_ones());
+    let Some((row, col, mask)) = open else {
+        return Some(grid);
+    };
+    for color in (0..64).filter(|&color| mask >> color & 1 == 1) {
+        let mut trial = grid.clone();
+        trial[row][col] = 1 << color;
+        if let Some(solution) = search(row_groups, col_groups, trial, solver, out_of_time) {
+            return Some(solution);
+        }
+    }
+    None
+}
+
+/// Re-solve every row and column of `grid` until nothing changes.
+///
+/// Returns `false` if some line has no valid filling.
+fn propagate(
+    row_groups: &[Vec<LineGroup>],
+    col_groups: &[Vec<LineGroup>],
+    grid: &mut [Vec<u64>],
+    solver: &mut OneLineSolver,
+) -> bool {
+    let mut column = Vec::with_capacity(grid.len());
+    loop {
+        let mut changed = false;
+        for (groups, line) in row_groups.iter().zip(grid.iter_mut()) {
+            let before = line.clone();
+            if !solver.update_state(groups, line) {
+                return false;
+            }
+            changed |= *line != before;
+        }
+        for (col, groups) in col_groups.iter().enumerate() {
+            column.clear();
+            column.extend(grid.iter().map(|line| line[col]));
+            if !solver.update_state(groups, &mut column) {
+                return false;
+            }
+            for (line, &mask) in grid.iter_mut().zip(&column) {
+                changed |= line[col] != mask;
+                line[col] = mask;
+            }
+        }
+        if !changed {
+            return true;
         }
     }
-    total
 }
 
 fn is_single_bit(mask: u64) -> bool {
@@ -137,10 +1444,43 @@ pub fn mask_to_color_index(mask: u64) -> Option<usize> {
     }
 }
 
+/// Cells each color should cover in the solution, from the row clue sums.
+///
+/// Index 0 (white) gets whatever the colored groups leave over. Groups
+/// without an exact length count their shortest length.
+pub fn expected_color_counts(data: &PuzzleData) -> Vec<usize> {
+    let mut counts = vec![0; data.color_panel.len()];
+    for group in data.row_groups.iter().flatten() {
+        if let Some(count) = counts.get_mut(group.color_id) {
+            *count += group.len.min();
+        }
+    }
+    let total = data.row_groups.len() * data.col_groups.len();
+    let colored: usize = counts.iter().skip(1).sum();
+    if let Some(white) = counts.first_mut() {
+        *white = total.saturating_sub(colored);
+    }
+    counts
+}
+
+/// Cells of each color that are already determined in `grid`.
+pub fn determined_color_counts(grid: &[Vec<u64>], color_count: usize) -> Vec<usize> {
+    let mut counts = vec![0; color_count];
+    for mask in grid.iter().flatten() {
+        if let Some(count) = mask_to_color_index(*mask).and_then(|idx| counts.get_mut(idx)) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::puzzle_crawler::Group;
+    use crate::puzzle_builder::Line;
+    use crate::puzzle_crawler::{Group, GroupLen};
+    use alloc::string::ToString;
+    use alloc::vec;
 
     fn masks_from_color_ids(grid: &[Vec<usize>]) -> Vec<Vec<u64>> {
         grid.iter()
@@ -164,7 +1504,7 @@ mod tests {
                 let len = col - start;
                 if color > 0 && len > 0 {
                     groups.push(Group {
-                        len,
+                        len: GroupLen::Exact(len),
                         color_id: color,
                     });
                 }
@@ -174,18 +1514,19 @@ mod tests {
 
         let mut col_groups = Vec::with_capacity(cols);
         for col in 0..cols {
+            let column: Vec<u64> = grid.iter().map(|line| line[col]).collect();
             let mut groups = Vec::new();
             let mut row = 0;
             while row < rows {
-                let color = mask_to_color_index(grid[row][col]).expect("unsolved cell");
+                let color = mask_to_color_index(column[row]).expect("unsolved cell");
                 let start = row;
-                while row < rows && mask_to_color_index(grid[row][col]).unwrap() == color {
+                while row < rows && mask_to_color_index(column[row]).unwrap() == color {
                     row += 1;
                 }
                 let len = row - start;
                 if color > 0 && len > 0 {
                     groups.push(Group {
-                        len,
+                        len: GroupLen::Exact(len),
                         color_id: color,
                     });
                 }
@@ -219,6 +1560,576 @@ mod tests {
         assert_eq!(col_out, puzzle.col_groups);
     }
 
+    #[test]
+    fn records_solve_stats() {
+        let solved_ids = vec![vec![1, 0, 1], vec![0, 1, 0], vec![1, 0, 1]];
+        let (row_groups, col_groups) = groups_from_grid(&masks_from_color_ids(&solved_ids));
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups,
+            col_groups,
+        };
+
+        let steps = solve_puzzle_steps(puzzle).expect("puzzle should solve");
+        let stats = &steps.stats;
+        assert!(stats.solved);
+        assert_eq!(stats.cells_from_rows + stats.cells_from_cols, 9);
+        assert_eq!(stats.passes, steps.steps.len());
+        assert!(stats.line_solves >= 6);
+        assert_eq!((stats.row_effort.len(), stats.col_effort.len()), (3, 3));
+        let per_line: usize = stats
+            .row_effort
+            .iter()
+            .chain(&stats.col_effort)
+            .map(|effort| effort.solves)
+            .sum();
+        assert_eq!(per_line, stats.line_solves);
+        assert!(stats.row_effort.iter().all(|effort| effort.solves >= 1));
+    }
+
+    #[test]
+    fn intersection_reports_changes_and_skips_settled_lines() {
+        let mut rows = LineFlags::new(2);
+        let mut cols = LineFlags::new(2);
+        rows.dirty.fill(false);
+        cols.dirty.fill(false);
+        rows.dead[0] = true;
+        let mut row_masks = vec![vec![0b10, 0b01], vec![0b11, 0b11]];
+        let mut col_masks = vec![vec![0b11, 0b01], vec![0b01, 0b11]];
+
+        let changed = update_cell_values(&mut row_masks, &mut col_masks, &mut rows, &mut cols);
+        assert_eq!(changed, vec![(0, 0), (1, 0)]);
+        assert_eq!(rows.dirty, vec![false, true]);
+        assert_eq!(cols.dirty, vec![true, false]);
+        assert_eq!(rows.settled, vec![true, false]);
+
+        // Row 0 is settled now, so only row 1's cells are intersected again.
+        row_masks[0][0] = 0b11;
+        let changed = update_cell_values(&mut row_masks, &mut col_masks, &mut rows, &mut cols);
+        assert!(changed.is_empty());
+        assert_eq!(row_masks[0][0], 0b11);
+    }
+
+    #[test]
+    fn enforces_solver_limits() {
+        let solved_ids = vec![vec![0, 1, 0], vec![1, 1, 1], vec![0, 1, 0]];
+        let (row_groups, col_groups) = groups_from_grid(&masks_from_color_ids(&solved_ids));
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups,
+            col_groups,
+        };
+
+        let small = SolverOptions {
+            max_side: 2,
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_with(puzzle.clone(), &small),
+            Err(SolveError::LimitExceeded(SolveLimit::GridSide {
+                max: 2,
+                ..
+            }))
+        ));
+        let one_step = SolverOptions {
+            max_steps: 1,
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_with(puzzle.clone(), &one_step),
+            Err(SolveError::LimitExceeded(SolveLimit::Steps(1)))
+        ));
+        assert!(solve_puzzle_steps_with(puzzle, &SolverOptions::web()).is_ok());
+    }
+
+    #[test]
+    fn symmetry_reaches_the_same_grid() {
+        let solved_ids = vec![
+            vec![1, 0, 0, 0, 1],
+            vec![1, 1, 0, 1, 1],
+            vec![0, 1, 1, 1, 0],
+            vec![1, 1, 0, 1, 1],
+            vec![1, 0, 0, 0, 1],
+        ];
+        let (row_groups, col_groups) = groups_from_grid(&masks_from_color_ids(&solved_ids));
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups,
+            col_groups,
+        };
+
+        let plain = solve_puzzle_steps_with(puzzle.clone(), &SolverOptions::default()).unwrap();
+        let mirrored = SolverOptions {
+            use_symmetry: true,
+            ..SolverOptions::default()
+        };
+        let symmetric = solve_puzzle_steps_with(puzzle, &mirrored).unwrap();
+        assert_eq!(symmetric.steps.last(), plain.steps.last());
+        assert!(symmetric.stats.passes <= plain.stats.passes);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn probing_stops_at_the_time_limit() {
+        // Any permutation matrix fits: line logic settles nothing and every
+        // probe propagates across the whole grid.
+        let side = 40;
+        let mut builder = PuzzleData::builder().palette(["#ffffff", "#000000"]);
+        for _ in 0..side {
+            builder = builder.row([(1, 1)]).col([(1, 1)]);
+        }
+        let options = SolverOptions {
+            strategy: SolveStrategy::Probing,
+            time_limit: Some(Duration::from_millis(20)),
+            ..SolverOptions::default()
+        };
+        let started = Instant::now();
+        let result = solve_puzzle_steps_with(builder.build().unwrap(), &options);
+        assert!(matches!(
+            result,
+            Err(SolveError::LimitExceeded(SolveLimit::Time(_)))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn strategies_go_past_line_logic() {
+        let solved_ids = vec![
+            vec![1, 0, 0, 1, 1],
+            vec![0, 1, 0, 0, 0],
+            vec![1, 1, 0, 0, 0],
+            vec![1, 1, 0, 0, 1],
+            vec![0, 0, 1, 1, 0],
+        ];
+        let solution = masks_from_color_ids(&solved_ids);
+        let (row_groups, col_groups) = groups_from_grid(&solution);
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups,
+            col_groups,
+        };
+        let with = |preferences: SolvePreferences| {
+            let options = SolverOptions::default().with_preferences(&preferences);
+            solve_puzzle_steps_with(puzzle.clone(), &options).unwrap()
+        };
+
+        let line_only = with(SolvePreferences::default());
+        assert!(!line_only.stats.solved);
+        let probed = with(SolvePreferences {
+            strategy: SolveStrategy::Probing,
+            ..SolvePreferences::default()
+        });
+        assert!(probed.stats.solved);
+        assert_eq!(probed.steps.last(), Some(&solution));
+        assert!(probed.steps.starts_with(&line_only.steps));
+
+        let sparse = with(SolvePreferences {
+            strategy: SolveStrategy::Probing,
+            sparse_steps: true,
+            ..SolvePreferences::default()
+        });
+        assert_eq!(sparse.steps.first(), probed.steps.first());
+        assert_eq!(sparse.steps.last(), Some(&solution));
+        assert!(sparse.steps.len() < probed.steps.len());
+
+        // Two diagonals fit these clues: probing rules neither out, a search
+        // settles on one.
+        let ambiguous = PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .row([(1, 1)])
+            .col([(1, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let solve = |strategy| {
+            let options = SolverOptions {
+                strategy,
+                ..SolverOptions::default()
+            };
+            solve_puzzle_steps_with(ambiguous.clone(), &options).unwrap()
+        };
+        assert!(!solve(SolveStrategy::Probing).stats.solved);
+        let full = solve(SolveStrategy::Full);
+        assert!(full.stats.solved);
+        assert!(
+            full.steps
+                .last()
+                .unwrap()
+                .iter()
+                .flatten()
+                .all(|&mask| is_single_bit(mask))
+        );
+
+        let capped = SolverOptions::web().with_preferences(&SolvePreferences {
+            time_limit_ms: Some(60_000),
+            ..SolvePreferences::default()
+        });
+        assert_eq!(capped.time_limit, Some(Duration::from_secs(10)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snapshots_partial_grids() {
+        let solved_ids = vec![vec![0, 1, 0], vec![1, 1, 1], vec![0, 1, 0]];
+        let (row_groups, col_groups) = groups_from_grid(&masks_from_color_ids(&solved_ids));
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups,
+            col_groups,
+        };
+
+        let mut snapshots = Vec::new();
+        let every_pass = SolverOptions {
+            snapshot_every: Some(Duration::ZERO),
+            ..SolverOptions::default()
+        };
+        let steps = solve_puzzle_steps_watched(puzzle.clone(), &every_pass, |grid, _| {
+            snapshots.push(grid.clone());
+        })
+        .unwrap();
+        assert!(!snapshots.is_empty());
+        assert!(snapshots.iter().all(|grid| steps.steps.contains(grid)));
+
+        // Running out of time still hands over the grid reached so far.
+        let mut last = None;
+        let no_time = SolverOptions {
+            time_limit: Some(Duration::ZERO),
+            ..SolverOptions::default()
+        };
+        let result = solve_puzzle_steps_watched(puzzle, &no_time, |grid, stats| {
+            last = Some((grid.clone(), stats.passes));
+        });
+        assert!(matches!(
+            result,
+            Err(SolveError::LimitExceeded(SolveLimit::Time(_)))
+        ));
+        assert_eq!(last, Some((steps.steps[1].clone(), 1)));
+    }
+
+    #[test]
+    fn rejects_grids_without_rows_or_columns() {
+        let palette = vec!["#ffffff".to_string(), "#000000".to_string()];
+        let shapes = [(0, 3), (3, 0), (0, 0)];
+        for (rows, cols) in shapes {
+            let puzzle = PuzzleData {
+                color_panel: palette.clone(),
+                row_groups: vec![Vec::new(); rows],
+                col_groups: vec![Vec::new(); cols],
+            };
+            assert!(
+                matches!(
+                    solve_puzzle_steps(puzzle),
+                    Err(SolveError::Invalid(ValidationError::EmptyGrid))
+                ),
+                "{rows}x{cols}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_mismatched_color_totals_before_solving() {
+        let one = |len| Group {
+            len: GroupLen::Exact(len),
+            color_id: 1,
+        };
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups: vec![vec![one(2)], vec![one(2)]],
+            col_groups: vec![vec![one(2)], vec![one(1)]],
+        };
+        assert!(matches!(
+            solve_puzzle_steps(puzzle),
+            Err(SolveError::Invalid(ValidationError::ColorTotalsDiffer {
+                color_id: 1,
+                rows: 4,
+                cols: 3
+            }))
+        ));
+    }
+
+    #[test]
+    fn rejects_lines_too_short_for_their_clues() {
+        let group = |len, color_id| Group {
+            len: GroupLen::Exact(len),
+            color_id,
+        };
+        // Row 2 needs 3 + 1 + 1 cells in a 4-wide grid; totals still agree.
+        let puzzle = PuzzleData {
+            color_panel: vec![
+                "#ffffff".to_string(),
+                "#000000".to_string(),
+                "#ff0000".to_string(),
+            ],
+            row_groups: vec![vec![], vec![group(3, 1), group(1, 1)]],
+            col_groups: vec![vec![group(1, 1)]; 4],
+        };
+        assert!(matches!(
+            solve_puzzle_steps(puzzle.clone()),
+            Err(SolveError::Invalid(ValidationError::LineTooShort {
+                line: Line::Row(1),
+                needed: 5,
+                available: 4
+            }))
+        ));
+        // The check follows the solve's line rules.
+        let touching_black = SolverOptions {
+            line_rules: LineRules {
+                touching: 0b10,
+                ..LineRules::STANDARD
+            },
+            ..SolverOptions::default()
+        };
+        let steps = solve_puzzle_steps_with(puzzle.clone(), &touching_black).unwrap();
+        assert!(steps.stats.solved);
+
+        // Different colors may touch, unless the rules say otherwise.
+        let mut touching = puzzle;
+        touching.row_groups[1] = vec![group(3, 1), group(1, 2)];
+        touching.col_groups[3] = vec![group(1, 2)];
+        assert!(solve_puzzle_steps(touching.clone()).is_ok());
+        let apart = SolverOptions {
+            line_rules: LineRules {
+                other_color_gap: 1,
+                ..LineRules::STANDARD
+            },
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_with(touching, &apart),
+            Err(SolveError::Invalid(ValidationError::LineTooShort {
+                needed: 5,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn empty_clues_solve_to_a_blank_grid() {
+        for (rows, cols) in [(1, 1), (1, 4), (3, 2)] {
+            let puzzle = PuzzleData {
+                color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+                row_groups: vec![Vec::new(); rows],
+                col_groups: vec![Vec::new(); cols],
+            };
+            let steps = solve_puzzle_steps(puzzle).unwrap();
+            assert_eq!(steps.steps, vec![vec![vec![1; cols]; rows]]);
+            assert!(steps.stats.solved);
+            assert_eq!(steps.stats.passes, 0);
+        }
+    }
+
+    #[test]
+    fn reports_line_extremes() {
+        let groups = [
+            Group {
+                len: GroupLen::Exact(3),
+                color_id: 1,
+            },
+            Group {
+                len: GroupLen::Exact(1),
+                color_id: 2,
+            },
+        ];
+        let (left, right) = line_extremes(&groups, &[0b111; 6]).unwrap();
+        assert_eq!(left, vec![0..3, 3..4]);
+        assert_eq!(right, vec![2..5, 5..6]);
+        assert!(line_extremes(&groups, &[0b111; 3]).is_none());
+    }
+
+    #[test]
+    fn reports_group_ownership() {
+        let groups = [
+            Group {
+                len: GroupLen::Exact(3),
+                color_id: 1,
+            },
+            Group {
+                len: GroupLen::Exact(1),
+                color_id: 2,
+            },
+        ];
+        assert_eq!(
+            group_ownership(&groups, &[0b111; 6]).unwrap(),
+            [None, None, Some(0), None, None, None]
+        );
+        // Same-colored groups tell apart cells the color alone cannot.
+        let pair = [Group::from((2, 1)), Group::from((2, 1))];
+        assert_eq!(
+            group_ownership(&pair, &[0b11; 5]).unwrap(),
+            [Some(0), Some(0), None, Some(1), Some(1)]
+        );
+        assert!(group_ownership(&pair, &[0b11; 4]).is_none());
+    }
+
+    #[test]
+    fn marks_placed_groups_done() {
+        let one = |len| Group {
+            len: GroupLen::Exact(len),
+            color_id: 1,
+        };
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups: vec![vec![one(1), one(1)], vec![one(1)]],
+            col_groups: vec![vec![one(1)], vec![one(1)], vec![one(1)]],
+        };
+        let open = 0b11;
+        // Row 0 is solved as `1 0 1`; row 1 is still open. Column 1's group
+        // can only sit in row 1, but that cell is not filled in yet.
+        let grid = vec![vec![0b10, 0b01, 0b10], vec![open; 3]];
+        let status = clue_status(&puzzle, &grid).unwrap();
+        assert_eq!(status.rows, vec![vec![true, true], vec![false]]);
+        assert_eq!(status.cols, vec![vec![true], vec![false], vec![true]]);
+        assert!(clue_status(&puzzle, &grid[..1]).is_none());
+    }
+
+    #[test]
+    fn finds_cells_forced_by_one_row_and_column() {
+        let one = |len| Group {
+            len: GroupLen::Exact(len),
+            color_id: 1,
+        };
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups: vec![vec![one(3)], vec![one(1)], vec![one(1)]],
+            col_groups: vec![vec![one(1)], vec![one(3)], vec![one(1)]],
+        };
+        let open = 0b11;
+        let grid = vec![vec![open; 3]; 3];
+        // Row 0 fills completely; column 1 then fills too, sharing cell (0, 1).
+        let mut forced = forced_cells(&puzzle, &grid, 0, 1).unwrap();
+        forced.sort_unstable();
+        assert_eq!(
+            forced,
+            vec![(0, 0, 1), (0, 1, 1), (0, 2, 1), (1, 1, 1), (2, 1, 1)]
+        );
+        // Once (2, 1) is filled the rest of row 2 is white; column 0 adds nothing.
+        let mut grid = grid;
+        grid[2][1] = 0b10;
+        assert_eq!(
+            forced_cells(&puzzle, &grid, 2, 0),
+            Some(vec![(2, 0, 0), (2, 2, 0)])
+        );
+        assert!(forced_cells(&puzzle, &grid, 3, 0).is_none());
+    }
+
+    #[test]
+    fn classifies_cell_changes() {
+        let before: Grid = vec![vec![0b111, 0b111, 0b011], vec![0b010, 0b001, 0b011]];
+        let after: Grid = vec![vec![0b100, 0b011, 0b011], vec![0b000, 0b011, 0b100]];
+        let kinds: Vec<_> = before
+            .diff(&after)
+            .iter()
+            .map(|change| (change.row, change.col, change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, 0, ChangeKind::Determined(2)),
+                (0, 1, ChangeKind::Narrowed),
+                (1, 0, ChangeKind::Contradiction),
+                (1, 1, ChangeKind::Widened),
+                (1, 2, ChangeKind::Contradiction),
+            ]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn estimates_probabilities_for_stalled_puzzle() {
+        // Two diagonals share the same clues, so line logic cannot pick one.
+        let puzzle = PuzzleData {
+            color_panel: vec!["#ffffff".to_string(), "#000000".to_string()],
+            row_groups: vec![
+                vec![Group {
+                    len: GroupLen::Exact(1),
+                    color_id: 1
+                }];
+                2
+            ],
+            col_groups: vec![
+                vec![Group {
+                    len: GroupLen::Exact(1),
+                    color_id: 1
+                }];
+                2
+            ],
+        };
+        let steps = solve_puzzle_steps(puzzle.clone()).expect("puzzle should propagate");
+        assert!(!steps.stats.solved);
+
+        let grid = steps.steps.last().unwrap();
+        let probabilities = cell_probabilities(&puzzle, grid).expect("grid is consistent");
+        for cell in probabilities.iter().flatten() {
+            assert!((cell[0] - 0.5).abs() < 1e-9);
+            assert!((cell[1] - 0.5).abs() < 1e-9);
+        }
+
+        // Any guess settles the other three cells, half the time.
+        let guess = best_guess(&puzzle, grid).expect("grid is stalled");
+        assert_eq!((guess.row, guess.col, guess.cells_determined), (0, 0, 3));
+        assert!((guess.expected_gain - 1.5).abs() < 1e-9);
+        let solved = vec![vec![0b10, 0b01], vec![0b01, 0b10]];
+        assert_eq!(best_guess(&puzzle, &solved), None);
+
+        // Following the guess through reaches that grid.
+        let mut guessed = grid.clone();
+        guessed[0][0] = 0b10;
+        let options = SolverOptions::default();
+        let followed = solve_puzzle_steps_from(puzzle.clone(), guessed.clone(), &options).unwrap();
+        assert_eq!(followed.steps.first(), Some(&guessed));
+        assert_eq!(followed.steps.last(), Some(&solved));
+        assert!(followed.stats.solved);
+        let mut clash = guessed.clone();
+        clash[0][1] = 0b10;
+        assert!(matches!(
+            solve_puzzle_steps_from(puzzle.clone(), clash.clone(), &options),
+            Err(SolveError::Unsolvable)
+        ));
+        // Traced, the clash comes back with the row that cannot take it.
+        let contradiction =
+            solve_puzzle_steps_traced(puzzle.clone(), Some(clash.clone()), &options)
+                .unwrap()
+                .unwrap_err();
+        assert_eq!(contradiction.conflict, Conflict::Row(0));
+        assert_eq!(contradiction.trail, vec![clash.clone()]);
+        clash[1][1] = 0;
+        let contradiction = solve_puzzle_steps_traced(puzzle.clone(), Some(clash), &options)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(contradiction.conflict, Conflict::Cell { row: 1, col: 1 });
+        assert!(
+            solve_puzzle_steps_traced(puzzle.clone(), Some(guessed), &options)
+                .unwrap()
+                .is_ok()
+        );
+        assert!(matches!(
+            solve_puzzle_steps_from(puzzle, vec![vec![0b11; 3]; 2], &options),
+            Err(SolveError::StartMismatch)
+        ));
+    }
+
+    #[test]
+    fn counts_expected_and_determined_colors() {
+        let solved_ids = vec![vec![1, 1, 0], vec![2, 0, 0]];
+        let grid = masks_from_color_ids(&solved_ids);
+        let (row_groups, col_groups) = groups_from_grid(&grid);
+        let puzzle = PuzzleData {
+            color_panel: vec![
+                "#ffffff".to_string(),
+                "#ff0000".to_string(),
+                "#0000ff".to_string(),
+            ],
+            row_groups,
+            col_groups,
+        };
+
+        assert_eq!(expected_color_counts(&puzzle), vec![3, 2, 1]);
+        assert_eq!(determined_color_counts(&grid, 3), vec![3, 2, 1]);
+        let partial = vec![vec![0b011, 0b010, 0b001], vec![0b100, 0b101, 0b111]];
+        assert_eq!(determined_color_counts(&partial, 3), vec![1, 1, 1]);
+    }
+
     #[test]
     fn solves_color_puzzle_matches_hints() {
         let solved_ids = vec![vec![1, 1, 1], vec![2, 2, 2], vec![1, 1, 1]];
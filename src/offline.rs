@@ -0,0 +1,113 @@
+//! Solving without a connection.
+//!
+//! The service worker in `public/sw.js` caches the app shell and the WASM
+//! solver. Every fetched puzzle is also kept in IndexedDB, so it loads again
+//! offline, and puzzles asked for while offline wait in a queue, persisted in
+//! `localStorage`, until the browser is back online; they then join the
+//! recent list.
+
+use dioxus::prelude::*;
+
+use crate::components::error_screen::LoadError;
+use crate::components::puzzle_session::download_puzzle;
+use crate::favorites::Favorite;
+use crate::local_store;
+use crate::puzzle_ref_from_input;
+use crate::recent::{self, use_recent};
+use nonogram_solver::puzzle_crawler::{FetchedPuzzle, PuzzleRef};
+
+const PENDING_KEY: &str = "nonogram-solver.pending";
+
+/// Context wrapper so the queue does not collide with other string lists.
+#[derive(Debug, Clone, Copy)]
+pub struct Pending(pub Signal<Vec<String>>);
+
+/// Registers the service worker and drains the queue now and each time the
+/// browser comes back online. Needs the recent list's provider above it.
+pub fn use_offline_provider() -> Signal<Vec<String>> {
+    let Pending(mut pending) = use_context_provider(|| Pending(Signal::new(Vec::new())));
+    let recent_list = use_recent();
+    use_effect(move || {
+        let _ = document::eval(
+            "if ('serviceWorker' in navigator) navigator.serviceWorker.register('/sw.js');",
+        );
+        spawn(async move {
+            if let Some(saved) = local_store::load::<Vec<String>>(PENDING_KEY).await {
+                pending.set(saved);
+            }
+            loop {
+                if is_online().await {
+                    drain(pending, recent_list).await;
+                }
+                if !next_reconnect().await {
+                    break;
+                }
+            }
+        });
+    });
+    pending
+}
+
+pub fn use_pending() -> Signal<Vec<String>> {
+    use_context::<Pending>().0
+}
+
+/// Fetch `input` once the browser is back online.
+pub fn queue(mut pending: Signal<Vec<String>>, input: &str) {
+    if !pending.read().iter().any(|queued| queued == input) {
+        pending.write().push(input.to_string());
+    }
+    local_store::save(PENDING_KEY, &*pending.read());
+}
+
+/// Keep `fetched` for loading `puzzle` without a connection.
+pub fn remember(puzzle: &PuzzleRef, fetched: &FetchedPuzzle) {
+    local_store::db_save(&storage_key(puzzle), fetched);
+}
+
+/// The copy of `puzzle` kept by `remember`, if any.
+pub async fn saved(puzzle: &PuzzleRef) -> Option<FetchedPuzzle> {
+    local_store::db_load(&storage_key(puzzle)).await
+}
+
+/// `navigator.onLine`; true outside the browser.
+pub async fn is_online() -> bool {
+    document::eval("return navigator.onLine;")
+        .join::<bool>()
+        .await
+        .unwrap_or(true)
+}
+
+fn storage_key(puzzle: &PuzzleRef) -> String {
+    format!("puzzle:{}", puzzle.url())
+}
+
+/// Wait for the next `online` event; `false` if there is no browser to ask.
+async fn next_reconnect() -> bool {
+    document::eval(
+        "await new Promise((resolve) => window.addEventListener('online', resolve, { once: true })); return true;",
+    )
+    .join::<bool>()
+    .await
+    .is_ok()
+}
+
+/// Fetch every queued puzzle. Those that fail for lack of a connection or
+/// budget stay queued; those that can never load are dropped.
+async fn drain(mut pending: Signal<Vec<String>>, recent_list: Signal<Vec<Favorite>>) {
+    let queued = pending.read().clone();
+    for input in queued {
+        if let Some(puzzle) = puzzle_ref_from_input(&input) {
+            match download_puzzle(&puzzle).await {
+                Ok(fetched) => {
+                    remember(&puzzle, &fetched);
+                    recent::record(recent_list, Favorite::from_meta(&fetched.meta));
+                }
+                Err(LoadError::Network(_) | LoadError::Server(_) | LoadError::Busy(_)) => continue,
+                Err(_) => {}
+            }
+        }
+        pending.write().retain(|queued| *queued != input);
+        local_store::save(PENDING_KEY, &*pending.read());
+    }
+}
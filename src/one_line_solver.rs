@@ -2,53 +2,152 @@
 //!
 //! Cells store a bitmask of possible colors; bit 0 is reserved for white.
 
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A clue group as the line DP sees it: any length in `min..=max`, one color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineGroup {
+    pub min: usize,
+    /// `usize::MAX` for a group of unknown length.
+    pub max: usize,
+    pub color: usize,
+}
+
+impl From<(usize, usize)> for LineGroup {
+    /// An exact `(len, color)` group.
+    fn from((len, color): (usize, usize)) -> Self {
+        Self {
+            min: len,
+            max: len,
+            color,
+        }
+    }
+}
+
+/// How far apart neighbouring groups of a line must be. The default is the
+/// standard rule: a white cell between groups of the same color, none
+/// between groups of different colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRules {
+    /// Least white cells between neighbouring groups of the same color.
+    pub same_color_gap: usize,
+    /// Least white cells between neighbouring groups of different colors.
+    pub other_color_gap: usize,
+    /// Colors, as a mask with bit `n` for color `n`, whose groups may touch
+    /// the next group of the same color.
+    pub touching: u64,
+}
+
+impl LineRules {
+    pub const STANDARD: Self = Self {
+        same_color_gap: 1,
+        other_color_gap: 0,
+        touching: 0,
+    };
+
+    /// White cells needed between a group of `color` and a following group
+    /// of `next`.
+    pub fn gap(&self, color: usize, next: usize) -> usize {
+        if color != next {
+            self.other_color_gap
+        } else if 1u64
+            .checked_shl(color as u32)
+            .is_some_and(|bit| self.touching & bit != 0)
+        {
+            0
+        } else {
+            self.same_color_gap
+        }
+    }
+}
+
+impl Default for LineRules {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// The scratch buffers are allocated once and only grow, so solving lines no
+/// longer than the first one never allocates.
 #[derive(Debug, Clone)]
 pub struct OneLineSolver {
-    /// Cache marker for memoized states; value is the last update counter.
-    cache: Vec<Vec<u64>>,
-    /// Memoized solvability for (group, cell) within the current update.
-    calc_fill: Vec<Vec<bool>>,
+    /// Cache marker for memoized states, flat over `(group, cell)`; value is the
+    /// last update counter.
+    cache: Vec<u64>,
+    /// Memoized solvability for `(group, cell)` within the current update.
+    calc_fill: Vec<bool>,
+    /// Row length of `cache` and `calc_fill`: the longest line plus one.
+    stride: usize,
     /// Monotonic counter to invalidate `cache` without clearing.
     cache_cnt: u64,
     /// Union of colors that are possible for each cell after solving.
     result_cell: Vec<u64>,
+    /// Separation between groups; the memo is only valid for one rule set.
+    rules: LineRules,
 }
 
 impl OneLineSolver {
     /// Create a solver sized for `line_len` cells.
     pub fn new(line_len: usize) -> Self {
-        let size = line_len + 1;
+        let stride = line_len + 1;
         Self {
-            cache: vec![vec![0; size]; size],
-            calc_fill: vec![vec![false; size]; size],
+            cache: vec![0; stride * stride],
+            calc_fill: vec![false; stride * stride],
+            stride,
             cache_cnt: 0,
             result_cell: vec![0; line_len],
+            rules: LineRules::STANDARD,
         }
     }
 
+    /// Separate groups by `rules` instead of the standard rule, for variant
+    /// puzzles.
+    pub fn with_rules(mut self, rules: LineRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    fn slot(&self, group: usize, cell: usize) -> usize {
+        group * self.stride + cell
+    }
+
     /// Update the state of a line in-place.
     ///
-    /// `groups` is a list of `(length, color_index)` pairs.
     /// `cells` contains bitmasks of possible colors for each position.
     /// Returns `false` if no valid filling exists for the given constraints.
-    pub fn update_state(&mut self, groups: &[(usize, usize)], cells: &mut [u64]) -> bool {
+    ///
+    /// The crate's own tests cross-check short lines against
+    /// [`brute_force_line`]; it is far too slow for debug builds at large.
+    pub fn update_state(&mut self, groups: &[LineGroup], cells: &mut [u64]) -> bool {
+        #[cfg(test)]
+        let mut oracle = [0; ORACLE_MAX_LEN];
+        #[cfg(test)]
+        let expected = brute_force_line(groups, cells, &self.rules, &mut oracle);
+
+        let feasible = self.solve(groups, cells);
+
+        #[cfg(test)]
+        if let Some(expected) = expected {
+            debug_assert_eq!(feasible, expected, "feasibility of {groups:?}");
+            if feasible {
+                debug_assert_eq!(cells, &oracle[..cells.len()], "masks for {groups:?}");
+            }
+        }
+        feasible
+    }
+
+    fn solve(&mut self, groups: &[LineGroup], cells: &mut [u64]) -> bool {
         self.ensure_capacity(cells.len(), groups.len());
 
         self.cache_cnt = self.cache_cnt.wrapping_add(1);
         if self.cache_cnt == 0 {
             // Overflow: clear cache markers and restart the counter.
-            for row in &mut self.cache {
-                for entry in row {
-                    *entry = 0;
-                }
-            }
+            self.cache.fill(0);
             self.cache_cnt = 1;
         }
 
-        self.result_cell.resize(cells.len(), 0);
-        for cell in &mut self.result_cell {
-            *cell = 0;
-        }
+        self.result_cell[..cells.len()].fill(0);
 
         if !self.can_fill(groups, cells, 0, 0) {
             return false;
@@ -59,12 +158,18 @@ impl OneLineSolver {
     }
 
     fn ensure_capacity(&mut self, line_len: usize, group_len: usize) {
-        let needed = line_len.max(group_len) + 1;
-        if self.cache.len() < needed {
-            self.cache = vec![vec![0; needed]; needed];
-            self.calc_fill = vec![vec![false; needed]; needed];
+        let stride = self.stride.max(line_len + 1);
+        let needed = stride * (group_len + 1);
+        if stride != self.stride || self.cache.len() < needed {
+            let size = stride * stride.max(group_len + 1);
+            self.cache = vec![0; size];
+            self.calc_fill = vec![false; size];
+            self.stride = stride;
             self.cache_cnt = 0;
         }
+        if self.result_cell.len() < line_len {
+            self.result_cell.resize(line_len, 0);
+        }
     }
 
     fn color_mask(color: usize) -> Option<u64> {
@@ -78,12 +183,9 @@ impl OneLineSolver {
         let Some(mask) = Self::color_mask(color) else {
             return false;
         };
-        for idx in l_bound..=r_bound {
-            if (cells[idx] & mask) == 0 {
-                return false;
-            }
-        }
-        true
+        cells[l_bound..=r_bound]
+            .iter()
+            .all(|cell| (cell & mask) != 0)
     }
 
     fn set_place_color(&mut self, color: usize, l_bound: usize, r_bound: usize) {
@@ -97,7 +199,7 @@ impl OneLineSolver {
 
     fn can_fill(
         &mut self,
-        groups: &[(usize, usize)],
+        groups: &[LineGroup],
         cells: &[u64],
         cur_group: usize,
         cur_cell: usize,
@@ -105,8 +207,9 @@ impl OneLineSolver {
         if cur_cell == cells.len() {
             return cur_group == groups.len();
         }
-        if self.cache[cur_group][cur_cell] == self.cache_cnt {
-            return self.calc_fill[cur_group][cur_cell];
+        let slot = self.slot(cur_group, cur_cell);
+        if self.cache[slot] == self.cache_cnt {
+            return self.calc_fill[slot];
         }
 
         let mut answer = false;
@@ -119,52 +222,401 @@ impl OneLineSolver {
         }
 
         if cur_group < groups.len() {
-            let (group_len, cur_color) = groups[cur_group];
-            if group_len > 0 {
-                let Some(end_exclusive) = cur_cell.checked_add(group_len) else {
-                    self.calc_fill[cur_group][cur_cell] = answer;
-                    self.cache[cur_group][cur_cell] = self.cache_cnt;
-                    return answer;
-                };
-                let r_bound = end_exclusive - 1;
+            let group = groups[cur_group];
+            let longest = group.max.min(cells.len() - cur_cell);
+            // White cells the rules put between this group and the next.
+            let gap = groups
+                .get(cur_group + 1)
+                .map_or(0, |next| self.rules.gap(group.color, next.color));
+            for group_len in group.min.max(1)..=longest {
                 let l_bound = cur_cell;
-
-                let mut can_place = Self::can_place_color(cells, cur_color, l_bound, r_bound);
-                let mut place_white = false;
+                let r_bound = cur_cell + group_len - 1;
+                if !Self::can_place_color(cells, group.color, l_bound, r_bound) {
+                    // Every longer run covers this one too.
+                    break;
+                }
                 let mut next_cell = r_bound + 1;
-
-                if can_place && cur_group + 1 < groups.len() && groups[cur_group + 1].1 == cur_color
-                {
-                    // Same-color groups must be separated by a white cell.
-                    place_white = true;
-                    can_place = Self::can_place_color(cells, 0, next_cell, next_cell);
-                    next_cell += 1;
+                if gap > 0 {
+                    if !Self::can_place_color(cells, 0, next_cell, next_cell + gap - 1) {
+                        continue;
+                    }
+                    next_cell += gap;
                 }
-
-                if can_place && self.can_fill(groups, cells, cur_group + 1, next_cell) {
+                if self.can_fill(groups, cells, cur_group + 1, next_cell) {
                     answer = true;
-                    self.set_place_color(cur_color, l_bound, r_bound);
-                    if place_white {
-                        self.set_place_color(0, r_bound + 1, r_bound + 1);
+                    self.set_place_color(group.color, l_bound, r_bound);
+                    if gap > 0 {
+                        self.set_place_color(0, r_bound + 1, r_bound + gap);
                     }
                 }
             }
         }
 
-        self.calc_fill[cur_group][cur_cell] = answer;
-        self.cache[cur_group][cur_cell] = self.cache_cnt;
+        self.calc_fill[slot] = answer;
+        self.cache[slot] = self.cache_cnt;
         answer
     }
 }
 
+/// Lines longer than this are not cross-checked.
+#[cfg(test)]
+pub(crate) const ORACLE_MAX_LEN: usize = 20;
+/// Partial arrangements the oracle tries before giving up on a line.
+#[cfg(test)]
+const ORACLE_MAX_STEPS: usize = 50_000;
+
+/// Solve a line the slow way, as a check on the DP: try every arrangement of
+/// the groups at least `rules` gaps apart, keep those that fit `cells` and
+/// whose runs read back as exactly `groups`, and union them into `out`.
+///
+/// It shares no code with the DP. Under the standard rules it knows nothing
+/// about separators: two same-color groups that touch simply read back as
+/// one run. Other rules are enforced as gaps while arranging, and where they
+/// let same-color groups touch there is no read-back. Returns `None` for
+/// lines longer than `ORACLE_MAX_LEN` or with too many arrangements to try,
+/// otherwise whether any filling fits.
+#[cfg(test)]
+pub(crate) fn brute_force_line(
+    groups: &[LineGroup],
+    cells: &[u64],
+    rules: &LineRules,
+    out: &mut [u64; ORACLE_MAX_LEN],
+) -> Option<bool> {
+    if cells.len() > ORACLE_MAX_LEN {
+        return None;
+    }
+    let mut oracle = Oracle {
+        groups,
+        cells,
+        rules,
+        line: [0; ORACLE_MAX_LEN],
+        steps_left: ORACLE_MAX_STEPS,
+        union: [0; ORACLE_MAX_LEN],
+        found: false,
+    };
+    oracle.arrange(0, 0)?;
+    *out = oracle.union;
+    Some(oracle.found)
+}
+
+#[cfg(test)]
+struct Oracle<'a> {
+    groups: &'a [LineGroup],
+    cells: &'a [u64],
+    rules: &'a LineRules,
+    /// Color of each cell in the arrangement being built.
+    line: [usize; ORACLE_MAX_LEN],
+    steps_left: usize,
+    union: [u64; ORACLE_MAX_LEN],
+    found: bool,
+}
+
+#[cfg(test)]
+impl Oracle<'_> {
+    fn fits(&self, cell: usize, color: usize) -> bool {
+        1u64.checked_shl(color as u32)
+            .is_some_and(|bit| self.cells[cell] & bit != 0)
+    }
+
+    /// Place groups `g..` somewhere in `from..`; `None` once out of steps.
+    fn arrange(&mut self, g: usize, from: usize) -> Option<()> {
+        self.steps_left = self.steps_left.checked_sub(1)?;
+        let len = self.cells.len();
+        let Some(group) = self.groups.get(g) else {
+            if (from..len).all(|cell| self.fits(cell, 0)) {
+                self.line[from..len].fill(0);
+                self.record();
+            }
+            return Some(());
+        };
+        // Beyond the standard rules, leave the required gap after the
+        // previous group; the standard one is checked on read-back instead.
+        let gap = match g.checked_sub(1) {
+            Some(prev) if *self.rules != LineRules::STANDARD => {
+                self.rules.gap(self.groups[prev].color, group.color)
+            }
+            _ => 0,
+        };
+        for start in from..len {
+            if start < from + gap {
+                if !self.fits(start, 0) {
+                    break;
+                }
+                self.line[start] = 0;
+                continue;
+            }
+            for run in 1..=group.max.min(len - start) {
+                if !self.fits(start + run - 1, group.color) {
+                    break;
+                }
+                self.line[start + run - 1] = group.color;
+                if run >= group.min {
+                    self.arrange(g + 1, start + run)?;
+                }
+            }
+            if !self.fits(start, 0) {
+                break;
+            }
+            self.line[start] = 0;
+        }
+        Some(())
+    }
+
+    /// Keep the current arrangement if its runs are exactly the clue, or
+    /// always where groups of one color may touch and so merge into one run.
+    fn record(&mut self) {
+        let line = &self.line[..self.cells.len()];
+        let merges = self.rules.same_color_gap == 0 || self.rules.touching != 0;
+        let mut runs = line
+            .chunk_by(|a, b| a == b)
+            .filter(|run| run[0] != 0)
+            .map(|run| (run.len(), run[0]));
+        let matches = self.groups.iter().all(|group| {
+            runs.next().is_some_and(|(len, color)| {
+                color == group.color && (group.min..=group.max).contains(&len)
+            })
+        }) && runs.next().is_none();
+        if merges || matches {
+            self.found = true;
+            for (cell, &color) in line.iter().enumerate() {
+                self.union[cell] |= 1 << color;
+            }
+        }
+    }
+}
+
+/// Number of valid fillings of a line, broken down per cell and color.
+///
+/// Counts are `f64` because they grow combinatorially with line length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillingCounts {
+    /// Fillings of the whole line.
+    pub total: f64,
+    /// `counts[cell][color]`: fillings that give `cell` that color.
+    pub counts: Vec<Vec<f64>>,
+}
+
+/// Count the valid fillings of a line given the current cell masks.
+///
+/// Uses the standard separation rule, [`LineRules::STANDARD`].
+/// Returns `None` if no valid filling exists.
+pub fn filling_counts(groups: &[LineGroup], cells: &[u64]) -> Option<FillingCounts> {
+    let len = cells.len();
+    let group_count = groups.len();
+    let color_count = cells
+        .iter()
+        .fold(0u64, |acc, mask| acc | mask)
+        .checked_ilog2()
+        .map_or(1, |bit| bit as usize + 1);
+    let allows = |color: usize, from: usize, to: usize| allows(cells, color, from, to);
+    let placements = |g: usize, cell: usize| placements(groups, cells, g, cell);
+
+    // suffix[g][c]: fillings of cells[c..] using groups[g..].
+    let mut suffix = vec![vec![0.0f64; len + 1]; group_count + 1];
+    suffix[group_count][len] = 1.0;
+    for cell in (0..len).rev() {
+        for g in (0..=group_count).rev() {
+            let mut ways = 0.0;
+            if allows(0, cell, cell + 1) {
+                ways += suffix[g][cell + 1];
+            }
+            if g < group_count {
+                for (_, next) in placements(g, cell) {
+                    ways += suffix[g + 1][next];
+                }
+            }
+            suffix[g][cell] = ways;
+        }
+    }
+    let total = suffix[0][0];
+    if total == 0.0 {
+        return None;
+    }
+
+    // prefix[g][c]: ways to reach cell `c` with groups[..g] already placed.
+    let mut prefix = vec![vec![0.0f64; len + 1]; group_count + 1];
+    prefix[0][0] = 1.0;
+    let mut counts = vec![vec![0.0f64; color_count]; len];
+    for cell in 0..len {
+        for g in 0..=group_count {
+            let reach = prefix[g][cell];
+            if reach == 0.0 {
+                continue;
+            }
+            if allows(0, cell, cell + 1) {
+                prefix[g][cell + 1] += reach;
+                counts[cell][0] += reach * suffix[g][cell + 1];
+            }
+            if g < group_count {
+                for (group_len, next) in placements(g, cell) {
+                    prefix[g + 1][next] += reach;
+                    let ways = reach * suffix[g + 1][next];
+                    for count in &mut counts[cell..cell + group_len] {
+                        count[groups[g].color] += ways;
+                    }
+                    if next > cell + group_len {
+                        counts[cell + group_len][0] += ways;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(FillingCounts { total, counts })
+}
+
+/// Whether every cell in `cells[from..to]` may take `color`.
+fn allows(cells: &[u64], color: usize, from: usize, to: usize) -> bool {
+    to <= cells.len()
+        && OneLineSolver::color_mask(color)
+            .is_some_and(|mask| cells[from..to].iter().all(|cell| (cell & mask) != 0))
+}
+
+/// Each way to place group `g` at `cell`, as `(length, next cell)`; the next
+/// cell is past a forced white separator, if any.
+fn placements<'a>(
+    groups: &'a [LineGroup],
+    cells: &'a [u64],
+    g: usize,
+    cell: usize,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let group = groups[g];
+    let longest = group.max.min(cells.len().saturating_sub(cell));
+    let separated = groups
+        .get(g + 1)
+        .is_some_and(|next| next.color == group.color);
+    (group.min.max(1)..=longest)
+        .take_while(move |&len| allows(cells, group.color, cell, cell + len))
+        .filter_map(move |len| {
+            let end = cell + len;
+            if separated {
+                allows(cells, 0, end, end + 1).then_some((len, end + 1))
+            } else {
+                Some((len, end))
+            }
+        })
+}
+
+/// Leftmost and rightmost start of each group over all valid fillings.
+///
+/// Returns `None` if no valid filling exists.
+pub fn group_start_bounds(groups: &[LineGroup], cells: &[u64]) -> Option<Vec<(usize, usize)>> {
+    let len = cells.len();
+    let group_count = groups.len();
+    let suffix = fitting_suffixes(groups, cells)?;
+
+    // Walk the reachable states forward; a start counts only if the rest still fits.
+    let mut reach = vec![vec![false; len + 1]; group_count + 1];
+    reach[0][0] = true;
+    let mut bounds: Vec<Option<(usize, usize)>> = vec![None; group_count];
+    for cell in 0..len {
+        for g in 0..=group_count {
+            if !reach[g][cell] {
+                continue;
+            }
+            if allows(cells, 0, cell, cell + 1) && suffix[g][cell + 1] {
+                reach[g][cell + 1] = true;
+            }
+            if g < group_count {
+                for (_, next) in placements(groups, cells, g, cell) {
+                    if suffix[g + 1][next] {
+                        reach[g + 1][next] = true;
+                        bounds[g] = Some(bounds[g].map_or((cell, cell), |(lo, _)| (lo, cell)));
+                    }
+                }
+            }
+        }
+    }
+    bounds.into_iter().collect()
+}
+
+/// The group that covers each cell in every valid filling, if one does;
+/// `None` marks cells that are white or owned by different groups in
+/// different fillings.
+///
+/// Returns `None` if no valid filling exists.
+pub fn group_owners(groups: &[LineGroup], cells: &[u64]) -> Option<Vec<Option<usize>>> {
+    let len = cells.len();
+    let group_count = groups.len();
+    let suffix = fitting_suffixes(groups, cells)?;
+
+    // What covers each cell in the fillings seen so far: a group, white
+    // (`Some(None)`), or several of them (`None` once `mixed`).
+    let mut seen: Vec<Option<Option<usize>>> = vec![None; len];
+    let mut mixed = vec![false; len];
+    let mut cover = |cell: usize, owner: Option<usize>| match seen[cell] {
+        None => seen[cell] = Some(owner),
+        Some(prev) if prev != owner => mixed[cell] = true,
+        Some(_) => {}
+    };
+    let mut reach = vec![vec![false; len + 1]; group_count + 1];
+    reach[0][0] = true;
+    for cell in 0..len {
+        for g in 0..=group_count {
+            if !reach[g][cell] {
+                continue;
+            }
+            if allows(cells, 0, cell, cell + 1) && suffix[g][cell + 1] {
+                reach[g][cell + 1] = true;
+                cover(cell, None);
+            }
+            if g < group_count {
+                for (group_len, next) in placements(groups, cells, g, cell) {
+                    if suffix[g + 1][next] {
+                        reach[g + 1][next] = true;
+                        for covered in cell..cell + group_len {
+                            cover(covered, Some(g));
+                        }
+                        if next > cell + group_len {
+                            cover(cell + group_len, None);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Some(
+        seen.into_iter()
+            .zip(mixed)
+            .map(|(owner, mixed)| owner.flatten().filter(|_| !mixed))
+            .collect(),
+    )
+}
+
+/// `suffix[g][c]`: `cells[c..]` can be filled with `groups[g..]`; `None` if
+/// the whole line cannot be filled.
+fn fitting_suffixes(groups: &[LineGroup], cells: &[u64]) -> Option<Vec<Vec<bool>>> {
+    let len = cells.len();
+    let group_count = groups.len();
+    let mut suffix = vec![vec![false; len + 1]; group_count + 1];
+    suffix[group_count][len] = true;
+    for cell in (0..len).rev() {
+        for g in (0..=group_count).rev() {
+            suffix[g][cell] = (allows(cells, 0, cell, cell + 1) && suffix[g][cell + 1])
+                || (g < group_count
+                    && placements(groups, cells, g, cell).any(|(_, next)| suffix[g + 1][next]));
+        }
+    }
+    suffix[0][0].then_some(suffix)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::OneLineSolver;
+    use super::{
+        LineGroup, LineRules, ORACLE_MAX_LEN, OneLineSolver, brute_force_line, filling_counts,
+        group_start_bounds,
+    };
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn exact(groups: &[(usize, usize)]) -> Vec<LineGroup> {
+        groups.iter().copied().map(LineGroup::from).collect()
+    }
 
     #[test]
     fn fills_when_group_is_forced() {
         let mut solver = OneLineSolver::new(2);
-        let groups = vec![(2, 1)];
+        let groups = exact(&[(2, 1)]);
         let mut cells = vec![(1u64 << 0) | (1u64 << 1); 2];
 
         assert!(solver.update_state(&groups, &mut cells));
@@ -174,10 +626,205 @@ mod tests {
     #[test]
     fn keeps_union_of_options() {
         let mut solver = OneLineSolver::new(3);
-        let groups = vec![(1, 1)];
+        let groups = exact(&[(1, 1)]);
         let mut cells = vec![(1u64 << 0) | (1u64 << 1); 3];
 
         assert!(solver.update_state(&groups, &mut cells));
         assert_eq!(cells, vec![(1u64 << 0) | (1u64 << 1); 3]);
     }
+
+    /// A case from `tests/fixtures/line_cases.txt`: groups, cells, and the
+    /// expected cells (`None` if infeasible).
+    type LineCase = (Vec<LineGroup>, Vec<u64>, Option<Vec<u64>>);
+
+    fn parse_cells(text: &str) -> Vec<u64> {
+        text.split_whitespace()
+            .map(|cell| {
+                cell.split('+')
+                    .map(|color| 1u64 << color.parse::<u32>().unwrap())
+                    .fold(0, |acc, bit| acc | bit)
+            })
+            .collect()
+    }
+
+    fn line_cases() -> Vec<(usize, LineCase)> {
+        include_str!("../tests/fixtures/line_cases.txt")
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(idx, line)| {
+                let (groups, rest) = line.split_once(';').expect("missing ';'");
+                let (cells, expected) = rest.split_once("->").expect("missing '->'");
+                let groups = match groups.trim() {
+                    "-" => Vec::new(),
+                    groups => groups
+                        .split_whitespace()
+                        .map(|group| {
+                            let (len, color) =
+                                group.split_once('x').expect("group is <len>x<color>");
+                            let (min, max) = match len.split_once('-') {
+                                _ if len == "?" => (1, usize::MAX),
+                                Some((min, max)) => (min.parse().unwrap(), max.parse().unwrap()),
+                                None => (len.parse().unwrap(), len.parse().unwrap()),
+                            };
+                            LineGroup {
+                                min,
+                                max,
+                                color: color.parse().unwrap(),
+                            }
+                        })
+                        .collect(),
+                };
+                let expected = match expected.trim() {
+                    "infeasible" => None,
+                    cells => Some(parse_cells(cells)),
+                };
+                (idx + 1, (groups, parse_cells(cells), expected))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_line_case_corpus() {
+        let cases = line_cases();
+        assert!(cases.len() >= 20);
+        let mut solver = OneLineSolver::new(1);
+        for (line, (groups, cells, expected)) in cases {
+            let mut solved = cells.clone();
+            let feasible = solver.update_state(&groups, &mut solved);
+            assert_eq!(
+                feasible.then_some(solved),
+                expected,
+                "line_cases.txt:{line}"
+            );
+
+            let counts = filling_counts(&groups, &cells);
+            assert_eq!(
+                counts.is_some(),
+                expected.is_some(),
+                "line_cases.txt:{line}"
+            );
+            if let (Some(counts), Some(expected)) = (counts, expected) {
+                let possible: Vec<u64> = counts
+                    .counts
+                    .iter()
+                    .map(|cell| {
+                        (0..cell.len())
+                            .filter(|&color| cell[color] > 0.0)
+                            .fold(0, |acc, color| acc | 1u64 << color)
+                    })
+                    .collect();
+                assert_eq!(possible, expected, "line_cases.txt:{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn line_rules_change_the_separation() {
+        const W: u64 = 0b001;
+        const A: u64 = 0b010;
+        const B: u64 = 0b100;
+        let solve = |rules: LineRules, groups: &[(usize, usize)], len: usize| {
+            let mut cells = vec![W | A | B; len];
+            OneLineSolver::new(len)
+                .with_rules(rules)
+                .update_state(&exact(groups), &mut cells)
+                .then_some(cells)
+        };
+        let wide = LineRules {
+            same_color_gap: 2,
+            ..LineRules::STANDARD
+        };
+        assert_eq!(
+            solve(wide, &[(2, 1), (2, 1)], 6),
+            Some(vec![A, A, W, W, A, A])
+        );
+        assert_eq!(solve(wide, &[(2, 1), (2, 1)], 5), None);
+        assert_eq!(
+            solve(LineRules::STANDARD, &[(2, 1), (2, 1)], 6),
+            Some(vec![A | W, A, A | W, A | W, A, A | W])
+        );
+
+        let apart = LineRules {
+            other_color_gap: 1,
+            ..LineRules::STANDARD
+        };
+        assert_eq!(solve(apart, &[(1, 1), (1, 2)], 3), Some(vec![A, W, B]));
+
+        let touching = LineRules {
+            touching: A,
+            ..LineRules::STANDARD
+        };
+        assert_eq!(solve(touching, &[(2, 1), (1, 1)], 3), Some(vec![A; 3]));
+        assert_eq!(solve(LineRules::STANDARD, &[(2, 1), (1, 1)], 3), None);
+        assert_eq!(touching.gap(2, 2), 1);
+    }
+
+    #[test]
+    fn oracle_agrees_with_line_case_corpus() {
+        for (line, (groups, cells, expected)) in line_cases() {
+            let mut union = [0; ORACLE_MAX_LEN];
+            let feasible = brute_force_line(&groups, &cells, &LineRules::STANDARD, &mut union)
+                .unwrap_or_else(|| panic!("line_cases.txt:{line} too big for the oracle"));
+            assert_eq!(
+                feasible.then(|| union[..cells.len()].to_vec()),
+                expected,
+                "line_cases.txt:{line}"
+            );
+        }
+        let mut union = [0; ORACLE_MAX_LEN];
+        assert_eq!(
+            brute_force_line(
+                &[],
+                &[1; ORACLE_MAX_LEN + 1],
+                &LineRules::STANDARD,
+                &mut union
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn bounds_group_starts() {
+        let any = (1u64 << 0) | (1u64 << 1) | (1u64 << 2);
+        let groups = exact(&[(2, 1), (1, 1), (1, 2)]);
+        assert_eq!(
+            group_start_bounds(&groups, &[any; 7]),
+            Some(vec![(0, 2), (3, 5), (4, 6)])
+        );
+        let mut cells = [any; 7];
+        cells[1] = 1;
+        assert_eq!(
+            group_start_bounds(&groups, &cells),
+            Some(vec![(2, 2), (5, 5), (6, 6)])
+        );
+        assert_eq!(
+            group_start_bounds(&groups, &[any; 5]),
+            Some(vec![(0, 0), (3, 3), (4, 4)])
+        );
+        assert_eq!(group_start_bounds(&groups, &[any; 4]), None);
+        assert_eq!(group_start_bounds(&[], &[any; 2]), Some(vec![]));
+    }
+
+    #[test]
+    fn counts_fillings_per_cell() {
+        let groups = exact(&[(1, 1)]);
+        let cells = vec![(1u64 << 0) | (1u64 << 1); 3];
+
+        let filling = filling_counts(&groups, &cells).expect("line has fillings");
+        assert_eq!(filling.total, 3.0);
+        assert_eq!(filling.counts, vec![vec![2.0, 1.0]; 3]);
+    }
+
+    #[test]
+    fn counts_respect_same_color_separator() {
+        let groups = exact(&[(1, 1), (1, 1)]);
+        let cells = vec![(1u64 << 0) | (1u64 << 1); 4];
+
+        let filling = filling_counts(&groups, &cells).expect("line has fillings");
+        assert_eq!(filling.total, 3.0);
+        assert_eq!(filling.counts[0], vec![1.0, 2.0]);
+        assert_eq!(filling.counts[1], vec![2.0, 1.0]);
+        assert!(filling_counts(&groups, &cells[..2]).is_none());
+    }
 }
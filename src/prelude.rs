@@ -0,0 +1,41 @@
+//! The crate's public items in one place, for
+//! `use nonogram_solver::prelude::*;`.
+
+pub use crate::booklet::{BookletPuzzle, render_booklet};
+pub use crate::clue_text::{ClueTextError, parse_clue_text};
+pub use crate::color::{
+    Color, ColorError, JUST_NOTICEABLE_DELTA_E, NearDuplicate, display_palette, near_duplicates,
+    repaint,
+};
+pub use crate::dedupe::{CanonicalForm, duplicate_groups};
+pub use crate::difficulty::{FeatureVector, difficulty_features, features_csv, features_jsonl};
+pub use crate::grid_codec::{
+    DecodeError, apply_delta, decode_grid, decode_steps, encode_delta, encode_grid, encode_steps,
+};
+pub use crate::nonogram_solver::{
+    CellChange, ChangeKind, ClueStatus, Conflict, Contradiction, Grid, GridDiff, GuessSuggestion,
+    LineEffort, LineRules, Placements, SolveError, SolveLimit, SolvePreferences, SolveStats,
+    SolveSteps, SolveStrategy, SolvedPuzzle, SolverOptions, best_guess, clue_status, forced_cells,
+    group_ownership, line_extremes, mask_to_color_index, solve_line, solve_puzzle,
+    solve_puzzle_steps, solve_puzzle_steps_from, solve_puzzle_steps_proved,
+    solve_puzzle_steps_traced, solve_puzzle_steps_watched, solve_puzzle_steps_with,
+};
+pub use crate::proof::{Proof, ProofError, ProofRule, ProofStep, verify_proof};
+pub use crate::puzzle_builder::{PuzzleDataBuilder, ValidationError};
+pub use crate::puzzle_crawler::{
+    CrawlError, DiagnosticBundle, FetchedPuzzle, Group, GroupLen, PuzzleData, PuzzleKind,
+    PuzzleMeta, PuzzleRef, PuzzleSource, parse_puzzle, parse_puzzle_meta, puzzle_url,
+};
+#[cfg(feature = "fetch")]
+pub use crate::puzzle_crawler::{HtmlFetcher, fetch_puzzle, fetch_puzzle_with_meta};
+pub use crate::puzzle_stats::{PuzzleStats, puzzle_stats};
+pub use crate::repair::{CellRepair, puzzle_from_picture, suggest_repairs};
+pub use crate::report::render_report;
+pub use crate::silhouette::{SilhouetteError, silhouette, silhouette_of_grid};
+pub use crate::social_card::{CARD_HEIGHT, CARD_WIDTH, render_card};
+#[cfg(feature = "stream")]
+pub use crate::solve_stream::{StepEvent, solve_stream};
+pub use crate::step_filter::{Coalesce, Dedupe, Sample, StepFilter, StepPipeline};
+pub use crate::symmetry::{Symmetry, detect_symmetry};
+pub use crate::thumbnail::{Thumbnail, render_thumbnail};
+pub use crate::transform::{CropError, GridTransform, Region};
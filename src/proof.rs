@@ -0,0 +1,340 @@
+//! Proof logs of a solve, and a checker that replays them.
+//!
+//! [`solve_puzzle_steps_proved`] records every narrowing the solver makes
+//! together with the rule behind it. [`verify_proof`] replays the log on a
+//! single grid and re-derives each removed color with the filling-count DP,
+//! which shares no code with the propagating line solver, so a bug in one is
+//! caught by the other.
+//!
+//! Proved solves use line logic alone and never guess, so a proof has no
+//! branches: every step follows from the clues and the steps before it.
+//!
+//! [`solve_puzzle_steps_proved`]: crate::nonogram_solver::solve_puzzle_steps_proved
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::nonogram_solver::{Grid, line_groups};
+use crate::one_line_solver::filling_counts;
+use crate::puzzle_crawler::{Group, PuzzleData};
+use crate::symmetry::detect_symmetry;
+
+/// Why the cells of a [`ProofStep`] narrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofRule {
+    /// No filling of this row's clue gives a cell any of its removed colors.
+    Row(usize),
+    /// No filling of this column's clue gives a cell any of its removed colors.
+    Column(usize),
+    /// The clues are symmetric and `(row, col)` is the cell's mirror image,
+    /// which no longer allows the removed colors.
+    Mirror { row: usize, col: usize },
+}
+
+/// Cells narrowed together by one rule, as `(row, col, mask after)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub rule: ProofRule,
+    pub cells: Vec<(usize, usize, u64)>,
+}
+
+/// Every narrowing of a solve, in the order the solver made them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    pub steps: Vec<ProofStep>,
+}
+
+impl Proof {
+    /// Record the cells of `line` that changed from `before` to `after`.
+    pub(crate) fn record_line(&mut self, rule: ProofRule, before: &[u64], after: &[u64]) {
+        let cells: Vec<_> = before
+            .iter()
+            .zip(after)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(idx, (_, &after))| match rule {
+                ProofRule::Row(row) => (row, idx, after),
+                ProofRule::Column(col) => (idx, col, after),
+                ProofRule::Mirror { .. } => unreachable!("mirror steps are single cells"),
+            })
+            .collect();
+        if !cells.is_empty() {
+            self.steps.push(ProofStep { rule, cells });
+        }
+    }
+
+    /// Record that `(row, col)` narrowed to `after` to match its mirror `from`.
+    pub(crate) fn record_mirror(
+        &mut self,
+        (row, col): (usize, usize),
+        from: (usize, usize),
+        after: u64,
+    ) {
+        self.steps.push(ProofStep {
+            rule: ProofRule::Mirror {
+                row: from.0,
+                col: from.1,
+            },
+            cells: vec![(row, col, after)],
+        });
+    }
+}
+
+/// The first step of a proof that does not check out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    /// The rule names a line or cell outside the grid.
+    OutOfBounds { step: usize },
+    /// The cell is not on the rule's line, or not a mirror image of the
+    /// rule's cell under the clues' symmetry.
+    NotCovered { step: usize, row: usize, col: usize },
+    /// The line has no filling at all given the steps so far.
+    NoFilling { step: usize },
+    /// The rule allows `color` in this cell, yet the step removes it.
+    Unjustified {
+        step: usize,
+        row: usize,
+        col: usize,
+        color: usize,
+    },
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { step } => write!(f, "step {step} points outside the grid"),
+            Self::NotCovered { step, row, col } => write!(
+                f,
+                "step {step} changes cell ({row}, {col}), which its rule says nothing about"
+            ),
+            Self::NoFilling { step } => {
+                write!(f, "step {step} uses a line that has no filling left")
+            }
+            Self::Unjustified {
+                step,
+                row,
+                col,
+                color,
+            } => write!(
+                f,
+                "step {step} removes color {color} from cell ({row}, {col}) without cause"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ProofError {}
+
+/// Replay `proof` against the clues of `data`, starting from a grid where
+/// every color is possible, and return the grid it proves.
+///
+/// Each step may only remove colors its rule rules out given the grid after
+/// the steps before it. Compare the result with the solver's last step to
+/// check that the proof covers the whole solve.
+pub fn verify_proof(data: &PuzzleData, proof: &Proof) -> Result<Grid, ProofError> {
+    let rows = data.row_groups.len();
+    let cols = data.col_groups.len();
+    let full_mask = u64::MAX >> (64 - data.color_panel.len().clamp(1, 64));
+    let mut grid = vec![vec![full_mask; cols]; rows];
+    let symmetry = detect_symmetry(data);
+
+    for (step, ProofStep { rule, cells }) in proof.steps.iter().enumerate() {
+        if cells
+            .iter()
+            .any(|&(row, col, _)| row >= rows || col >= cols)
+        {
+            return Err(ProofError::OutOfBounds { step });
+        }
+        let allowed: Vec<u64> = match *rule {
+            ProofRule::Row(row) => {
+                let groups = data
+                    .row_groups
+                    .get(row)
+                    .ok_or(ProofError::OutOfBounds { step })?;
+                line_support(step, groups, &grid[row], cells, |r, col| {
+                    (r == row).then_some(col)
+                })?
+            }
+            ProofRule::Column(col) => {
+                let groups = data
+                    .col_groups
+                    .get(col)
+                    .ok_or(ProofError::OutOfBounds { step })?;
+                let line: Vec<u64> = grid.iter().map(|line| line[col]).collect();
+                line_support(step, groups, &line, cells, |row, c| {
+                    (c == col).then_some(row)
+                })?
+            }
+            ProofRule::Mirror {
+                row: from_row,
+                col: from_col,
+            } => {
+                if from_row >= rows || from_col >= cols {
+                    return Err(ProofError::OutOfBounds { step });
+                }
+                let mut allowed = Vec::with_capacity(cells.len());
+                for &(row, col, _) in cells {
+                    if !symmetry
+                        .images(row, col, rows, cols)
+                        .any(|image| image == (from_row, from_col))
+                    {
+                        return Err(ProofError::NotCovered { step, row, col });
+                    }
+                    allowed.push(grid[from_row][from_col]);
+                }
+                allowed
+            }
+        };
+
+        for (&(row, col, after), allowed) in cells.iter().zip(allowed) {
+            let unjustified = grid[row][col] & !after & allowed;
+            if unjustified != 0 {
+                return Err(ProofError::Unjustified {
+                    step,
+                    row,
+                    col,
+                    color: unjustified.trailing_zeros() as usize,
+                });
+            }
+        }
+        for &(row, col, after) in cells {
+            grid[row][col] &= after;
+        }
+    }
+    Ok(grid)
+}
+
+/// The colors each of `cells` may take in some filling of `line`;
+/// `position` gives a cell's index in the line, or `None` if it is off it.
+fn line_support(
+    step: usize,
+    groups: &[Group],
+    line: &[u64],
+    cells: &[(usize, usize, u64)],
+    position: impl Fn(usize, usize) -> Option<usize>,
+) -> Result<Vec<u64>, ProofError> {
+    let filling =
+        filling_counts(&line_groups(groups), line).ok_or(ProofError::NoFilling { step })?;
+    cells
+        .iter()
+        .map(|&(row, col, _)| {
+            let idx = position(row, col).ok_or(ProofError::NotCovered { step, row, col })?;
+            Ok(support(&filling.counts[idx]))
+        })
+        .collect()
+}
+
+/// Mask of the colors with at least one filling.
+fn support(counts: &[f64]) -> u64 {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0.0)
+        .fold(0, |mask, (color, _)| mask | 1 << color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::{
+        LineRules, SolveError, SolveStrategy, SolverOptions, solve_puzzle_steps_proved,
+    };
+
+    fn plus() -> PuzzleData {
+        PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .row([(3, 1)])
+            .row([(1, 1)])
+            .col([(1, 1)])
+            .col([(3, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn replays_solver_proofs_and_rejects_tampering() {
+        let data = plus();
+        for use_symmetry in [false, true] {
+            let options = SolverOptions {
+                use_symmetry,
+                ..SolverOptions::default()
+            };
+            let (steps, proof) = solve_puzzle_steps_proved(data.clone(), &options).unwrap();
+            assert_eq!(
+                verify_proof(&data, &proof).as_ref(),
+                Ok(steps.steps.last().unwrap())
+            );
+        }
+
+        let spaced = SolverOptions {
+            line_rules: LineRules {
+                same_color_gap: 2,
+                ..LineRules::STANDARD
+            },
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_proved(data.clone(), &spaced),
+            Err(SolveError::NotProvable)
+        ));
+        // The proof would be about the merged clues, not `data`'s.
+        let two_blacks = PuzzleData::builder()
+            .palette(["#ffffff", "#000000", "#111111"])
+            .row([(1, 2)])
+            .col([(1, 2)])
+            .build()
+            .unwrap();
+        let merged = SolverOptions {
+            recolor: vec![(2, 1)],
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_proved(two_blacks, &merged),
+            Err(SolveError::NotProvable)
+        ));
+        let full = SolverOptions {
+            strategy: SolveStrategy::Full,
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            solve_puzzle_steps_proved(data.clone(), &full),
+            Err(SolveError::NotProvable)
+        ));
+
+        // Row 0 alone allows black in its middle cell, as the picture has it.
+        let forged = Proof {
+            steps: vec![ProofStep {
+                rule: ProofRule::Row(0),
+                cells: vec![(0, 1, 0b01)],
+            }],
+        };
+        assert_eq!(
+            verify_proof(&data, &forged),
+            Err(ProofError::Unjustified {
+                step: 0,
+                row: 0,
+                col: 1,
+                color: 1
+            })
+        );
+        let off_line = Proof {
+            steps: vec![ProofStep {
+                rule: ProofRule::Column(2),
+                cells: vec![(1, 1, 0b10)],
+            }],
+        };
+        assert_eq!(
+            verify_proof(&data, &off_line),
+            Err(ProofError::NotCovered {
+                step: 0,
+                row: 1,
+                col: 1
+            })
+        );
+    }
+}
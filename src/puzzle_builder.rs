@@ -0,0 +1,480 @@
+//! Build `PuzzleData` by hand with the invariants the solver relies on checked.
+//!
+//! ```
+//! use nonogram_solver::prelude::*;
+//!
+//! // A 2x2 puzzle: a black diagonal.
+//! let data = PuzzleData::builder()
+//!     .palette(["#ffffff", "#000000"])
+//!     .row([(1, 1)])
+//!     .row([(1, 1)])
+//!     .col([(1, 1)])
+//!     .col([(1, 1)])
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(data.row_groups.len(), 2);
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::color::{Color, ColorError};
+use crate::one_line_solver::LineRules;
+use crate::puzzle_crawler::{Group, GroupLen, PuzzleData};
+
+/// A row or column, by zero-based index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line {
+    Row(usize),
+    Col(usize),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Row(idx) => write!(f, "row {}", idx + 1),
+            Self::Col(idx) => write!(f, "column {}", idx + 1),
+        }
+    }
+}
+
+/// Why a hand-built puzzle was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The palette needs at least the background color.
+    EmptyPalette,
+    /// A palette entry is not a `#rgb` / `#rrggbb` color.
+    BadColor { color_id: usize, error: ColorError },
+    /// No rows or no columns were added.
+    EmptyGrid,
+    /// A group uses white (0) or a color id past the end of the palette.
+    UnknownColor { line: Line, color_id: usize },
+    /// A group has length zero (or a range that starts at zero).
+    EmptyGroup { line: Line },
+    /// A group's length range ends before it starts.
+    InvertedRange { line: Line },
+    /// The groups, with their required gaps, do not fit the line.
+    LineTooShort {
+        line: Line,
+        needed: usize,
+        available: usize,
+    },
+    /// Rows and columns disagree on how many cells a color covers.
+    ColorTotalsDiffer {
+        color_id: usize,
+        rows: usize,
+        cols: usize,
+    },
+    /// A color merge starts from white or names a color past the palette.
+    BadMerge { from: usize, to: usize },
+    /// A merge gives two neighbouring groups of different colors the same
+    /// color, and the clue cannot say whether they touch.
+    AmbiguousMerge { line: Line, color_id: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyPalette => write!(f, "the palette is empty"),
+            Self::BadColor { color_id, error } => {
+                write!(f, "palette color {color_id} is invalid: {error}")
+            }
+            Self::EmptyGrid => write!(f, "the puzzle needs at least one row and one column"),
+            Self::UnknownColor { line, color_id } => {
+                write!(f, "{line} uses unknown color {color_id}")
+            }
+            Self::EmptyGroup { line } => write!(f, "{line} has a zero-length group"),
+            Self::InvertedRange { line } => {
+                write!(f, "{line} has a group whose length range is reversed")
+            }
+            Self::LineTooShort {
+                line,
+                needed,
+                available,
+            } => write!(f, "{line} needs {needed} cells but has {available}"),
+            Self::ColorTotalsDiffer {
+                color_id,
+                rows,
+                cols,
+            } => {
+                let (more, fewer, by) = if rows > cols {
+                    (rows, cols, "rows")
+                } else {
+                    (cols, rows, "columns")
+                };
+                let cells = if *rows == 1 { "cell" } else { "cells" };
+                write!(
+                    f,
+                    "color {color_id} covers {rows} {cells} by rows but {cols} by columns \
+                     ({} extra by {by})",
+                    more - fewer
+                )
+            }
+            Self::BadMerge { from, to } => {
+                write!(f, "cannot merge color {from} into color {to}")
+            }
+            Self::AmbiguousMerge { line, color_id } => write!(
+                f,
+                "{line} would have neighbouring groups of color {color_id} that may or may not touch"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+impl From<(usize, usize)> for Group {
+    /// `(len, color_id)`.
+    fn from((len, color_id): (usize, usize)) -> Self {
+        Self {
+            len: GroupLen::Exact(len),
+            color_id,
+        }
+    }
+}
+
+impl From<(GroupLen, usize)> for Group {
+    /// `(len, color_id)`, for blotted or bounded clues.
+    fn from((len, color_id): (GroupLen, usize)) -> Self {
+        Self { len, color_id }
+    }
+}
+
+/// Collects a palette and clues, then validates them in `build`.
+#[derive(Debug, Clone, Default)]
+pub struct PuzzleDataBuilder {
+    color_panel: Vec<String>,
+    row_groups: Vec<Vec<Group>>,
+    col_groups: Vec<Vec<Group>>,
+}
+
+impl PuzzleData {
+    pub fn builder() -> PuzzleDataBuilder {
+        PuzzleDataBuilder::default()
+    }
+}
+
+impl PuzzleDataBuilder {
+    /// Set the palette; entry 0 is the background (white). `build` checks
+    /// each entry and normalizes it to lowercase `#rrggbb`.
+    pub fn palette<I, S>(mut self, colors: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.color_panel = colors.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Append the next row's clue, top to bottom.
+    pub fn row<I, G>(mut self, groups: I) -> Self
+    where
+        I: IntoIterator<Item = G>,
+        G: Into<Group>,
+    {
+        self.row_groups
+            .push(groups.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Append the next column's clue, left to right.
+    pub fn col<I, G>(mut self, groups: I) -> Self
+    where
+        I: IntoIterator<Item = G>,
+        G: Into<Group>,
+    {
+        self.col_groups
+            .push(groups.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn build(mut self) -> Result<PuzzleData, ValidationError> {
+        if self.color_panel.is_empty() {
+            return Err(ValidationError::EmptyPalette);
+        }
+        for (color_id, entry) in self.color_panel.iter_mut().enumerate() {
+            let color: Color = entry
+                .parse()
+                .map_err(|error| ValidationError::BadColor { color_id, error })?;
+            *entry = color.to_string();
+        }
+        let rows = self.row_groups.len();
+        let cols = self.col_groups.len();
+        if rows == 0 || cols == 0 {
+            return Err(ValidationError::EmptyGrid);
+        }
+
+        let color_count = self.color_panel.len();
+        for (line, groups, available) in lines(&self.row_groups, &self.col_groups) {
+            for group in groups {
+                if group.color_id == 0 || group.color_id >= color_count {
+                    return Err(ValidationError::UnknownColor {
+                        line,
+                        color_id: group.color_id,
+                    });
+                }
+                if group.len.min() == 0 {
+                    return Err(ValidationError::EmptyGroup { line });
+                }
+                if group.len.max().is_some_and(|max| max < group.len.min()) {
+                    return Err(ValidationError::InvertedRange { line });
+                }
+            }
+            check_fits(line, groups, available, &LineRules::STANDARD)?;
+        }
+
+        let data = PuzzleData {
+            color_panel: self.color_panel,
+            row_groups: self.row_groups,
+            col_groups: self.col_groups,
+        };
+        check_color_totals(&data)?;
+        Ok(data)
+    }
+}
+
+/// Every color must cover as many cells by rows as by columns; the first
+/// color that does not is reported with both totals.
+///
+/// With blotted or bounded clues the totals are ranges, and only ranges that
+/// cannot overlap are rejected; the error then reports the two nearest ends.
+/// Group color ids past the palette are ignored here.
+pub fn check_color_totals(data: &PuzzleData) -> Result<(), ValidationError> {
+    let color_count = data.color_panel.len();
+    let row_totals = color_totals(&data.row_groups, color_count);
+    let col_totals = color_totals(&data.col_groups, color_count);
+    for (color_id, (&rows, &cols)) in row_totals.iter().zip(&col_totals).enumerate() {
+        let (rows, cols) = if rows.max.is_some_and(|max| max < cols.min) {
+            (rows.max.unwrap_or(rows.min), cols.min)
+        } else if cols.max.is_some_and(|max| max < rows.min) {
+            (rows.min, cols.max.unwrap_or(cols.min))
+        } else {
+            continue;
+        };
+        return Err(ValidationError::ColorTotalsDiffer {
+            color_id,
+            rows,
+            cols,
+        });
+    }
+    Ok(())
+}
+
+/// Every line's groups, with the gaps `rules` puts between neighbours, must
+/// fit its length; the first that do not are reported with what they need.
+///
+/// Crawled puzzles skip the builder, so the solver runs this before any
+/// propagation: a corrupt clue fails at once with the line to blame.
+pub fn check_line_lengths(data: &PuzzleData, rules: &LineRules) -> Result<(), ValidationError> {
+    for (line, groups, available) in lines(&data.row_groups, &data.col_groups) {
+        check_fits(line, groups, available, rules)?;
+    }
+    Ok(())
+}
+
+/// Each row and column with its groups and length.
+fn lines<'a>(
+    rows: &'a [Vec<Group>],
+    cols: &'a [Vec<Group>],
+) -> impl Iterator<Item = (Line, &'a [Group], usize)> {
+    let row_lines = rows
+        .iter()
+        .enumerate()
+        .map(move |(idx, groups)| (Line::Row(idx), groups.as_slice(), cols.len()));
+    let col_lines = cols
+        .iter()
+        .enumerate()
+        .map(move |(idx, groups)| (Line::Col(idx), groups.as_slice(), rows.len()));
+    row_lines.chain(col_lines)
+}
+
+fn check_fits(
+    line: Line,
+    groups: &[Group],
+    available: usize,
+    rules: &LineRules,
+) -> Result<(), ValidationError> {
+    let needed = min_line_len(groups, rules);
+    if needed > available {
+        return Err(ValidationError::LineTooShort {
+            line,
+            needed,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// Shortest line that fits `groups`, with the gaps `rules` asks for.
+fn min_line_len(groups: &[Group], rules: &LineRules) -> usize {
+    let gaps: usize = groups
+        .windows(2)
+        .map(|pair| rules.gap(pair[0].color_id, pair[1].color_id))
+        .sum();
+    groups.iter().map(|group| group.len.min()).sum::<usize>() + gaps
+}
+
+/// Cells a color covers over a set of lines; `max` is `None` if unbounded.
+#[derive(Debug, Clone, Copy)]
+struct Total {
+    min: usize,
+    max: Option<usize>,
+}
+
+fn color_totals(lines: &[Vec<Group>], color_count: usize) -> Vec<Total> {
+    let mut totals = vec![
+        Total {
+            min: 0,
+            max: Some(0)
+        };
+        color_count
+    ];
+    for group in lines.iter().flatten() {
+        if let Some(total) = totals.get_mut(group.color_id) {
+            total.min = total.min.saturating_add(group.len.min());
+            total.max = total
+                .max
+                .zip(group.len.max())
+                .map(|(sum, max)| sum.saturating_add(max));
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_colors() -> PuzzleDataBuilder {
+        PuzzleData::builder().palette(["#ffffff", "#000000", "#ff0000"])
+    }
+
+    #[test]
+    fn builds_consistent_puzzle() {
+        let data = three_colors()
+            .row([(1, 1), (1, 2)])
+            .row([(1, 1)])
+            .col([(2, 1)])
+            .col([(1, 2)])
+            .build()
+            .unwrap();
+        assert_eq!(
+            data.col_groups[0],
+            vec![Group {
+                len: GroupLen::Exact(2),
+                color_id: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_clues() {
+        assert_eq!(
+            PuzzleData::builder().row([(1, 1)]).col([(1, 1)]).build(),
+            Err(ValidationError::EmptyPalette)
+        );
+        assert_eq!(
+            three_colors().row([(1, 3)]).col([(1, 3)]).build(),
+            Err(ValidationError::UnknownColor {
+                line: Line::Row(0),
+                color_id: 3
+            })
+        );
+        assert_eq!(
+            three_colors()
+                .row([(1, 1), (1, 1)])
+                .row([(0usize, 1usize); 0])
+                .col([(1, 1)])
+                .col([(1, 1)])
+                .build(),
+            Err(ValidationError::LineTooShort {
+                line: Line::Row(0),
+                needed: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            three_colors()
+                .row([(2, 1)])
+                .row([(1, 1)])
+                .col([(1, 1)])
+                .col([(1, 1)])
+                .build(),
+            Err(ValidationError::ColorTotalsDiffer {
+                color_id: 1,
+                rows: 3,
+                cols: 2
+            })
+        );
+    }
+
+    #[test]
+    fn checks_blotted_and_bounded_groups() {
+        let data = three_colors()
+            .row([(GroupLen::Unknown, 1)])
+            .row([(GroupLen::Range(1, 2), 1)])
+            .col([(2, 1)])
+            .col([(1, 1)])
+            .build();
+        assert!(data.is_ok());
+        assert_eq!(
+            three_colors()
+                .row([(GroupLen::Range(2, 1), 1)])
+                .col([(1, 1)])
+                .build(),
+            Err(ValidationError::InvertedRange { line: Line::Row(0) })
+        );
+        assert_eq!(
+            three_colors()
+                .row([(GroupLen::Range(1, 2), 1)])
+                .row([(1usize, 1usize); 0])
+                .col([(2, 1)])
+                .col([(2, 1)])
+                .build(),
+            Err(ValidationError::ColorTotalsDiffer {
+                color_id: 1,
+                rows: 2,
+                cols: 4
+            })
+        );
+    }
+
+    #[test]
+    fn normalizes_palette_entries() {
+        let data = PuzzleData::builder()
+            .palette(["#FFF", "#1A2b3C"])
+            .row([(1, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        assert_eq!(data.color_panel, ["#ffffff", "#1a2b3c"]);
+        assert_eq!(
+            PuzzleData::builder()
+                .palette(["#fff", "black"])
+                .row([(1, 1)])
+                .col([(1, 1)])
+                .build(),
+            Err(ValidationError::BadColor {
+                color_id: 1,
+                error: ColorError::MissingHash
+            })
+        );
+    }
+
+    #[test]
+    fn color_totals_report_names_the_difference() {
+        let err = three_colors()
+            .row([(1, 2)])
+            .row([(0usize, 0usize); 0])
+            .col([(1, 2)])
+            .col([(1, 2)])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "color 2 covers 1 cell by rows but 2 by columns (1 extra by columns)"
+        );
+    }
+}
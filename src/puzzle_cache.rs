@@ -0,0 +1,158 @@
+//! Where fetched puzzle pages are kept between requests.
+//!
+//! Pages are cached as raw HTML rather than parsed puzzles, so a parser fix
+//! applies to everything already cached. Pick a backend per deployment: a
+//! long-running server keeps a [`MemoryCache`], one that restarts often or
+//! runs several instances shares a [`FileCache`] directory.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::puzzle_crawler::PuzzleRef;
+
+/// Storage for fetched puzzle pages.
+///
+/// Backends swallow their own I/O errors: a cache that cannot be read is
+/// just a miss.
+pub trait PuzzleCacheBackend: Send + Sync {
+    fn get(&self, puzzle: &PuzzleRef) -> Option<String>;
+    fn put(&self, puzzle: &PuzzleRef, html: &str);
+}
+
+/// Keeps the `capacity` most recently used pages in memory.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    /// Page and the tick it was last used at.
+    pages: HashMap<PuzzleRef, (String, u64)>,
+    tick: u64,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Lru::default()),
+        }
+    }
+}
+
+impl PuzzleCacheBackend for MemoryCache {
+    fn get(&self, puzzle: &PuzzleRef) -> Option<String> {
+        let mut lru = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        lru.tick += 1;
+        let tick = lru.tick;
+        let (html, used) = lru.pages.get_mut(puzzle)?;
+        *used = tick;
+        Some(html.clone())
+    }
+
+    fn put(&self, puzzle: &PuzzleRef, html: &str) {
+        let mut lru = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.pages.insert(puzzle.clone(), (html.to_string(), tick));
+        if lru.pages.len() > self.capacity {
+            // Linear scan: caches are small and misses cost a network fetch anyway.
+            let oldest = lru
+                .pages
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                lru.pages.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// One `<source>-<kind>-<id>.html` file per page in `dir`, never evicted.
+///
+/// Only numeric ids are cached, so an id can never name a path outside `dir`.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Use `dir`, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, puzzle: &PuzzleRef) -> Option<PathBuf> {
+        let id = &puzzle.id;
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let source = puzzle.source.slug();
+        let kind = puzzle.kind.param();
+        Some(self.dir.join(format!("{source}-{kind}-{id}.html")))
+    }
+}
+
+impl PuzzleCacheBackend for FileCache {
+    fn get(&self, puzzle: &PuzzleRef) -> Option<String> {
+        std::fs::read_to_string(self.path(puzzle)?).ok()
+    }
+
+    fn put(&self, puzzle: &PuzzleRef, html: &str) {
+        let Some(path) = self.path(puzzle) else {
+            return;
+        };
+        // Write then rename, so a concurrent reader never sees half a page.
+        let partial = path.with_extension("html.part");
+        let written =
+            std::fs::write(&partial, html).and_then(|()| std::fs::rename(&partial, &path));
+        if let Err(err) = written {
+            tracing::warn!(path = %path.display(), %err, "could not cache page");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_crawler::PuzzleKind;
+
+    fn color(id: &str) -> PuzzleRef {
+        PuzzleRef::nonograms_org(PuzzleKind::Color, id)
+    }
+
+    fn bw(id: &str) -> PuzzleRef {
+        PuzzleRef::nonograms_org(PuzzleKind::BlackWhite, id)
+    }
+
+    #[test]
+    fn memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.put(&color("1"), "one");
+        cache.put(&color("2"), "two");
+        assert_eq!(cache.get(&color("1")).as_deref(), Some("one"));
+        cache.put(&color("3"), "three");
+        assert_eq!(cache.get(&color("2")), None);
+        assert_eq!(cache.get(&color("1")).as_deref(), Some("one"));
+        assert_eq!(cache.get(&bw("3")), None);
+    }
+
+    #[test]
+    fn file_cache_round_trips_numeric_ids_only() {
+        let dir = std::env::temp_dir().join(format!("nonogram-cache-{}", std::process::id()));
+        let cache = FileCache::new(&dir).unwrap();
+        cache.put(&bw("1822"), "<html>bw</html>");
+        assert_eq!(cache.get(&bw("1822")).as_deref(), Some("<html>bw</html>"));
+        assert_eq!(cache.get(&color("1822")), None);
+        cache.put(&color("../escape"), "nope");
+        assert_eq!(cache.get(&color("../escape")), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,23 +2,155 @@
 //!
 //! This only handles color puzzles (not black-white) and keeps everything in memory.
 
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
 use serde::{Deserialize, Serialize};
 
+use crate::color::{Color, JUST_NOTICEABLE_DELTA_E, near_duplicates};
+
 const COLOR_URL: &str = "https://www.nonograms.org/nonograms2/i/";
 const BW_URL: &str = "https://www.nonograms.org/nonograms/i/";
+/// Largest row or column count accepted from a page; the site stays well below it.
+const MAX_GRID_SIDE: usize = 500;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PuzzleKind {
     Color,
     BlackWhite,
 }
 
+impl PuzzleKind {
+    /// The kind as it appears in API paths: `color` or `bw`.
+    pub fn param(self) -> &'static str {
+        match self {
+            Self::Color => "color",
+            Self::BlackWhite => "bw",
+        }
+    }
+}
+
+/// The site a puzzle id belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PuzzleSource {
+    #[default]
+    NonogramsOrg,
+}
+
+impl PuzzleSource {
+    /// Short lowercase name, safe in file names and URL paths.
+    pub fn slug(self) -> &'static str {
+        match self {
+            Self::NonogramsOrg => "nonograms-org",
+        }
+    }
+}
+
+/// A puzzle's identity. Ids are only unique within a source and kind, so
+/// anything keyed by puzzle (caches, in-flight fetches) keys by this.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PuzzleRef {
+    pub source: PuzzleSource,
+    pub kind: PuzzleKind,
+    pub id: String,
+}
+
+impl PuzzleRef {
+    /// A puzzle on nonograms.org.
+    pub fn nonograms_org(kind: PuzzleKind, id: impl Into<String>) -> Self {
+        Self {
+            source: PuzzleSource::NonogramsOrg,
+            kind,
+            id: id.into(),
+        }
+    }
+
+    /// The puzzle's page on its source site.
+    pub fn url(&self) -> String {
+        match self.source {
+            PuzzleSource::NonogramsOrg => puzzle_url(self.kind, &self.id),
+        }
+    }
+
+    /// The puzzle `delta` ids away in the same source and kind; `None` if
+    /// the id is not a number or the result would be below 1.
+    pub fn offset(&self, delta: i64) -> Option<Self> {
+        let id = self.id.parse::<u64>().ok()?.checked_add_signed(delta)?;
+        (id >= 1).then(|| Self {
+            id: id.to_string(),
+            ..self.clone()
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Group {
-    pub len: usize,
+    pub len: GroupLen,
     pub color_id: usize,
 }
 
+/// How many cells a group covers. nonograms.org clues are always exact;
+/// other puzzle variants blot some lengths out (`?`) or only bound them.
+///
+/// Serialized untagged: a number, `null` for unknown, or `[min, max]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GroupLen {
+    Exact(usize),
+    /// Any length, at least one cell.
+    Unknown,
+    /// Between the two lengths, inclusive.
+    Range(usize, usize),
+}
+
+impl GroupLen {
+    /// Shortest length the group may have.
+    pub fn min(self) -> usize {
+        match self {
+            Self::Exact(len) | Self::Range(len, _) => len,
+            Self::Unknown => 1,
+        }
+    }
+
+    /// Longest length the group may have; `None` if unbounded.
+    pub fn max(self) -> Option<usize> {
+        match self {
+            Self::Exact(len) | Self::Range(_, len) => Some(len),
+            Self::Unknown => None,
+        }
+    }
+
+    /// The length, if it is known exactly.
+    pub fn exact(self) -> Option<usize> {
+        match self {
+            Self::Exact(len) => Some(len),
+            Self::Range(min, max) if min == max => Some(min),
+            _ => None,
+        }
+    }
+}
+
+impl From<usize> for GroupLen {
+    fn from(len: usize) -> Self {
+        Self::Exact(len)
+    }
+}
+
+impl core::fmt::Display for GroupLen {
+    /// `3`, `?` or `2-4`, as printed in a clue.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Exact(len) => write!(f, "{len}"),
+            Self::Unknown => write!(f, "?"),
+            Self::Range(min, max) => write!(f, "{min}-{max}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PuzzleData {
     pub color_panel: Vec<String>,
@@ -26,25 +158,51 @@ pub struct PuzzleData {
     pub col_groups: Vec<Vec<Group>>,
 }
 
-#[derive(Debug)]
+/// Descriptive information scraped from a puzzle page alongside the clue data.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleMeta {
+    pub id: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// The page's rating as the site words it, e.g. `4.6 (23 votes)`.
+    #[serde(default)]
+    pub rating: Option<String>,
+    pub source_url: String,
+}
+
+/// A parsed puzzle together with the metadata of the page it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedPuzzle {
+    pub data: PuzzleData,
+    pub meta: PuzzleMeta,
+}
+
+#[derive(Debug, Clone)]
 pub enum CrawlError {
     Network(String),
+    NotFound,
     MissingData(&'static str),
     InvalidData(&'static str),
+    /// The site's robots.txt asks crawlers like this one to stay away, or
+    /// could not be read while politeness is strict.
+    Disallowed,
 }
 
-impl std::fmt::Display for CrawlError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for CrawlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Network(msg) => write!(f, "network error: {msg}"),
+            Self::NotFound => write!(f, "puzzle not found"),
             Self::MissingData(label) => write!(f, "missing data: {label}"),
             Self::InvalidData(label) => write!(f, "invalid data: {label}"),
+            Self::Disallowed => write!(f, "disallowed by robots.txt"),
         }
     }
 }
 
-impl std::error::Error for CrawlError {}
+impl core::error::Error for CrawlError {}
 
+#[cfg(feature = "fetch")]
 /// Fetch and parse a color puzzle into structured data.
 ///
 /// ```no_run
@@ -59,37 +217,382 @@ pub async fn fetch_color_puzzle(puzzle_id: &str) -> Result<PuzzleData, CrawlErro
     fetch_puzzle(PuzzleKind::Color, puzzle_id).await
 }
 
+#[cfg(feature = "fetch")]
 pub async fn fetch_puzzle(kind: PuzzleKind, puzzle_id: &str) -> Result<PuzzleData, CrawlError> {
-    let html = fetch_html(kind, puzzle_id).await?;
+    let html = HtmlFetcher::direct()
+        .fetch_puzzle_page(&PuzzleRef::nonograms_org(kind, puzzle_id))
+        .await?;
     parse_puzzle(kind, &html)
 }
 
+#[cfg(feature = "fetch")]
+/// Fetch a puzzle and the page metadata (title, author, source link) in one request.
+pub async fn fetch_puzzle_with_meta(
+    kind: PuzzleKind,
+    puzzle_id: &str,
+) -> Result<FetchedPuzzle, CrawlError> {
+    let html = HtmlFetcher::direct()
+        .fetch_puzzle_page(&PuzzleRef::nonograms_org(kind, puzzle_id))
+        .await?;
+    let data = parse_puzzle(kind, &html)?;
+    let meta = parse_puzzle_meta(kind, puzzle_id, &html);
+    Ok(FetchedPuzzle { data, meta })
+}
+
 /// Parse a puzzle from a page's HTML.
+#[tracing::instrument(skip(html), fields(html_len = html.len()))]
 pub fn parse_puzzle(kind: PuzzleKind, html: &str) -> Result<PuzzleData, CrawlError> {
     let data = extract_d_array(html)?;
-    decode_puzzle_data(kind, &data)
+    let puzzle = decode_puzzle_data(kind, &data)?;
+    tracing::debug!(
+        rows = puzzle.row_groups.len(),
+        cols = puzzle.col_groups.len(),
+        colors = puzzle.color_panel.len(),
+        "decoded puzzle"
+    );
+    let palette: Vec<Color> = puzzle
+        .color_panel
+        .iter()
+        .filter_map(|color| color.parse().ok())
+        .collect();
+    // Still separate clue colors, but a viewer will draw them alike.
+    for pair in near_duplicates(&palette, JUST_NOTICEABLE_DELTA_E) {
+        tracing::warn!(
+            first = pair.first,
+            second = pair.second,
+            delta_e = pair.delta_e,
+            "near-duplicate palette colors"
+        );
+    }
+    Ok(puzzle)
+}
+
+/// What the parser saw when a page failed to decode, for bug reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticBundle {
+    /// A hash of the page, so the same broken page always gets the same id
+    /// and repeated failures land in one bundle.
+    pub id: String,
+    pub puzzle: PuzzleRef,
+    pub error: String,
+    /// The `var d` entries as read, or `None` if the array itself is missing.
+    pub numbers: Option<Vec<[i64; 4]>>,
+    pub html: String,
 }
 
-async fn fetch_html(kind: PuzzleKind, puzzle_id: &str) -> Result<String, CrawlError> {
+impl DiagnosticBundle {
+    /// Capture `html`, which `parse_puzzle` rejected with `error`.
+    pub fn capture(puzzle: &PuzzleRef, html: &str, error: &CrawlError) -> Self {
+        // FNV-1a; stable across builds, unlike the std hasher.
+        let hash = html.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self {
+            id: format!("{:012x}", hash >> 16),
+            puzzle: puzzle.clone(),
+            error: error.to_string(),
+            numbers: extract_d_array(html).ok(),
+            html: html.to_string(),
+        }
+    }
+}
+
+/// The canonical nonograms.org page for a puzzle.
+pub fn puzzle_url(kind: PuzzleKind, puzzle_id: &str) -> String {
     let base = match kind {
         PuzzleKind::Color => COLOR_URL,
         PuzzleKind::BlackWhite => BW_URL,
     };
-    let url = format!("{base}{puzzle_id}");
-    let response = reqwest::get(url)
-        .await
-        .map_err(|e| CrawlError::Network(e.to_string()))?;
-    let status = response.status();
-    if !status.is_success() {
-        return Err(CrawlError::Network(format!("HTTP {status}")));
+    format!("{base}{puzzle_id}")
+}
+
+/// Extract the title and author from a puzzle page.
+///
+/// Missing fields are left as `None`; metadata never fails a crawl.
+pub fn parse_puzzle_meta(kind: PuzzleKind, puzzle_id: &str, html: &str) -> PuzzleMeta {
+    let title = tag_text(html, "h1")
+        .or_else(|| tag_text(html, "title"))
+        .map(|text| strip_title_decoration(&text))
+        .filter(|text| !text.is_empty());
+    let author = labelled_text(html, "Author:").filter(|text| !text.is_empty());
+    let rating = labelled_text(html, "Rating:").filter(|text| !text.is_empty());
+    PuzzleMeta {
+        id: puzzle_id.to_string(),
+        title,
+        author,
+        rating,
+        source_url: puzzle_url(kind, puzzle_id),
     }
-    response
-        .text()
+}
+
+#[cfg(feature = "fetch")]
+/// Download the raw HTML of a puzzle page.
+pub async fn fetch_html(kind: PuzzleKind, puzzle_id: &str) -> Result<String, CrawlError> {
+    HtmlFetcher::direct()
+        .fetch_html(&PuzzleRef::nonograms_org(kind, puzzle_id))
         .await
-        .map_err(|e| CrawlError::Network(e.to_string()))
 }
 
+#[cfg(feature = "fetch")]
+/// Downloads puzzle pages, optionally through a CORS proxy.
+///
+/// On `wasm32` reqwest uses the browser's fetch API, and nonograms.org sends
+/// no CORS headers, so a static deployment has to go through a proxy:
+///
+/// ```
+/// # use nonogram_solver::puzzle_crawler::{HtmlFetcher, PuzzleKind, PuzzleRef};
+/// let fetcher = HtmlFetcher::via_proxy("https://proxy.example/?url={url}");
+/// assert_eq!(
+///     fetcher.url(&PuzzleRef::nonograms_org(PuzzleKind::Color, "19048")),
+///     "https://proxy.example/?url=https%3A%2F%2Fwww.nonograms.org%2Fnonograms2%2Fi%2F19048"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HtmlFetcher {
+    proxy: Option<String>,
+    user_agent: Option<String>,
+}
+
+#[cfg(feature = "fetch")]
+impl HtmlFetcher {
+    /// Fetch pages from nonograms.org itself.
+    pub fn direct() -> Self {
+        Self::default()
+    }
+
+    /// Fetch pages through `proxy`. A `{url}` in it is replaced by the
+    /// percent-encoded page URL; otherwise the page URL is appended as is,
+    /// which is what cors-anywhere style proxies expect.
+    pub fn via_proxy(proxy: impl Into<String>) -> Self {
+        Self {
+            proxy: Some(proxy.into()),
+            user_agent: None,
+        }
+    }
+
+    /// Send `user_agent` as the `User-Agent` header. Browsers keep their
+    /// own, so this only takes effect off `wasm32`.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// The URL actually requested for a puzzle page.
+    pub fn url(&self, puzzle: &PuzzleRef) -> String {
+        self.proxied(&puzzle.url())
+    }
+
+    /// `page`, or the proxy URL that stands for it.
+    fn proxied(&self, page: &str) -> String {
+        match &self.proxy {
+            None => page.to_string(),
+            Some(proxy) if proxy.contains("{url}") => proxy.replace("{url}", &percent_encode(page)),
+            Some(proxy) => format!("{proxy}{page}"),
+        }
+    }
+
+    /// Download the raw HTML of a puzzle page.
+    #[tracing::instrument(skip(self))]
+    pub async fn fetch_html(&self, puzzle: &PuzzleRef) -> Result<String, CrawlError> {
+        self.fetch_text(&puzzle.url()).await
+    }
+
+    /// `fetch_html`, fetching once more if the page has no puzzle data, as
+    /// when an anti-bot interstitial was served in its place. The second try
+    /// waits [`RETRY_DELAY`] and goes out as [`browser_like`](Self::browser_like).
+    pub async fn fetch_puzzle_page(&self, puzzle: &PuzzleRef) -> Result<String, CrawlError> {
+        let html = self.fetch_html(puzzle).await?;
+        if has_puzzle_data(&html) {
+            return Ok(html);
+        }
+        tracing::info!(id = %puzzle.id, "page has no puzzle data, fetching it again");
+        pause(RETRY_DELAY).await;
+        self.browser_like().fetch_html(puzzle).await
+    }
+
+    /// This fetcher with its `User-Agent` in the `Mozilla/5.0 (compatible;
+    /// ...)` form well-known bots use, which anti-bot checks that only pass
+    /// browsers often accept. The crawler's own name stays in it.
+    pub fn browser_like(&self) -> Self {
+        let own = self
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+        self.clone()
+            .with_user_agent(format!("Mozilla/5.0 (compatible; {own})"))
+    }
+
+    /// Download any page, such as the site's robots.txt, the way puzzle
+    /// pages are downloaded. A 404 is [`CrawlError::NotFound`].
+    pub async fn fetch_text(&self, page: &str) -> Result<String, CrawlError> {
+        let mut request = reqwest::Client::new().get(self.proxied(page));
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header(reqwest::header::USER_AGENT, user_agent);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CrawlError::Network(e.to_string()))?;
+        let status = response.status();
+        tracing::debug!(%status, "received response");
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(CrawlError::NotFound);
+        }
+        if !status.is_success() {
+            return Err(CrawlError::Network(format!("HTTP {status}")));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| CrawlError::Network(e.to_string()))
+    }
+}
+
+#[cfg(feature = "fetch")]
+/// How long to wait before fetching a page again that came back without
+/// puzzle data; an interstitial that just fired usually fires again at once.
+pub const RETRY_DELAY: core::time::Duration = core::time::Duration::from_secs(1);
+
+#[cfg(feature = "fetch")]
+/// Wait `duration` without blocking the executor.
+async fn pause(duration: core::time::Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
+
+#[cfg(feature = "fetch")]
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Text content of the first `<tag ...>...</tag>` element, with inner markup removed.
+fn tag_text(html: &str, tag: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find(&format!("<{tag}"))?;
+    let content_start = open + lower[open..].find('>')? + 1;
+    let content_end = content_start + lower[content_start..].find(&format!("</{tag}>"))?;
+    Some(plain_text(&html[content_start..content_end]))
+}
+
+/// Text following a visible label such as `Author:`, up to the end of its element.
+fn labelled_text(html: &str, label: &str) -> Option<String> {
+    let start = html.find(label)? + label.len();
+    let rest = &html[start..];
+    // Read until the enclosing block ends; inline markup is dropped by `plain_text`.
+    let end = ["</td>", "</div>", "</p>", "<br", "\n"]
+        .iter()
+        .filter_map(|marker| rest.find(marker))
+        .min()
+        .unwrap_or(rest.len());
+    Some(plain_text(&rest[..end]))
+}
+
+/// Strip the site's "Nonogram «...»" wrapper and any trailing " - site" suffix.
+fn strip_title_decoration(title: &str) -> String {
+    if let (Some(open), Some(close)) = (title.find('«'), title.rfind('»'))
+        && open < close
+    {
+        return title[open + '«'.len_utf8()..close].trim().to_string();
+    }
+    title
+        .split(" - ")
+        .next()
+        .unwrap_or(title)
+        .trim()
+        .to_string()
+}
+
+/// Remove tags, decode common entities, and collapse whitespace.
+fn plain_text(fragment: &str) -> String {
+    let mut text = String::with_capacity(fragment.len());
+    let mut in_tag = false;
+    for ch in fragment.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    let decoded = decode_entities(&text);
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|&semi| semi <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "laquo" => Some('«'),
+            "raquo" => Some('»'),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether `html` has a puzzle's `d` array at all. Pages without one, such
+/// as an anti-bot interstitial served instead of the puzzle, may well parse
+/// when fetched again; pages with a malformed one will not.
+pub fn has_puzzle_data(html: &str) -> bool {
+    d_array_text(html).is_ok() || loose_d_array_text(html).is_some()
+}
+
+/// Read the `var d=[...];` array, falling back to [`loose_d_array_text`]
+/// when the page's markup is not quite the usual.
 fn extract_d_array(html: &str) -> Result<Vec<[i64; 4]>, CrawlError> {
+    let strict = d_array_text(html).and_then(read_d_numbers);
+    match (&strict, loose_d_array_text(html)) {
+        (Err(_), Some(loose)) => read_d_numbers(loose).or(strict),
+        _ => strict,
+    }
+}
+
+/// The text from the `[` after `var d=` to the first `];`.
+fn d_array_text(html: &str) -> Result<&str, CrawlError> {
     let marker = "var d=";
     let start = html
         .find(marker)
@@ -98,8 +601,39 @@ fn extract_d_array(html: &str) -> Result<Vec<[i64; 4]>, CrawlError> {
     let end = after
         .find("];")
         .ok_or(CrawlError::MissingData("d array end"))?;
-    let slice = &after[..end + 1];
+    Ok(&after[..end + 1])
+}
 
+/// The array assigned to `d` by `var`, `let` or `const`, with any spacing
+/// around `=`, up to its matching `]` even without a `;`.
+fn loose_d_array_text(html: &str) -> Option<&str> {
+    ["var", "let", "const"].into_iter().find_map(|keyword| {
+        html.match_indices(keyword).find_map(|(at, _)| {
+            let rest = html[at + keyword.len()..].strip_prefix(char::is_whitespace)?;
+            let rest = rest.trim_start().strip_prefix('d')?;
+            let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+            if !rest.starts_with('[') {
+                return None;
+            }
+            let mut depth = 0usize;
+            for (idx, ch) in rest.char_indices() {
+                match ch {
+                    '[' => depth += 1,
+                    ']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(&rest[..=idx]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            None
+        })
+    })
+}
+
+fn read_d_numbers(slice: &str) -> Result<Vec<[i64; 4]>, CrawlError> {
     let mut nums = Vec::new();
     let mut cur: Option<i64> = None;
     let mut sign: i64 = 1;
@@ -112,7 +646,12 @@ fn extract_d_array(html: &str) -> Result<Vec<[i64; 4]>, CrawlError> {
             cur = Some(0);
         } else if ch.is_ascii_digit() {
             let digit = (ch as i64) - ('0' as i64);
-            cur = Some(cur.unwrap_or(0) * 10 + digit);
+            let value = cur
+                .unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or(CrawlError::InvalidData("d array number"))?;
+            cur = Some(value);
         } else if let Some(value) = cur.take() {
             nums.push(sign * value);
             sign = 1;
@@ -138,27 +677,28 @@ fn decode_puzzle_data(kind: PuzzleKind, data: &[[i64; 4]]) -> Result<PuzzleData,
         return Err(CrawlError::InvalidData("d array too short"));
     }
 
-    let mod_js = |a: i64, b: i64| {
-        if b == 0 {
+    // Widened so hostile moduli cannot overflow the sums and products below.
+    let mod_js = |a: i64, b: i64| -> i128 {
+        if b <= 0 {
             0
         } else {
-            let r = a % b;
-            if r < 0 { r + b } else { r }
+            i128::from(a).rem_euclid(i128::from(b))
         }
     };
+    let decode_count = |entry: [i64; 4]| {
+        mod_js(entry[0], entry[3]) + mod_js(entry[1], entry[3]) - mod_js(entry[2], entry[3])
+    };
+    let dimension = |value: i128, limit: usize| {
+        usize::try_from(value)
+            .ok()
+            .filter(|&value| value > 0 && value <= limit)
+            .ok_or(CrawlError::InvalidData("decoded dimensions"))
+    };
 
     let d = data;
-    let cols = mod_js(d[1][0], d[1][3]) + mod_js(d[1][1], d[1][3]) - mod_js(d[1][2], d[1][3]);
-    let rows = mod_js(d[2][0], d[2][3]) + mod_js(d[2][1], d[2][3]) - mod_js(d[2][2], d[2][3]);
-    let colors = mod_js(d[3][0], d[3][3]) + mod_js(d[3][1], d[3][3]) - mod_js(d[3][2], d[3][3]);
-
-    if rows <= 0 || cols <= 0 || colors <= 0 {
-        return Err(CrawlError::InvalidData("decoded dimensions"));
-    }
-
-    let rows = rows as usize;
-    let cols = cols as usize;
-    let colors = colors as usize;
+    let cols = dimension(decode_count(d[1]), MAX_GRID_SIDE)?;
+    let rows = dimension(decode_count(d[2]), MAX_GRID_SIDE)?;
+    let colors = dimension(decode_count(d[3]), d.len())?;
 
     if d.len() < 5 + colors {
         return Err(CrawlError::InvalidData("color data truncated"));
@@ -175,10 +715,13 @@ fn decode_puzzle_data(kind: PuzzleKind, data: &[[i64; 4]]) -> Result<PuzzleData,
             color_panel.push("#ffffff".to_string());
             for i in 0..colors {
                 let entry = d[5 + i];
-                let r = ((entry[0] - base[1]) % 256 + 256) % 256;
-                let g = ((entry[1] - base[0]) % 256 + 256) % 256;
-                let b = ((entry[2] - base[3]) % 256 + 256) % 256;
-                color_panel.push(format!("#{:02x}{:02x}{:02x}", r, g, b));
+                let channel = |value: i64, key: i64| value.wrapping_sub(key).rem_euclid(256) as u8;
+                let color = Color::rgb(
+                    channel(entry[0], base[1]),
+                    channel(entry[1], base[0]),
+                    channel(entry[2], base[3]),
+                );
+                color_panel.push(color.to_string());
             }
         }
     }
@@ -192,73 +735,41 @@ fn decode_puzzle_data(kind: PuzzleKind, data: &[[i64; 4]]) -> Result<PuzzleData,
         + mod_js(d[v_idx][2], d[v_idx][3]);
 
     let ia = d[v_idx + 1];
+    let max_idx = usize::try_from(ha)
+        .ok()
+        .and_then(|ha| (v_idx + 1).checked_add(ha))
+        .filter(|&max_idx| max_idx < d.len())
+        .ok_or(CrawlError::InvalidData("grid data out of bounds"))?;
     let mut grid = vec![vec![0i64; cols]; rows];
-    let max_idx = v_idx + 1 + (ha as usize);
-    if d.len() <= max_idx {
-        return Err(CrawlError::InvalidData("grid data out of bounds"));
-    }
 
     for entry in &d[(v_idx + 2)..=max_idx] {
-        let row = entry[3] - ia[3] - 1;
-        let start = entry[0] - ia[0] - 1;
-        let len = entry[1] - ia[1];
-        let color = entry[2] - ia[2];
-        if row < 0 || start < 0 || len <= 0 {
+        // Offsets that overflow cannot describe a cell, so they are skipped like
+        // any other out-of-range run.
+        let offset = |idx: usize, extra: i64| {
+            entry[idx]
+                .checked_sub(ia[idx])
+                .and_then(|value| value.checked_sub(extra))
+                .and_then(|value| usize::try_from(value).ok())
+        };
+        let (Some(row), Some(start), Some(len)) = (offset(3, 1), offset(0, 1), offset(1, 0)) else {
             continue;
-        }
-        let row = row as usize;
-        let start = start as usize;
-        let len = len as usize;
-        if row >= rows || start >= cols {
+        };
+        let color = entry[2].wrapping_sub(ia[2]);
+        let known_color = usize::try_from(color).is_ok_and(|color| color < color_panel.len());
+        if len == 0 || row >= rows || start >= cols || !known_color {
             continue;
         }
-        let end = (start + len).min(cols);
-        for col in start..end {
-            grid[row][col] = color;
-        }
+        let end = start.saturating_add(len).min(cols);
+        grid[row][start..end].fill(color);
     }
 
-    let mut row_groups = Vec::with_capacity(rows);
-    for row in &grid {
-        let mut groups = Vec::new();
-        let mut col = 0;
-        while col < cols {
-            let z = row[col];
-            let start = col;
-            while col < cols && row[col] == z {
-                col += 1;
-            }
-            let len = col - start;
-            if z > 0 && len > 0 {
-                groups.push(Group {
-                    len,
-                    color_id: z as usize,
-                });
-            }
-        }
-        row_groups.push(groups);
-    }
-
-    let mut col_groups = Vec::with_capacity(cols);
-    for col in 0..cols {
-        let mut groups = Vec::new();
-        let mut row = 0;
-        while row < rows {
-            let z = grid[row][col];
-            let start = row;
-            while row < rows && grid[row][col] == z {
-                row += 1;
-            }
-            let len = row - start;
-            if z > 0 && len > 0 {
-                groups.push(Group {
-                    len,
-                    color_id: z as usize,
-                });
-            }
-        }
-        col_groups.push(groups);
-    }
+    let row_groups: Vec<Vec<Group>> = grid.iter().map(|row| line_groups(row)).collect();
+    let col_groups: Vec<Vec<Group>> = (0..cols)
+        .map(|col| {
+            let column: Vec<i64> = grid.iter().map(|row| row[col]).collect();
+            line_groups(&column)
+        })
+        .collect();
 
     Ok(PuzzleData {
         color_panel,
@@ -266,3 +777,160 @@ fn decode_puzzle_data(kind: PuzzleKind, data: &[[i64; 4]]) -> Result<PuzzleData,
         col_groups,
     })
 }
+
+/// Run-length encode one decoded line into its clue groups, skipping white runs.
+fn line_groups(line: &[i64]) -> Vec<Group> {
+    let mut groups = Vec::new();
+    let mut idx = 0;
+    while idx < line.len() {
+        let z = line[idx];
+        let start = idx;
+        while idx < line.len() && line[idx] == z {
+            idx += 1;
+        }
+        let len = idx - start;
+        if z > 0 && len > 0 {
+            groups.push(Group {
+                len: GroupLen::Exact(len),
+                color_id: z as usize,
+            });
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzle_refs_name_source_kind_and_id() {
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::BlackWhite, "1822");
+        assert_eq!(puzzle.url(), "https://www.nonograms.org/nonograms/i/1822");
+        assert_ne!(puzzle, PuzzleRef::nonograms_org(PuzzleKind::Color, "1822"));
+        assert_eq!(
+            serde_json::to_string(&puzzle).unwrap(),
+            r#"{"source":"nonograms_org","kind":"black_white","id":"1822"}"#
+        );
+    }
+
+    #[test]
+    fn diagnostics_keep_the_numbers_read() {
+        let puzzle = PuzzleRef::nonograms_org(PuzzleKind::Color, "5");
+        let html = "<script>var d=[[1,2,3,4],[5,6,7,8]];</script>";
+        let err = parse_puzzle(PuzzleKind::Color, html).unwrap_err();
+        let bundle = DiagnosticBundle::capture(&puzzle, html, &err);
+        assert_eq!(bundle.error, "invalid data: d array too short");
+        assert_eq!(bundle.numbers, Some(vec![[1, 2, 3, 4], [5, 6, 7, 8]]));
+        assert_eq!(bundle.id.len(), 12);
+        assert_eq!(bundle.id, DiagnosticBundle::capture(&puzzle, html, &err).id);
+
+        let missing = DiagnosticBundle::capture(&puzzle, "<p>", &err);
+        assert_eq!(missing.numbers, None);
+        assert_ne!(missing.id, bundle.id);
+    }
+
+    #[test]
+    fn reads_d_arrays_in_unusual_markup() {
+        let numbers = Ok(vec![[1, 2, 3, 4], [5, -6, 7, 8]]);
+        for html in [
+            "<script>var d=[[1,2,3,4],[5,-6,7,8]];</script>",
+            "<script>var d = [[1,2,3,4],\n[5,-6,7,8]]\n</script>",
+            "<script>let d=[[1,2,3,4],[5,-6,7,8]]</script>",
+            "<script>var data=[0];const  d =[[1,2,3,4],[5,-6,7,8]];</script>",
+        ] {
+            assert!(has_puzzle_data(html), "{html}");
+            assert_eq!(
+                extract_d_array(html).map_err(|err| err.to_string()),
+                numbers
+            );
+        }
+        let interstitial = "<html><body>Checking your browser...</body></html>";
+        assert!(!has_puzzle_data(interstitial));
+        assert!(matches!(
+            extract_d_array(interstitial),
+            Err(CrawlError::MissingData("var d array"))
+        ));
+        assert!(matches!(
+            extract_d_array("var d=[[1,2,3]];"),
+            Err(CrawlError::InvalidData("d array length"))
+        ));
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn fetcher_builds_proxy_urls() {
+        assert_eq!(
+            HtmlFetcher::direct().url(&PuzzleRef::nonograms_org(PuzzleKind::BlackWhite, "7")),
+            "https://www.nonograms.org/nonograms/i/7"
+        );
+        assert_eq!(
+            HtmlFetcher::via_proxy("https://cors.example/")
+                .url(&PuzzleRef::nonograms_org(PuzzleKind::Color, "7")),
+            "https://cors.example/https://www.nonograms.org/nonograms2/i/7"
+        );
+    }
+
+    #[test]
+    fn parses_title_and_author_from_page() {
+        let html = r#"<html><head><title>Nonograms &laquo;Cat&raquo; - nonograms.org</title></head>
+            <body><h1>Nonogram &laquo;Sleepy <b>Cat</b>&raquo;</h1>
+            <table><tr><td>Author: <a href="/u/1">Jane&nbsp;Doe</a></td></tr>
+            <tr><td>Rating: <b>4.6</b> (23 votes)</td></tr></table>
+            </body></html>"#;
+        let meta = parse_puzzle_meta(PuzzleKind::Color, "56215", html);
+        assert_eq!(meta.title.as_deref(), Some("Sleepy Cat"));
+        assert_eq!(meta.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(meta.rating.as_deref(), Some("4.6 (23 votes)"));
+        assert_eq!(
+            meta.source_url,
+            "https://www.nonograms.org/nonograms2/i/56215"
+        );
+    }
+
+    #[test]
+    fn missing_meta_fields_are_none() {
+        let meta = parse_puzzle_meta(PuzzleKind::BlackWhite, "1822", "<html></html>");
+        assert_eq!(meta.title, None);
+        assert_eq!(meta.author, None);
+        assert_eq!(meta.rating, None);
+        assert_eq!(
+            meta.source_url,
+            "https://www.nonograms.org/nonograms/i/1822"
+        );
+    }
+
+    #[test]
+    fn group_lengths_keep_their_json_shape() {
+        let groups: Vec<Group> = serde_json::from_str(
+            r#"[{"len":3,"color_id":1},{"len":null,"color_id":1},{"len":[2,4],"color_id":2}]"#,
+        )
+        .unwrap();
+        let lens: Vec<GroupLen> = groups.iter().map(|group| group.len).collect();
+        assert_eq!(
+            lens,
+            vec![GroupLen::Exact(3), GroupLen::Unknown, GroupLen::Range(2, 4)]
+        );
+        assert_eq!(
+            serde_json::to_string(&groups[0]).unwrap(),
+            r#"{"len":3,"color_id":1}"#
+        );
+        let labels: Vec<String> = lens.iter().map(ToString::to_string).collect();
+        assert_eq!(labels, ["3", "?", "2-4"]);
+    }
+
+    #[test]
+    fn skips_runs_with_unknown_colors() {
+        // A 1x2 black-and-white grid: one run of color 1, one claiming color 5.
+        let html = "var d=[[0,0,0,0],[2,0,0,9],[1,0,0,9],[1,0,0,9],[0,0,0,0],[0,0,0,0],\
+                    [0,1,0,9],[0,0,0,0],[1,1,1,1],[2,1,5,1]];";
+        let data = parse_puzzle(PuzzleKind::BlackWhite, html).unwrap();
+        assert_eq!(
+            data.row_groups,
+            vec![vec![Group {
+                len: GroupLen::Exact(1),
+                color_id: 1
+            }]]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Summary numbers for a puzzle's clues, cheap enough to compute for a
+//! whole list: for filtering puzzles and as inputs to a difficulty estimate.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::nonogram_solver::{expected_color_counts, line_groups};
+use crate::one_line_solver::filling_counts;
+use crate::puzzle_crawler::PuzzleData;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleStats {
+    pub rows: usize,
+    pub cols: usize,
+    /// Cells of each color in the solution, white first, from the row clues.
+    pub color_histogram: Vec<usize>,
+    /// `color_histogram` as a share of all cells.
+    pub density: Vec<f64>,
+    /// Mean group length over all row and column clues.
+    pub mean_group_len: f64,
+    /// `group_counts[n]`: rows and columns whose clue has `n` groups.
+    pub group_counts: Vec<usize>,
+    /// Shannon entropy of `density`, in bits per cell: 0 for a single
+    /// color, higher for evenly mixed palettes.
+    pub color_entropy: f64,
+    /// Bits the clues leave open, line by line: the sum over rows and
+    /// columns of log2 of the line's fillings on a blank grid. Crossings
+    /// are ignored, so this is an upper bound on what the solver has to
+    /// work out; 0 means every line is forced on its own.
+    pub line_entropy: f64,
+}
+
+/// Statistics for `data`; blotted and bounded groups count their shortest
+/// length. Lines whose clue cannot fit add nothing to `line_entropy`.
+pub fn puzzle_stats(data: &PuzzleData) -> PuzzleStats {
+    let rows = data.row_groups.len();
+    let cols = data.col_groups.len();
+    let cells = rows * cols;
+    let color_histogram = expected_color_counts(data);
+    let density: Vec<f64> = color_histogram
+        .iter()
+        .map(|&count| {
+            if cells == 0 {
+                0.0
+            } else {
+                count as f64 / cells as f64
+            }
+        })
+        .collect();
+    let color_entropy = density
+        .iter()
+        .filter(|&&share| share > 0.0)
+        .map(|&share| -share * log2(share))
+        .sum();
+
+    let lines = || data.row_groups.iter().chain(&data.col_groups);
+    let group_total = lines().map(Vec::len).sum::<usize>();
+    let len_total: usize = lines().flatten().map(|group| group.len.min()).sum();
+    let mean_group_len = if group_total == 0 {
+        0.0
+    } else {
+        len_total as f64 / group_total as f64
+    };
+    let mut group_counts = vec![0; lines().map(Vec::len).max().map_or(0, |most| most + 1)];
+    for clue in lines() {
+        group_counts[clue.len()] += 1;
+    }
+
+    let full_mask = 1u64
+        .checked_shl(data.color_panel.len() as u32)
+        .map_or(u64::MAX, |bit| bit - 1);
+    let line_entropy = data
+        .row_groups
+        .iter()
+        .map(|clue| (clue, cols))
+        .chain(data.col_groups.iter().map(|clue| (clue, rows)))
+        .filter_map(|(clue, len)| filling_counts(&line_groups(clue), &vec![full_mask; len]))
+        .map(|fillings| log2(fillings.total))
+        .sum();
+
+    PuzzleStats {
+        rows,
+        cols,
+        color_histogram,
+        density,
+        mean_group_len,
+        group_counts,
+        color_entropy,
+        line_entropy,
+    }
+}
+
+/// Base-2 logarithm of a positive `x`; `core` has no `log2`.
+///
+/// Splits off the binary exponent, then sums the atanh series for the
+/// mantissa in `[1, 2)`, which converges to full precision in a few terms.
+fn log2(x: f64) -> f64 {
+    if x <= 0.0 || !x.is_finite() {
+        return 0.0;
+    }
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if exponent == -1023 {
+        // Subnormal: scale into the normal range first.
+        let scaled = x * (1u64 << 54) as f64;
+        return log2(scaled) - 54.0;
+    }
+    if mantissa > core::f64::consts::SQRT_2 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+    // ln(m) = 2 * atanh(z), z = (m - 1) / (m + 1), |z| <= 0.172.
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut term = z;
+    let mut ln = 0.0;
+    for k in 0..20 {
+        ln += term / f64::from(2 * k + 1);
+        term *= z2;
+    }
+    exponent as f64 + 2.0 * ln / core::f64::consts::LN_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log2_matches_powers_and_halves() {
+        for (x, expected) in [
+            (1.0, 0.0),
+            (8.0, 3.0),
+            (0.25, -2.0),
+            (1e300, 996.578_428_466_208_7),
+        ] {
+            assert!((log2(x) - expected).abs() < 1e-9, "log2({x}) = {}", log2(x));
+        }
+        assert!((log2(3.0) - 1.584_962_500_721_156).abs() < 1e-12);
+    }
+
+    #[test]
+    fn summarizes_clues() {
+        // Picture:  r r .
+        //           . b r
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(2, 1)])
+            .row([(1, 2), (1, 1)])
+            .col([(1, 1)])
+            .col([(1, 1), (1, 2)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let stats = puzzle_stats(&data);
+        assert_eq!((stats.rows, stats.cols), (2, 3));
+        assert_eq!(stats.color_histogram, vec![2, 3, 1]);
+        assert!((stats.density[1] - 0.5).abs() < 1e-12);
+        assert!((stats.mean_group_len - 8.0 / 7.0).abs() < 1e-12);
+        assert_eq!(stats.group_counts, vec![0, 3, 2]);
+        // Shares 1/3, 1/2, 1/6.
+        assert!((stats.color_entropy - 1.459_147_917_027_245).abs() < 1e-9);
+        // Rows: 2 and 3 fillings. Columns: 2, 1 and 2.
+        assert!((stats.line_entropy - (3.0 + log2(3.0))).abs() < 1e-9);
+    }
+}
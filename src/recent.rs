@@ -0,0 +1,40 @@
+//! Recently loaded puzzles, most recent first, persisted in `localStorage`.
+
+use dioxus::prelude::*;
+
+use crate::favorites::Favorite;
+use crate::local_store;
+
+const STORAGE_KEY: &str = "nonogram-solver.recent";
+const MAX_RECENT: usize = 12;
+
+/// Context wrapper so this list does not collide with the favorites signal.
+#[derive(Debug, Clone, Copy)]
+pub struct Recent(pub Signal<Vec<Favorite>>);
+
+pub fn use_recent_provider() -> Signal<Vec<Favorite>> {
+    let Recent(mut recent) = use_context_provider(|| Recent(Signal::new(Vec::new())));
+    use_effect(move || {
+        spawn(async move {
+            if let Some(saved) = local_store::load::<Vec<Favorite>>(STORAGE_KEY).await {
+                recent.set(saved);
+            }
+        });
+    });
+    recent
+}
+
+pub fn use_recent() -> Signal<Vec<Favorite>> {
+    use_context::<Recent>().0
+}
+
+/// Move `entry` to the front, dropping the oldest entries past the limit.
+pub fn record(mut recent: Signal<Vec<Favorite>>, entry: Favorite) {
+    {
+        let mut list = recent.write();
+        list.retain(|existing| existing.url != entry.url);
+        list.insert(0, entry);
+        list.truncate(MAX_RECENT);
+    }
+    local_store::save(STORAGE_KEY, &*recent.read());
+}
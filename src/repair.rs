@@ -0,0 +1,170 @@
+//! Help for authors whose picture line logic cannot finish: derive the clues
+//! from the picture, find the cells the solver leaves open, and try
+//! recoloring each of them to see which single change makes the puzzle
+//! line-solvable again.
+//!
+//! Line logic stalling is a stronger condition than the picture being
+//! ambiguous: some stalled puzzles do have a unique solution that takes
+//! guessing to find. Either way it is what a casual solver runs into, so a
+//! repair that clears it is worth suggesting.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::nonogram_solver::{Grid, SolveError, mask_to_color_index, solve_puzzle_steps};
+use crate::puzzle_crawler::{Group, GroupLen, PuzzleData};
+
+/// Recolor one cell of the picture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRepair {
+    pub row: usize,
+    pub col: usize,
+    /// The cell's color in the picture now.
+    pub from: usize,
+    /// The color to paint it instead.
+    pub to: usize,
+    /// Cells line logic still leaves open after the change; 0 means the
+    /// repaired puzzle solves without guessing.
+    pub left_open: usize,
+}
+
+/// The clues of `picture`, a grid of palette indices with 0 as background.
+pub fn puzzle_from_picture(color_panel: Vec<String>, picture: &[Vec<usize>]) -> PuzzleData {
+    let cols = picture.first().map_or(0, Vec::len);
+    PuzzleData {
+        color_panel,
+        row_groups: picture
+            .iter()
+            .map(|line| line_clue(line.iter().copied()))
+            .collect(),
+        col_groups: (0..cols)
+            .map(|col| line_clue(picture.iter().map(|line| line[col])))
+            .collect(),
+    }
+}
+
+/// Single-cell repairs that leave fewer cells open than the picture does
+/// now, best first. Empty if line logic already solves the picture.
+///
+/// Only cells that line logic leaves open are tried, each with every other
+/// color, so this takes one full solve per open cell and color.
+pub fn suggest_repairs(
+    color_panel: &[String],
+    picture: &[Vec<usize>],
+) -> Result<Vec<CellRepair>, SolveError> {
+    let open = open_cells(color_panel, picture)?;
+    let mut repairs = Vec::new();
+    let mut edited: Vec<Vec<usize>> = picture.to_vec();
+    for &(row, col) in &open {
+        let from = picture[row][col];
+        for to in (0..color_panel.len()).filter(|&to| to != from) {
+            edited[row][col] = to;
+            if let Ok(still_open) = open_cells(color_panel, &edited)
+                && still_open.len() < open.len()
+            {
+                repairs.push(CellRepair {
+                    row,
+                    col,
+                    from,
+                    to,
+                    left_open: still_open.len(),
+                });
+            }
+        }
+        edited[row][col] = from;
+    }
+    repairs.sort_by_key(|repair| (repair.left_open, repair.row, repair.col, repair.to));
+    Ok(repairs)
+}
+
+/// Cells of `picture` that line logic cannot determine from its clues.
+fn open_cells(
+    color_panel: &[String],
+    picture: &[Vec<usize>],
+) -> Result<Vec<(usize, usize)>, SolveError> {
+    let data = puzzle_from_picture(color_panel.to_vec(), picture);
+    let steps = solve_puzzle_steps(data)?;
+    let grid: Grid = steps.steps.last().cloned().unwrap_or_default();
+    Ok(grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .filter(|&(_, &mask)| mask_to_color_index(mask).is_none())
+                .map(move |(col, _)| (row, col))
+        })
+        .collect())
+}
+
+/// Groups for the runs of non-background colors in one line.
+fn line_clue(line: impl IntoIterator<Item = usize>) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut prev = 0;
+    for color in line {
+        if color != 0 {
+            match groups.last_mut() {
+                Some(group) if prev == color => {
+                    group.len = GroupLen::Exact(group.len.min() + 1);
+                }
+                _ => groups.push(Group::from((GroupLen::Exact(1), color))),
+            }
+        }
+        prev = color;
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn black_white() -> Vec<String> {
+        vec!["#ffffff".to_string(), "#000000".to_string()]
+    }
+
+    #[test]
+    fn derives_clues_per_color_run() {
+        let palette = vec![
+            "#ffffff".to_string(),
+            "#ff0000".to_string(),
+            "#0000ff".to_string(),
+        ];
+        let data = puzzle_from_picture(palette, &[vec![1, 1, 2, 0, 2]]);
+        let row: Vec<(usize, usize)> = data.row_groups[0]
+            .iter()
+            .map(|group| (group.len.min(), group.color_id))
+            .collect();
+        assert_eq!(row, [(2, 1), (1, 2), (1, 2)]);
+        assert_eq!(data.col_groups.len(), 5);
+        assert!(data.col_groups[3].is_empty());
+    }
+
+    #[test]
+    fn one_cell_fixes_a_diagonal() {
+        // Both diagonals share these clues, so line logic cannot start.
+        let picture = [vec![1, 0], vec![0, 1]];
+        let repairs = suggest_repairs(&black_white(), &picture).unwrap();
+        assert_eq!(repairs.len(), 4);
+        assert!(repairs.iter().all(|repair| repair.left_open == 0));
+        assert_eq!(
+            repairs[0],
+            CellRepair {
+                row: 0,
+                col: 0,
+                from: 1,
+                to: 0,
+                left_open: 0
+            }
+        );
+
+        let solvable = [vec![1, 1], vec![0, 1]];
+        assert!(
+            suggest_repairs(&black_white(), &solvable)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
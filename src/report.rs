@@ -0,0 +1,229 @@
+//! A solve as one self-contained HTML page, for archiving or sharing
+//! outside the app.
+//!
+//! The page needs no network access: styles and the step scrubber are
+//! inline, and without JavaScript it still shows the clues, the stats and
+//! the final grid.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::color::Color;
+use crate::nonogram_solver::{Grid, SolveStats, mask_to_color_index};
+use crate::puzzle_crawler::{Group, PuzzleData};
+
+/// Cells that are still undetermined.
+const UNKNOWN: Color = Color::rgb(0xd0, 0xd0, 0xd0);
+/// One character per palette index in the embedded steps; `.` is undetermined.
+const DIGITS: &[u8; 64] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ+/";
+
+pub(crate) const STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse}\
+td{width:1.2em;height:1.2em;padding:0;text-align:center;font-size:.75em}\
+td.c{border:1px solid #888}\
+td.r{text-align:right;padding-right:.4em;white-space:nowrap}\
+td.t{vertical-align:bottom;line-height:1.3}\
+.g{display:inline-block;min-width:1.1em;margin:1px;border-radius:2px}\
+dl{display:grid;grid-template-columns:max-content auto;gap:.2em 1em}\
+dd{margin:0}";
+
+const SCRIPT: &str = "const cells=document.querySelectorAll('#grid td.c');\
+const range=document.getElementById('step');\
+const label=document.getElementById('label');\
+function show(n){const s=STEPS[n];cells.forEach((td,i)=>{const d=DIGITS.indexOf(s[i]);\
+td.style.background=d<0?UNKNOWN:PALETTE[d];});\
+label.textContent=n+' / '+(STEPS.length-1);}\
+range.addEventListener('input',()=>show(+range.value));";
+
+/// Render `puzzle` and its recorded `steps` as a standalone HTML document.
+///
+/// The grid shows the last step; a slider replays the others. Palette
+/// entries that do not parse draw gray.
+pub fn render_report(puzzle: &PuzzleData, steps: &[Grid], stats: &SolveStats) -> String {
+    let palette = palette_colors(puzzle);
+    let rows = puzzle.row_groups.len();
+    let cols = puzzle.col_groups.len();
+    let last = steps.len().saturating_sub(1);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>Nonogram solve report ({cols}x{rows})</title>");
+    let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(html, "<h1>Nonogram {cols}x{rows}</h1>");
+
+    html.push_str("<dl>\n");
+    let verdict = if stats.solved {
+        "by line logic"
+    } else {
+        "no, a guess is needed"
+    };
+    for (term, value) in [
+        ("Solved", String::from(verdict)),
+        ("Steps", format!("{}", steps.len())),
+        ("Passes", format!("{}", stats.passes)),
+        ("Line solves", format!("{}", stats.line_solves)),
+        ("Cells from rows", format!("{}", stats.cells_from_rows)),
+        ("Cells from columns", format!("{}", stats.cells_from_cols)),
+        ("Time", format!("{} ms", stats.elapsed.as_millis())),
+    ] {
+        let _ = writeln!(html, "<dt>{term}</dt><dd>{value}</dd>");
+    }
+    html.push_str("</dl>\n");
+
+    let final_grid = steps.get(last);
+    push_table(&mut html, "id=\"grid\"", puzzle, &palette, |row, col| {
+        let mask = final_grid
+            .and_then(|grid| grid.get(row))
+            .and_then(|line| line.get(col));
+        Some(cell_color(mask.copied(), &palette))
+    });
+
+    if steps.len() > 1 {
+        let _ = writeln!(
+            html,
+            "<p><input id=\"step\" type=\"range\" min=\"0\" max=\"{last}\" value=\"{last}\"> \
+             step <span id=\"label\">{last} / {last}</span></p>"
+        );
+        html.push_str("<script>\n");
+        let colors: Vec<String> = palette.iter().map(|c| format!("'{c}'")).collect();
+        let _ = writeln!(html, "const PALETTE=[{}];", colors.join(","));
+        let _ = writeln!(html, "const UNKNOWN='{UNKNOWN}';");
+        let _ = writeln!(
+            html,
+            "const DIGITS='{}';",
+            core::str::from_utf8(DIGITS).unwrap_or_default()
+        );
+        html.push_str("const STEPS=[\n");
+        for grid in steps {
+            let encoded: String = (0..rows)
+                .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                .map(|(row, col)| {
+                    grid.get(row)
+                        .and_then(|line| line.get(col))
+                        .and_then(|&mask| mask_to_color_index(mask))
+                        .map_or('.', |idx| char::from(DIGITS[idx]))
+                })
+                .collect();
+            let _ = writeln!(html, "'{encoded}',");
+        }
+        html.push_str("];\n");
+        html.push_str(SCRIPT);
+        html.push_str("\n</script>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The clues around a grid whose cells are filled by `fill`; `None` leaves a
+/// cell blank. `attrs` go on the `<table>` tag.
+pub(crate) fn push_table(
+    html: &mut String,
+    attrs: &str,
+    puzzle: &PuzzleData,
+    palette: &[Color],
+    fill: impl Fn(usize, usize) -> Option<Color>,
+) {
+    let _ = writeln!(html, "<table {attrs}>");
+    html.push_str("<tr><td></td>");
+    for clue in &puzzle.col_groups {
+        html.push_str("<td class=\"t\">");
+        for group in clue {
+            push_group(html, group, palette);
+            html.push_str("<br>");
+        }
+        html.push_str("</td>");
+    }
+    html.push_str("</tr>\n");
+    for (row, clue) in puzzle.row_groups.iter().enumerate() {
+        html.push_str("<tr><td class=\"r\">");
+        for group in clue {
+            push_group(html, group, palette);
+        }
+        html.push_str("</td>");
+        for col in 0..puzzle.col_groups.len() {
+            match fill(row, col) {
+                Some(color) => {
+                    let _ = write!(html, "<td class=\"c\" style=\"background:{color}\"></td>");
+                }
+                None => html.push_str("<td class=\"c\"></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+}
+
+/// `puzzle`'s palette, with entries that do not parse drawn gray.
+pub(crate) fn palette_colors(puzzle: &PuzzleData) -> Vec<Color> {
+    puzzle
+        .color_panel
+        .iter()
+        .map(|color| color.parse().unwrap_or(UNKNOWN))
+        .collect()
+}
+
+pub(crate) fn cell_color(mask: Option<u64>, palette: &[Color]) -> Color {
+    mask.and_then(mask_to_color_index)
+        .and_then(|idx| palette.get(idx).copied())
+        .unwrap_or(UNKNOWN)
+}
+
+fn push_group(html: &mut String, group: &Group, palette: &[Color]) {
+    let ink = palette.get(group.color_id).copied().unwrap_or(UNKNOWN);
+    let _ = write!(
+        html,
+        "<span class=\"g\" style=\"background:{ink};color:{}\">{}</span>",
+        ink.text_color(),
+        group.len
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle_steps;
+
+    #[test]
+    fn embeds_clues_steps_and_final_grid() {
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000"])
+            .row([(1, 1)])
+            .row([(2, 1)])
+            .col([(2, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let solved = solve_puzzle_steps(data.clone()).unwrap();
+        let html = render_report(&data, &solved.steps, &solved.stats);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<dt>Solved</dt><dd>by line logic</dd>"));
+        assert_eq!(html.matches("<td class=\"c\"").count(), 4);
+        // Final grid: one white cell, three red.
+        assert_eq!(
+            html.matches("style=\"background:#ff0000\"></td>").count(),
+            3
+        );
+        assert!(html.contains("'1011',"));
+        assert_eq!(
+            html.matches("',\n").count(),
+            solved.steps.len(),
+            "one encoded grid per step"
+        );
+    }
+
+    #[test]
+    fn single_step_has_no_scrubber() {
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let html = render_report(&data, &[], &SolveStats::default());
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("background:#d0d0d0"));
+    }
+}
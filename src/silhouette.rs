@@ -0,0 +1,137 @@
+//! Black-and-white projections of color puzzles, for previews and for
+//! comparing how hard the shape alone is against the full puzzle.
+//!
+//! Collapsing every ink into black merges groups of different colors that
+//! touch, and the clue does not say whether two neighbours touch: `1r 1b`
+//! fits both `rb` and `r.b`. So the projection is read off the picture
+//! instead. It only needs to know which cells are white, so a color puzzle
+//! that line logic leaves with "red or blue" cells still projects.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::nonogram_solver::{Grid, SolveError, solve_puzzle_steps};
+use crate::puzzle_crawler::{Group, GroupLen, PuzzleData};
+
+/// Why a puzzle has no silhouette.
+#[derive(Debug)]
+pub enum SilhouetteError {
+    /// Solving the color puzzle failed.
+    Solve(SolveError),
+    /// Line logic left this cell possibly white, possibly inked.
+    Undetermined { row: usize, col: usize },
+}
+
+impl fmt::Display for SilhouetteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Solve(err) => write!(f, "{err}"),
+            Self::Undetermined { row, col } => write!(
+                f,
+                "cell ({row}, {col}) may or may not be white without guessing"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SilhouetteError {}
+
+/// Solve `data` and return its black-and-white projection.
+pub fn silhouette(data: PuzzleData) -> Result<PuzzleData, SilhouetteError> {
+    let steps = solve_puzzle_steps(data).map_err(SilhouetteError::Solve)?;
+    let grid = steps
+        .steps
+        .last()
+        .ok_or(SilhouetteError::Solve(SolveError::Unsolvable))?;
+    silhouette_of_grid(grid)
+}
+
+/// The black-and-white puzzle whose solution is every non-white cell of
+/// `grid`.
+pub fn silhouette_of_grid(grid: &Grid) -> Result<PuzzleData, SilhouetteError> {
+    let mut inked: Vec<Vec<bool>> = Vec::with_capacity(grid.len());
+    for (row, line) in grid.iter().enumerate() {
+        let mut cells = Vec::with_capacity(line.len());
+        for (col, &mask) in line.iter().enumerate() {
+            let ink = match (mask & 1 != 0, mask & !1 != 0) {
+                (true, true) => return Err(SilhouetteError::Undetermined { row, col }),
+                (white, _) => !white,
+            };
+            cells.push(ink);
+        }
+        inked.push(cells);
+    }
+    let cols = inked.first().map_or(0, Vec::len);
+    Ok(PuzzleData {
+        color_panel: ["#ffffff", "#000000"].map(Into::into).into(),
+        row_groups: inked
+            .iter()
+            .map(|line| runs(line.iter().copied()))
+            .collect(),
+        col_groups: (0..cols)
+            .map(|col| runs(inked.iter().map(|line| line[col])))
+            .collect(),
+    })
+}
+
+/// Black groups for the inked runs of one line.
+fn runs(line: impl IntoIterator<Item = bool>) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut prev = false;
+    for ink in line {
+        if ink {
+            match groups.last_mut() {
+                Some(group) if prev => group.len = GroupLen::Exact(group.len.min() + 1),
+                _ => groups.push(Group::from((GroupLen::Exact(1), 1))),
+            }
+        }
+        prev = ink;
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn lens(groups: &[Vec<Group>]) -> Vec<Vec<usize>> {
+        groups
+            .iter()
+            .map(|clue| clue.iter().map(|g| g.len.min()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn touching_colors_merge_into_one_group() {
+        // Picture:  r b .
+        //           . b b
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(1, 1), (1, 2)])
+            .row([(2, 2)])
+            .col([(1, 1)])
+            .col([(2, 2)])
+            .col([(1, 2)])
+            .build()
+            .unwrap();
+        let bw = silhouette(data).unwrap();
+        assert_eq!(bw.color_panel, vec!["#ffffff", "#000000"]);
+        assert_eq!(lens(&bw.row_groups), vec![vec![2], vec![2]]);
+        assert_eq!(lens(&bw.col_groups), vec![vec![1], vec![2], vec![1]]);
+        assert!(bw.row_groups.iter().flatten().all(|g| g.color_id == 1));
+    }
+
+    #[test]
+    fn needs_to_know_which_cells_are_white() {
+        // Red or blue is fine; red or white is not.
+        let grid = vec![vec![0b110, 0b001], vec![0b011, 0b001]];
+        assert!(matches!(
+            silhouette_of_grid(&grid),
+            Err(SilhouetteError::Undetermined { row: 1, col: 0 })
+        ));
+        let bw = silhouette_of_grid(&vec![vec![0b110, 0b001]]).unwrap();
+        assert_eq!(lens(&bw.row_groups), vec![vec![1]]);
+        assert_eq!(lens(&bw.col_groups), vec![vec![1], vec![]]);
+    }
+}
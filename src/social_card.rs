@@ -0,0 +1,302 @@
+//! Link preview images: the 1200x630 PNG that chat apps and social sites
+//! show when a puzzle link is shared.
+//!
+//! The card carries the title and size in a built-in 5x7 pixel font, so it
+//! renders without font files, next to a coarse mosaic of the solution:
+//! enough to recognise the picture's colors and layout, not enough to copy.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::color::Color;
+use crate::nonogram_solver::{SolvedPuzzle, mask_to_color_index};
+use crate::thumbnail::encode_png;
+
+/// Card size recommended for `og:image`.
+pub const CARD_WIDTH: u32 = 1200;
+pub const CARD_HEIGHT: u32 = 630;
+
+const BACKGROUND: Color = Color::rgb(0xf7, 0xf5, 0xf0);
+const INK: Color = Color::rgb(0x22, 0x22, 0x22);
+const MUTED: Color = Color::rgb(0x88, 0x88, 0x88);
+/// Cells that are still undetermined.
+const UNKNOWN: Color = Color::rgb(0xd0, 0xd0, 0xd0);
+
+/// Left edge of the text column and top edge of everything.
+const MARGIN: usize = 60;
+/// Square the picture is fitted into, right-aligned.
+const PICTURE_SIDE: usize = 540;
+/// The mosaic has about this many blocks along the picture's longer side.
+const MOSAIC_BLOCKS: usize = 8;
+/// Title lines before the rest is cut off with `...`.
+const TITLE_LINES: usize = 3;
+
+/// Render the sharing card for `solved`, titled `title`.
+///
+/// Characters outside printable ASCII draw as `?`; callers with titles in
+/// other scripts should pass a fallback such as `Nonogram 56215` instead.
+pub fn render_card(title: &str, solved: &SolvedPuzzle) -> Vec<u8> {
+    let mut canvas = Canvas {
+        width: CARD_WIDTH as usize,
+        pixels: vec![BACKGROUND; (CARD_WIDTH * CARD_HEIGHT) as usize],
+    };
+    draw_mosaic(&mut canvas, solved);
+
+    let text_width = CARD_WIDTH as usize - PICTURE_SIDE - 3 * MARGIN;
+    let mut y = MARGIN;
+    for line in wrap(title, text_width / advance(6), TITLE_LINES) {
+        canvas.text(MARGIN, y, 6, INK, &line);
+        y += line_height(6);
+    }
+
+    let rows = solved.grid.len();
+    let cols = solved.grid.first().map_or(0, Vec::len);
+    let inks = solved.color_panel.len().saturating_sub(1);
+    let size = format!("{cols} x {rows}");
+    let colors = match inks {
+        1 => String::from("1 color"),
+        n => format!("{n} colors"),
+    };
+    y += line_height(6) / 2;
+    canvas.text(MARGIN, y, 4, INK, &size);
+    canvas.text(MARGIN, y + line_height(4), 4, MUTED, &colors);
+    let footer_y = CARD_HEIGHT as usize - MARGIN - 7 * 3;
+    canvas.text(MARGIN, footer_y, 3, MUTED, "Nonogram Solver");
+
+    let rgb: Vec<u8> = canvas.pixels.iter().flat_map(|c| c.channels()).collect();
+    encode_png(CARD_WIDTH, CARD_HEIGHT, &rgb)
+}
+
+struct Canvas {
+    width: usize,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
+        let height = self.pixels.len() / self.width;
+        for row in y..(y + h).min(height) {
+            let start = row * self.width;
+            let end = start + (x + w).min(self.width);
+            self.pixels[start + x.min(self.width)..end].fill(color);
+        }
+    }
+
+    /// Draw `text` with its top-left corner at (`x`, `y`), each font pixel
+    /// a `scale` x `scale` square.
+    fn text(&mut self, x: usize, y: usize, scale: usize, color: Color, text: &str) {
+        for (idx, ch) in text.chars().enumerate() {
+            let glyph = glyph(ch);
+            let left = x + idx * advance(scale);
+            for (col, bits) in glyph.iter().enumerate() {
+                for row in 0..7 {
+                    if bits >> row & 1 != 0 {
+                        self.fill(left + col * scale, y + row * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Horizontal distance between glyphs at `scale`: 5 columns and 1 of space.
+fn advance(scale: usize) -> usize {
+    6 * scale
+}
+
+fn line_height(scale: usize) -> usize {
+    10 * scale
+}
+
+/// The solution as blocks of averaged cell colors, lightened slightly so it
+/// reads as a teaser rather than the picture itself.
+fn draw_mosaic(canvas: &mut Canvas, solved: &SolvedPuzzle) {
+    let rows = solved.grid.len();
+    let cols = solved.grid.first().map_or(0, Vec::len);
+    if rows == 0 || cols == 0 {
+        return;
+    }
+    let palette: Vec<Color> = solved
+        .color_panel
+        .iter()
+        .map(|color| color.parse().unwrap_or(UNKNOWN))
+        .collect();
+    let cell_color = |row: usize, col: usize| {
+        solved
+            .grid
+            .get(row)
+            .and_then(|line| line.get(col))
+            .and_then(|&mask| mask_to_color_index(mask))
+            .and_then(|idx| palette.get(idx).copied())
+            .unwrap_or(UNKNOWN)
+    };
+
+    let longest = rows.max(cols);
+    let block = longest.div_ceil(MOSAIC_BLOCKS).max(2);
+    let (width, height) = (cols * PICTURE_SIDE / longest, rows * PICTURE_SIDE / longest);
+    let left = CARD_WIDTH as usize - MARGIN - PICTURE_SIDE + (PICTURE_SIDE - width) / 2;
+    let top = (CARD_HEIGHT as usize - height) / 2;
+
+    for block_row in (0..rows).step_by(block) {
+        for block_col in (0..cols).step_by(block) {
+            let (row_end, col_end) = ((block_row + block).min(rows), (block_col + block).min(cols));
+            let mut sum = [0u32; 3];
+            for row in block_row..row_end {
+                for col in block_col..col_end {
+                    for (total, channel) in sum.iter_mut().zip(cell_color(row, col).channels()) {
+                        *total += u32::from(channel);
+                    }
+                }
+            }
+            let count = ((row_end - block_row) * (col_end - block_col)) as u32;
+            let background = BACKGROUND.channels();
+            let [r, g, b] = core::array::from_fn(|i| {
+                ((3 * sum[i] / count + u32::from(background[i])) / 4) as u8
+            });
+            let (x0, x1) = (block_col * width / cols, col_end * width / cols);
+            let (y0, y1) = (block_row * height / rows, row_end * height / rows);
+            canvas.fill(left + x0, top + y0, x1 - x0, y1 - y0, Color::rgb(r, g, b));
+        }
+    }
+}
+
+/// Split `text` into at most `max_lines` lines of at most `width` chars,
+/// breaking at spaces where possible and ending with `...` if cut short.
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut truncated = false;
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        loop {
+            let used = current.chars().count();
+            let gap = usize::from(used > 0);
+            if used + gap + word.len() <= width {
+                if gap > 0 {
+                    current.push(' ');
+                }
+                current.extend(word.drain(..));
+                break;
+            }
+            if used == 0 {
+                // A word longer than a line: hard break it.
+                current.extend(word.drain(..width));
+            }
+            lines.push(core::mem::take(&mut current));
+            if lines.len() == max_lines {
+                truncated = true;
+                break;
+            }
+        }
+        if truncated {
+            break;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    if truncated && let Some(last) = lines.last_mut() {
+        let keep = width.saturating_sub(3).min(last.chars().count());
+        *last = last.chars().take(keep).collect::<String>() + "...";
+    }
+    lines
+}
+
+/// Columns of a 5x7 glyph, bit 0 at the top.
+fn glyph(ch: char) -> [u8; 5] {
+    let idx = match ch {
+        ' '..='~' => ch as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    FONT[idx]
+}
+
+/// Printable ASCII from `' '` to `'~'` (the classic 5x7 LCD font).
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31], [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00], [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x09, 0x01],
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41], [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31], [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x3f, 0x40, 0x38, 0x40, 0x3f], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x07, 0x08, 0x70, 0x08, 0x07], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00], [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x10, 0x08, 0x08, 0x10, 0x08],
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::string::ToString;
+
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn draws_title_and_a_blurred_picture() {
+        // A 16x16 red and white checkerboard.
+        let solved = SolvedPuzzle {
+            color_panel: vec!["#ffffff".to_string(), "#ff0000".to_string()],
+            grid: (0..16)
+                .map(|row| (0..16).map(|col| 1 << ((row + col) % 2)).collect())
+                .collect(),
+        };
+        let png = render_card("Checkers", &solved);
+        // Small enough for link unfurlers, which often cap images at 1 MB.
+        assert!(png.len() < 100_000, "{} bytes", png.len());
+        let (width, height, pixels) = decode(&png);
+        assert_eq!((width, height), (CARD_WIDTH, CARD_HEIGHT));
+        let at = |x: usize, y: usize| &pixels[(y * width as usize + x) * 3..][..3];
+
+        // Top stroke of the `C`, one font pixel in from its left edge.
+        assert_eq!(at(MARGIN + 6 + 1, MARGIN + 1), INK.channels());
+        // Blocks average to pink: no cell shows through as pure red or white.
+        let middle = CARD_HEIGHT as usize / 2;
+        let picture = (CARD_WIDTH as usize - MARGIN - PICTURE_SIDE)..(CARD_WIDTH as usize - MARGIN);
+        assert!(picture.clone().all(|x| at(x, middle) != [0xff, 0, 0]));
+        assert!(picture.clone().all(|x| at(x, middle) != [0xff, 0xff, 0xff]));
+        assert_ne!(at(picture.start + 10, middle), BACKGROUND.channels());
+    }
+
+    #[test]
+    fn wraps_titles_at_spaces_and_truncates() {
+        assert_eq!(wrap("a cat on a mat", 8, 3), ["a cat on", "a mat"]);
+        assert_eq!(wrap("abcdefghij", 4, 3), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("one two three four five", 5, 2), ["one", "tw..."]);
+        assert!(wrap("", 5, 2).is_empty());
+    }
+}
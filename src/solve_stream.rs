@@ -0,0 +1,121 @@
+//! Solve steps as a [`Stream`], for async servers that forward progress to
+//! a client as it happens instead of waiting for the whole solve.
+//!
+//! The solve itself is synchronous, so it runs on Tokio's blocking pool and
+//! hands steps over a small bounded channel: when the consumer falls behind
+//! the solver waits for it, and when the stream is dropped the solve stops
+//! at its next step.
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+use crate::nonogram_solver::{
+    Grid, SolveError, SolveStats, SolverOptions, solve_puzzle_steps_until,
+};
+use crate::puzzle_crawler::PuzzleData;
+
+/// Steps the solver may run ahead of the consumer.
+const BUFFER: usize = 4;
+
+/// One item of [`solve_stream`]: the steps in order, then how it ended.
+#[derive(Debug)]
+pub enum StepEvent {
+    /// The grid recorded as step `index`, the first being 0.
+    Step { index: usize, grid: Grid },
+    /// The solve finished; its last grid was the last `Step`.
+    Finished(SolveStats),
+    /// The solve failed after the steps already sent.
+    Failed(SolveError),
+}
+
+/// Solve `data` under `options`, yielding each step as the solver records
+/// it and ending with [`StepEvent::Finished`] or [`StepEvent::Failed`].
+///
+/// Must be called from within a Tokio runtime.
+pub fn solve_stream(
+    data: PuzzleData,
+    options: SolverOptions,
+) -> impl Stream<Item = StepEvent> + Send + Unpin {
+    let (tx, rx) = mpsc::channel(BUFFER);
+    tokio::task::spawn_blocking(move || {
+        let mut index = 0;
+        let result = solve_puzzle_steps_until(data, &options, |grid| {
+            let event = StepEvent::Step {
+                index,
+                grid: grid.clone(),
+            };
+            index += 1;
+            tx.blocking_send(event).is_ok()
+        });
+        let last = match result {
+            Ok(steps) => StepEvent::Finished(steps.stats),
+            Err(err) => StepEvent::Failed(err),
+        };
+        // Fails only if the stream was dropped, and then no one is waiting.
+        let _ = tx.blocking_send(last);
+    });
+    StepStream { rx }
+}
+
+struct StepStream {
+    rx: mpsc::Receiver<StepEvent>,
+}
+
+impl Stream for StepStream {
+    type Item = StepEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<StepEvent>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle_steps;
+
+    async fn next(stream: &mut (impl Stream<Item = StepEvent> + Unpin)) -> Option<StepEvent> {
+        core::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test]
+    async fn streams_the_same_steps() {
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .row([(2, 1)])
+            .col([(2, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let expected = solve_puzzle_steps(data.clone()).unwrap();
+
+        let mut stream = solve_stream(data.clone(), SolverOptions::default());
+        let mut steps = Vec::new();
+        while let Some(event) = next(&mut stream).await {
+            match event {
+                StepEvent::Step { index, grid } => {
+                    assert_eq!(index, steps.len());
+                    steps.push(grid);
+                }
+                StepEvent::Finished(stats) => assert!(stats.solved),
+                StepEvent::Failed(err) => panic!("solve failed: {err}"),
+            }
+        }
+        assert_eq!(steps, expected.steps);
+
+        let tight = SolverOptions {
+            max_side: 1,
+            ..SolverOptions::default()
+        };
+        let mut stream = solve_stream(data, tight);
+        assert!(matches!(
+            next(&mut stream).await,
+            Some(StepEvent::Failed(_))
+        ));
+        assert!(next(&mut stream).await.is_none());
+    }
+}
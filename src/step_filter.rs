@@ -0,0 +1,192 @@
+//! Post-processing for recorded solve steps.
+//!
+//! Different viewers want different step densities: a slider is fine with
+//! every pass, an animation wants a few frames. A [`StepFilter`] rewrites
+//! [`SolveSteps`] in place; chain several with [`StepPipeline`].
+//!
+//! ```
+//! use nonogram_solver::prelude::*;
+//! use nonogram_solver::step_filter::{Coalesce, Sample, StepPipeline};
+//!
+//! let data = PuzzleData::builder()
+//!     .palette(["#ffffff", "#000000"])
+//!     .row([(1, 1)])
+//!     .row([(2, 1)])
+//!     .col([(2, 1)])
+//!     .col([(1, 1)])
+//!     .build()
+//!     .unwrap();
+//! let mut steps = solve_puzzle_steps(data).unwrap();
+//! StepPipeline::new()
+//!     .with(Coalesce { min_changes: 2 })
+//!     .with(Sample { max_steps: 10 })
+//!     .apply(&mut steps);
+//! assert!(steps.steps.len() <= 10);
+//! ```
+//!
+//! Every built-in keeps the first and the last grid, so the blank start and
+//! the final state survive any chain.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::nonogram_solver::{Grid, GridDiff, SolveSteps};
+
+/// Rewrites the steps of a solve, e.g. to drop or merge frames.
+///
+/// Implemented for closures, so a one-off filter needs no type:
+/// `(|steps: &mut SolveSteps| steps.steps.truncate(5)).apply(&mut steps)`.
+pub trait StepFilter {
+    fn apply(&self, steps: &mut SolveSteps);
+}
+
+impl<F: Fn(&mut SolveSteps)> StepFilter for F {
+    fn apply(&self, steps: &mut SolveSteps) {
+        self(steps)
+    }
+}
+
+/// Filters applied in the order they were added.
+#[derive(Default)]
+pub struct StepPipeline {
+    filters: Vec<Box<dyn StepFilter>>,
+}
+
+impl StepPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `filter` to the end of the chain.
+    pub fn with(mut self, filter: impl StepFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+impl StepFilter for StepPipeline {
+    fn apply(&self, steps: &mut SolveSteps) {
+        for filter in &self.filters {
+            filter.apply(steps);
+        }
+    }
+}
+
+/// Drops grids identical to the one before them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dedupe;
+
+impl StepFilter for Dedupe {
+    fn apply(&self, steps: &mut SolveSteps) {
+        steps.steps.dedup();
+    }
+}
+
+/// Merges consecutive steps until each kept step changes at least
+/// `min_changes` cells since the previous kept one.
+#[derive(Debug, Clone, Copy)]
+pub struct Coalesce {
+    pub min_changes: usize,
+}
+
+impl StepFilter for Coalesce {
+    fn apply(&self, steps: &mut SolveSteps) {
+        let grids = core::mem::take(&mut steps.steps);
+        let last = grids.len().saturating_sub(1);
+        let mut kept: Vec<Grid> = Vec::with_capacity(grids.len());
+        for (idx, grid) in grids.into_iter().enumerate() {
+            let far_enough = kept
+                .last()
+                .is_none_or(|prev| prev.diff(&grid).len() >= self.min_changes);
+            if far_enough || idx == last {
+                kept.push(grid);
+            }
+        }
+        steps.steps = kept;
+    }
+}
+
+/// Keeps at most `max_steps` grids (but always the first and last), evenly
+/// spaced.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub max_steps: usize,
+}
+
+impl StepFilter for Sample {
+    fn apply(&self, steps: &mut SolveSteps) {
+        let len = steps.steps.len();
+        if len <= self.max_steps {
+            return;
+        }
+        let keep = self.max_steps.max(2);
+        let picked: Vec<usize> = (0..keep).map(|n| n * (len - 1) / (keep - 1)).collect();
+        let mut idx = 0;
+        steps.steps.retain(|_| {
+            let hit = picked.binary_search(&idx).is_ok();
+            idx += 1;
+            hit
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::SolveStats;
+    use alloc::vec;
+
+    /// Steps of a 1x4 grid where step `n` has cells `..counts[n]` solved.
+    fn steps(counts: &[usize]) -> SolveSteps {
+        SolveSteps {
+            color_panel: Vec::new(),
+            steps: counts
+                .iter()
+                .map(|&solved| vec![(0..4).map(|col| if col < solved { 1 } else { 3 }).collect()])
+                .collect(),
+            stats: SolveStats::default(),
+        }
+    }
+
+    fn solved_counts(steps: &SolveSteps) -> Vec<usize> {
+        steps
+            .steps
+            .iter()
+            .map(|grid| grid[0].iter().filter(|&&mask| mask == 1).count())
+            .collect()
+    }
+
+    #[test]
+    fn built_ins_keep_the_ends() {
+        let mut deduped = steps(&[0, 0, 1, 1, 4]);
+        Dedupe.apply(&mut deduped);
+        assert_eq!(solved_counts(&deduped), vec![0, 1, 4]);
+
+        let mut coalesced = steps(&[0, 1, 2, 3, 4]);
+        Coalesce { min_changes: 2 }.apply(&mut coalesced);
+        assert_eq!(solved_counts(&coalesced), vec![0, 2, 4]);
+
+        let mut sampled = steps(&[0, 1, 2, 3, 4]);
+        Sample { max_steps: 3 }.apply(&mut sampled);
+        assert_eq!(solved_counts(&sampled), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn pipeline_runs_filters_in_order() {
+        let pipeline = StepPipeline::new()
+            .with(Dedupe)
+            .with(|steps: &mut SolveSteps| steps.steps.truncate(2));
+        let mut filtered = steps(&[0, 0, 3, 4]);
+        pipeline.apply(&mut filtered);
+        assert_eq!(solved_counts(&filtered), vec![0, 3]);
+        assert_eq!(pipeline.len(), 2);
+    }
+}
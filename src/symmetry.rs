@@ -0,0 +1,132 @@
+//! Mirror and rotation symmetry read off a puzzle's clues.
+//!
+//! If the clues are symmetric, the mirror image of every solution is a
+//! solution too. A cell forced to a color in every solution therefore forces
+//! its mirror cell as well, which is what [`SolverOptions::use_symmetry`]
+//! propagates. Symmetric clues do not make the picture symmetric when the
+//! puzzle has several solutions, and the solver never assumes they do.
+//!
+//! [`SolverOptions::use_symmetry`]: crate::nonogram_solver::SolverOptions::use_symmetry
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::puzzle_crawler::{Group, PuzzleData};
+
+/// Which symmetries the clues have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symmetry {
+    /// Unchanged when flipped left to right.
+    pub left_right: bool,
+    /// Unchanged when flipped top to bottom.
+    pub top_bottom: bool,
+    /// Unchanged when turned 180 degrees.
+    pub half_turn: bool,
+}
+
+impl Symmetry {
+    /// Whether the clues have at least one symmetry.
+    pub fn any(self) -> bool {
+        self.left_right || self.top_bottom || self.half_turn
+    }
+
+    /// The cells `(row, col)` maps to under each symmetry present, in a
+    /// `rows` x `cols` grid.
+    pub(crate) fn images(
+        self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (flipped_row, flipped_col) = (rows - 1 - row, cols - 1 - col);
+        [
+            self.left_right.then_some((row, flipped_col)),
+            self.top_bottom.then_some((flipped_row, col)),
+            self.half_turn.then_some((flipped_row, flipped_col)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// The symmetries of `data`'s clues.
+pub fn detect_symmetry(data: &PuzzleData) -> Symmetry {
+    let rows = &data.row_groups;
+    let cols = &data.col_groups;
+    if rows.is_empty() || cols.is_empty() {
+        return Symmetry::default();
+    }
+    let palindromes = |lines: &[Vec<Group>]| lines.iter().all(|clue| reversed(clue, clue));
+    let mirrored = |lines: &[Vec<Group>]| lines.iter().eq(lines.iter().rev());
+    let turned = |lines: &[Vec<Group>]| {
+        lines
+            .iter()
+            .zip(lines.iter().rev())
+            .all(|(clue, opposite)| reversed(clue, opposite))
+    };
+    Symmetry {
+        left_right: palindromes(rows) && mirrored(cols),
+        top_bottom: palindromes(cols) && mirrored(rows),
+        half_turn: turned(rows) && turned(cols),
+    }
+}
+
+/// Whether `a` read backwards is `b`.
+fn reversed(a: &[Group], b: &[Group]) -> bool {
+    a.len() == b.len() && a.iter().eq(b.iter().rev())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_mirrors_and_half_turns() {
+        // Picture:  r . b
+        //           . r .
+        //           b . r
+        // Turning it 180 degrees gives the same picture; neither flip does.
+        let data = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(1, 1), (1, 2)])
+            .row([(1, 1)])
+            .row([(1, 2), (1, 1)])
+            .col([(1, 1), (1, 2)])
+            .col([(1, 1)])
+            .col([(1, 2), (1, 1)])
+            .build()
+            .unwrap();
+        let symmetry = detect_symmetry(&data);
+        assert_eq!(
+            symmetry,
+            Symmetry {
+                left_right: false,
+                top_bottom: false,
+                half_turn: true
+            }
+        );
+        assert_eq!(symmetry.images(0, 1, 3, 3).collect::<Vec<_>>(), [(2, 1)]);
+
+        // A plus sign has all three.
+        let plus = PuzzleData::builder()
+            .palette(["#ffffff", "#000000"])
+            .row([(1, 1)])
+            .row([(3, 1)])
+            .row([(1, 1)])
+            .col([(1, 1)])
+            .col([(3, 1)])
+            .col([(1, 1)])
+            .build()
+            .unwrap();
+        let all = detect_symmetry(&plus);
+        assert!(all.left_right && all.top_bottom && all.half_turn);
+        let empty = PuzzleData {
+            color_panel: Vec::new(),
+            row_groups: Vec::new(),
+            col_groups: Vec::new(),
+        };
+        assert!(!detect_symmetry(&empty).any());
+    }
+}
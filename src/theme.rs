@@ -0,0 +1,73 @@
+//! Light/dark theme, persisted in `localStorage` and applied as `data-theme` on `<html>`.
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::local_store;
+
+const STORAGE_KEY: &str = "nonogram-solver.theme";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Light => Self::Dark,
+            Self::Dark => Self::Light,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+/// Shared theme signal. Starts from the saved choice, else the OS preference.
+pub fn use_theme_provider() -> Signal<Theme> {
+    let mut theme = use_context_provider(|| Signal::new(Theme::Light));
+    use_effect(move || {
+        spawn(async move {
+            let saved = local_store::load::<Theme>(STORAGE_KEY).await;
+            let initial = match saved {
+                Some(saved) => saved,
+                None if prefers_dark().await => Theme::Dark,
+                None => Theme::Light,
+            };
+            theme.set(initial);
+        });
+    });
+    use_effect(move || apply(theme()));
+    theme
+}
+
+pub fn use_theme() -> Signal<Theme> {
+    use_context::<Signal<Theme>>()
+}
+
+pub fn set(mut theme: Signal<Theme>, next: Theme) {
+    theme.set(next);
+    local_store::save(STORAGE_KEY, &next);
+}
+
+fn apply(theme: Theme) {
+    let script = format!(
+        "document.documentElement.dataset.theme = \"{}\";",
+        theme.as_str()
+    );
+    let _ = document::eval(&script);
+}
+
+async fn prefers_dark() -> bool {
+    document::eval("return window.matchMedia('(prefers-color-scheme: dark)').matches;")
+        .join::<bool>()
+        .await
+        .unwrap_or(false)
+}
@@ -0,0 +1,291 @@
+//! Tiny PNG previews for puzzle lists.
+//!
+//! A solved puzzle renders as its picture. An unsolved one renders as muted
+//! bands of its palette in the puzzle's aspect ratio, so a list can show size
+//! and colors without giving the image away.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::color::Color;
+use crate::nonogram_solver::{SolvedPuzzle, mask_to_color_index};
+use crate::puzzle_crawler::PuzzleData;
+
+/// Cells that are still undetermined in a `Solved` grid.
+const UNKNOWN: Color = Color::rgb(0xd0, 0xd0, 0xd0);
+
+/// What to draw.
+#[derive(Debug, Clone, Copy)]
+pub enum Thumbnail<'a> {
+    /// The picture itself; only for puzzles the user has already seen solved.
+    Solved(&'a SolvedPuzzle),
+    /// A spoiler-free placeholder.
+    Unsolved(&'a PuzzleData),
+}
+
+/// Encode `source` as a PNG whose longer side is at most `max_px` pixels.
+///
+/// Cells are drawn as whole-pixel squares when they fit, otherwise the grid is
+/// sampled down. Palette entries that do not parse draw gray.
+pub fn render_thumbnail(source: Thumbnail<'_>, max_px: u32) -> Vec<u8> {
+    let (rows, cols) = match source {
+        Thumbnail::Solved(solved) => (solved.grid.len(), solved.grid.first().map_or(0, Vec::len)),
+        Thumbnail::Unsolved(data) => (data.row_groups.len(), data.col_groups.len()),
+    };
+    let (width, height) = fit(rows.max(1), cols.max(1), max_px.max(1) as usize);
+    let palette: Vec<Color> = match source {
+        Thumbnail::Solved(solved) => parse_palette(&solved.color_panel),
+        Thumbnail::Unsolved(data) => parse_palette(&data.color_panel),
+    };
+
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let color = match source {
+                Thumbnail::Solved(solved) => {
+                    let cell = solved
+                        .grid
+                        .get(y * rows / height)
+                        .and_then(|row| row.get(x * cols / width));
+                    cell.and_then(|&mask| mask_to_color_index(mask))
+                        .map_or(UNKNOWN, |idx| palette.get(idx).copied().unwrap_or(UNKNOWN))
+                }
+                Thumbnail::Unsolved(_) => band_color(&palette, y, height),
+            };
+            pixels.extend_from_slice(&color.channels());
+        }
+    }
+    encode_png(width as u32, height as u32, &pixels)
+}
+
+/// Output size for a `rows` x `cols` grid: an integer scale if the grid fits
+/// in `max_px`, otherwise shrunk to `max_px` on the longer side.
+fn fit(rows: usize, cols: usize, max_px: usize) -> (usize, usize) {
+    let longest = rows.max(cols);
+    if longest <= max_px {
+        let scale = max_px / longest;
+        (cols * scale, rows * scale)
+    } else {
+        (
+            (cols * max_px / longest).max(1),
+            (rows * max_px / longest).max(1),
+        )
+    }
+}
+
+fn parse_palette(colors: &[String]) -> Vec<Color> {
+    colors
+        .iter()
+        .map(|color| color.parse().unwrap_or(UNKNOWN))
+        .collect()
+}
+
+/// Horizontal bands of the non-background colors, blended towards white.
+fn band_color(palette: &[Color], y: usize, height: usize) -> Color {
+    let inks = palette.get(1..).unwrap_or_default();
+    let Some(&ink) = inks.get(y * inks.len() / height) else {
+        return Color::WHITE;
+    };
+    let [r, g, b] = ink.channels().map(|c| ((u16::from(c) + 2 * 255) / 3) as u8);
+    Color::rgb(r, g, b)
+}
+
+/// 8-bit RGB PNG. Each row is stored as its difference from the row above
+/// (filter "up"), so flat areas and repeated rows become runs of zeros, and
+/// the runs are deflated as back-references. Social cards are mostly flat
+/// color and shrink from megabytes to tens of kilobytes.
+pub(crate) fn encode_png(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    let mut above: &[u8] = &[];
+    for row in rgb.chunks(stride) {
+        raw.push(2); // filter: up
+        raw.extend(
+            row.iter()
+                .enumerate()
+                .map(|(idx, &byte)| byte.wrapping_sub(above.get(idx).copied().unwrap_or(0))),
+        );
+        above = row;
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend(deflate_runs(&raw));
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit, RGB, deflate, no filter, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    push_chunk(&mut png, b"IHDR", &ihdr);
+    push_chunk(&mut png, b"IDAT", &zlib);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Base match length of each deflate length code from 257, and its extra bits.
+#[rustfmt::skip]
+const LENGTH_CODES: [(usize, u32); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 1), (13, 1), (15, 1), (17, 1), (19, 2), (23, 2), (27, 2), (31, 2),
+    (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4),
+    (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+
+/// One fixed-Huffman deflate block in which every run of three or more
+/// repeats of the previous byte is a match at distance 1.
+fn deflate_runs(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::default();
+    out.bits(1, 1); // final block
+    out.bits(1, 2); // fixed Huffman codes
+    let mut pos = 0;
+    while pos < data.len() {
+        let run = match pos.checked_sub(1).map(|prev| data[prev]) {
+            Some(prev) => data[pos..]
+                .iter()
+                .take(258)
+                .take_while(|&&byte| byte == prev)
+                .count(),
+            None => 0,
+        };
+        if run >= 3 {
+            let code = LENGTH_CODES.iter().rposition(|&(base, _)| base <= run);
+            let code = code.unwrap_or(0);
+            let (base, extra) = LENGTH_CODES[code];
+            out.symbol(257 + code as u16);
+            out.bits((run - base) as u32, extra);
+            out.huffman(0, 5); // distance code 0: distance 1
+            pos += run;
+        } else {
+            out.symbol(u16::from(data[pos]));
+            pos += 1;
+        }
+    }
+    out.symbol(256); // end of block
+    out.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    len: u32,
+}
+
+impl BitWriter {
+    /// `count` bits of `value`, least significant first.
+    fn bits(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.len;
+        self.len += count;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// A Huffman code, which deflate packs most significant bit first.
+    fn huffman(&mut self, code: u32, count: u32) {
+        self.bits(code.reverse_bits() >> (32 - count), count);
+    }
+
+    /// A literal/length symbol under the fixed code.
+    fn symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.huffman(0x30 + symbol, 8),
+            144..=255 => self.huffman(0x190 + symbol - 144, 9),
+            256..=279 => self.huffman(symbol - 256, 7),
+            _ => self.huffman(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::string::ToString;
+
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    fn palette() -> Vec<String> {
+        vec!["#ffffff".to_string(), "#ff0000".to_string()]
+    }
+
+    #[test]
+    fn draws_solved_cells_as_squares() {
+        let solved = SolvedPuzzle {
+            color_panel: palette(),
+            grid: vec![vec![0b10, 0b01], vec![0b01, 0b11]],
+        };
+        let (width, height, pixels) = decode(&render_thumbnail(Thumbnail::Solved(&solved), 5));
+        assert_eq!((width, height), (4, 4));
+        let at = |x: usize, y: usize| &pixels[(y * 4 + x) * 3..][..3];
+        assert_eq!(at(1, 1), [0xff, 0, 0]);
+        assert_eq!(at(2, 0), [0xff, 0xff, 0xff]);
+        assert_eq!(at(3, 3), UNKNOWN.channels());
+    }
+
+    #[test]
+    fn placeholder_keeps_shape_and_hides_the_picture() {
+        let data = PuzzleData {
+            color_panel: palette(),
+            row_groups: vec![Vec::new(); 40],
+            col_groups: vec![Vec::new(); 20],
+        };
+        let (width, height, pixels) = decode(&render_thumbnail(Thumbnail::Unsolved(&data), 16));
+        assert_eq!((width, height), (8, 16));
+        assert!(pixels.chunks(3).all(|px| px == [0xff, 0xaa, 0xaa]));
+    }
+}
@@ -0,0 +1,310 @@
+//! Turning, flipping, cropping and recoloring puzzles and grids.
+//!
+//! Transposing or mirroring only rearranges the clues, so those work on any
+//! puzzle. Cropping cuts runs in two, which the clues cannot describe, so a
+//! puzzle is solved first and the crop's clues are read off its picture.
+//! Recoloring works on the clues as long as no two groups that may touch end
+//! up the same color.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::nonogram_solver::{Grid, SolveError, mask_to_color_index, solve_puzzle_steps};
+use crate::puzzle_builder::{Line, ValidationError};
+use crate::puzzle_crawler::{Group, PuzzleData};
+use crate::repair::puzzle_from_picture;
+
+/// A rectangle of cells, as half-open row and column ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub rows: Range<usize>,
+    pub cols: Range<usize>,
+}
+
+impl Region {
+    fn fits(&self, rows: usize, cols: usize) -> bool {
+        !self.rows.is_empty()
+            && !self.cols.is_empty()
+            && self.rows.end <= rows
+            && self.cols.end <= cols
+    }
+}
+
+/// Why a puzzle cannot be cropped.
+#[derive(Debug)]
+pub enum CropError {
+    /// The region is empty or reaches past the grid.
+    OutOfBounds,
+    /// Solving the puzzle failed.
+    Solve(SolveError),
+    /// Line logic left this cell inside the region open.
+    Undetermined { row: usize, col: usize },
+}
+
+impl fmt::Display for CropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "the region is empty or outside the grid"),
+            Self::Solve(err) => write!(f, "{err}"),
+            Self::Undetermined { row, col } => {
+                write!(
+                    f,
+                    "cell ({row}, {col}) cannot be determined without guessing"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for CropError {}
+
+/// Reorientations of a grid of cell masks, indexed `[row][col]`.
+pub trait GridTransform {
+    /// Rows become columns: cell `(r, c)` moves to `(c, r)`.
+    fn transpose(&self) -> Grid;
+    /// Flipped left to right.
+    fn mirror_h(&self) -> Grid;
+    /// Flipped top to bottom.
+    fn mirror_v(&self) -> Grid;
+    /// The cells inside `region`; `None` if it is empty or outside the grid.
+    fn crop(&self, region: &Region) -> Option<Grid>;
+}
+
+impl GridTransform for [Vec<u64>] {
+    fn transpose(&self) -> Grid {
+        let cols = self.first().map_or(0, Vec::len);
+        (0..cols)
+            .map(|col| self.iter().map(|line| line[col]).collect())
+            .collect()
+    }
+
+    fn mirror_h(&self) -> Grid {
+        self.iter()
+            .map(|line| line.iter().rev().copied().collect())
+            .collect()
+    }
+
+    fn mirror_v(&self) -> Grid {
+        self.iter().rev().cloned().collect()
+    }
+
+    fn crop(&self, region: &Region) -> Option<Grid> {
+        let cols = self.first().map_or(0, Vec::len);
+        if !region.fits(self.len(), cols) {
+            return None;
+        }
+        Some(
+            self[region.rows.clone()]
+                .iter()
+                .map(|line| line[region.cols.clone()].to_vec())
+                .collect(),
+        )
+    }
+}
+
+impl PuzzleData {
+    /// The puzzle whose solutions are this one's, transposed.
+    pub fn transpose(&self) -> Self {
+        Self {
+            color_panel: self.color_panel.clone(),
+            row_groups: self.col_groups.clone(),
+            col_groups: self.row_groups.clone(),
+        }
+    }
+
+    /// The puzzle whose solutions are this one's, flipped left to right.
+    pub fn mirror_h(&self) -> Self {
+        Self {
+            color_panel: self.color_panel.clone(),
+            row_groups: self.row_groups.iter().map(|clue| reversed(clue)).collect(),
+            col_groups: self.col_groups.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// The puzzle whose solutions are this one's, flipped top to bottom.
+    pub fn mirror_v(&self) -> Self {
+        Self {
+            color_panel: self.color_panel.clone(),
+            row_groups: self.row_groups.iter().rev().cloned().collect(),
+            col_groups: self.col_groups.iter().map(|clue| reversed(clue)).collect(),
+        }
+    }
+
+    /// The puzzle for the part of the picture inside `region`, keeping the
+    /// palette.
+    ///
+    /// Solves the puzzle first, so every cell inside `region` must be
+    /// determined by line logic; cells outside it may stay open.
+    pub fn crop(&self, region: &Region) -> Result<Self, CropError> {
+        if !region.fits(self.row_groups.len(), self.col_groups.len()) {
+            return Err(CropError::OutOfBounds);
+        }
+        let steps = solve_puzzle_steps(self.clone()).map_err(CropError::Solve)?;
+        let grid = steps
+            .steps
+            .last()
+            .ok_or(CropError::Solve(SolveError::Unsolvable))?;
+        let mut picture = Vec::with_capacity(region.rows.len());
+        for row in region.rows.clone() {
+            let mut line = Vec::with_capacity(region.cols.len());
+            for col in region.cols.clone() {
+                let color = mask_to_color_index(grid[row][col])
+                    .ok_or(CropError::Undetermined { row, col })?;
+                line.push(color);
+            }
+            picture.push(line);
+        }
+        Ok(puzzle_from_picture(self.color_panel.clone(), &picture))
+    }
+
+    /// The puzzle with each color `from` painted `to` instead, for every
+    /// `(from, to)` in `merges`; `to` 0 turns the color into background.
+    ///
+    /// The palette is kept, so merged-away colors are simply unused. Fails
+    /// with [`ValidationError::AmbiguousMerge`] if two groups next to each
+    /// other in a clue would end up the same color without having been so:
+    /// they may touch, making one group, or not.
+    pub fn recolor(&self, merges: &[(usize, usize)]) -> Result<Self, ValidationError> {
+        let colors = self.color_panel.len();
+        if let Some(&(from, to)) = merges
+            .iter()
+            .find(|&&(from, to)| from == 0 || from >= colors || to >= colors)
+        {
+            return Err(ValidationError::BadMerge { from, to });
+        }
+        let paint = |color: usize| {
+            merges
+                .iter()
+                .find(|&&(from, _)| from == color)
+                .map_or(color, |&(_, to)| to)
+        };
+        let recolor_line = |line: Line, clue: &[Group]| -> Result<Vec<Group>, ValidationError> {
+            for pair in clue.windows(2) {
+                let color_id = paint(pair[0].color_id);
+                if color_id != 0
+                    && color_id == paint(pair[1].color_id)
+                    && pair[0].color_id != pair[1].color_id
+                {
+                    return Err(ValidationError::AmbiguousMerge { line, color_id });
+                }
+            }
+            Ok(clue
+                .iter()
+                .filter_map(|group| {
+                    let color_id = paint(group.color_id);
+                    (color_id != 0).then_some(Group { color_id, ..*group })
+                })
+                .collect())
+        };
+        Ok(Self {
+            color_panel: self.color_panel.clone(),
+            row_groups: self
+                .row_groups
+                .iter()
+                .enumerate()
+                .map(|(idx, clue)| recolor_line(Line::Row(idx), clue))
+                .collect::<Result<_, _>>()?,
+            col_groups: self
+                .col_groups
+                .iter()
+                .enumerate()
+                .map(|(idx, clue)| recolor_line(Line::Col(idx), clue))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn reversed(clue: &[Group]) -> Vec<Group> {
+    clue.iter().rev().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram_solver::solve_puzzle;
+    use alloc::vec;
+
+    // Picture:  r r .
+    //           . b b
+    //           . . b
+    fn puzzle() -> PuzzleData {
+        PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(2, 1)])
+            .row([(2, 2)])
+            .row([(1, 2)])
+            .col([(1, 1)])
+            .col([(1, 1), (1, 2)])
+            .col([(2, 2)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn reorienting_the_clues_reorients_the_solution() {
+        let data = puzzle();
+        let grid = solve_puzzle(data.clone()).unwrap().grid;
+        let solved = |data: PuzzleData| solve_puzzle(data).unwrap().grid;
+        assert_eq!(solved(data.transpose()), grid.transpose());
+        assert_eq!(solved(data.mirror_h()), grid.mirror_h());
+        assert_eq!(solved(data.mirror_v()), grid.mirror_v());
+        assert_eq!(grid.transpose().transpose(), grid);
+    }
+
+    #[test]
+    fn crops_recompute_clues_from_the_picture() {
+        let data = puzzle();
+        let region = Region {
+            rows: 0..2,
+            cols: 1..3,
+        };
+        // Picture:  r .
+        //           b b
+        let cropped = data.crop(&region).unwrap();
+        let expected = PuzzleData::builder()
+            .palette(["#ffffff", "#ff0000", "#0000ff"])
+            .row([(1, 1)])
+            .row([(2, 2)])
+            .col([(1, 1), (1, 2)])
+            .col([(1, 2)])
+            .build()
+            .unwrap();
+        assert_eq!(cropped, expected);
+
+        let grid = solve_puzzle(data.clone()).unwrap().grid;
+        assert_eq!(
+            grid.crop(&region),
+            Some(vec![vec![0b010, 0b001], vec![0b100, 0b100]])
+        );
+        let outside = Region {
+            rows: 2..4,
+            cols: 0..1,
+        };
+        assert!(matches!(data.crop(&outside), Err(CropError::OutOfBounds)));
+        assert_eq!(grid.crop(&outside), None);
+    }
+
+    #[test]
+    fn recolors_clues_unless_groups_may_touch() {
+        let data = puzzle();
+        // Dropping red leaves the blue picture.
+        let blue = data.recolor(&[(1, 0)]).unwrap();
+        assert_eq!(blue.row_groups[0], []);
+        assert_eq!(blue.col_groups[1], [Group::from((1, 2))]);
+        assert_eq!(solve_puzzle(blue).unwrap().grid[0], [0b001, 0b001, 0b001]);
+
+        // Red sits right above blue in column 1, so they may touch.
+        assert_eq!(
+            data.recolor(&[(1, 2)]),
+            Err(ValidationError::AmbiguousMerge {
+                line: Line::Col(1),
+                color_id: 2
+            })
+        );
+        assert_eq!(
+            data.recolor(&[(0, 1)]),
+            Err(ValidationError::BadMerge { from: 0, to: 1 })
+        );
+    }
+}
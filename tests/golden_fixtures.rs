@@ -0,0 +1,91 @@
+//! Offline parse -> solve -> verify runs over the saved pages in `tests/fixtures/`.
+//!
+//! Each `<name>.html` page has a `<name>.json` sidecar with the expected
+//! `PuzzleData`, page metadata and solution (color ids, 0 = white).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nonogram_solver::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Expected {
+    kind: String,
+    id: String,
+    title: Option<String>,
+    author: Option<String>,
+    #[serde(default)]
+    rating: Option<String>,
+    puzzle: PuzzleData,
+    solution: Vec<Vec<usize>>,
+}
+
+fn fixtures() -> Vec<(PathBuf, Expected)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut pages: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("fixtures directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    pages.sort();
+    assert!(!pages.is_empty(), "no fixtures in {}", dir.display());
+    pages
+        .into_iter()
+        .map(|page| {
+            let sidecar = page.with_extension("json");
+            let json = fs::read_to_string(&sidecar)
+                .unwrap_or_else(|err| panic!("{}: {err}", sidecar.display()));
+            let expected = serde_json::from_str(&json)
+                .unwrap_or_else(|err| panic!("{}: {err}", sidecar.display()));
+            (page, expected)
+        })
+        .collect()
+}
+
+fn kind(expected: &Expected) -> PuzzleKind {
+    match expected.kind.as_str() {
+        "bw" => PuzzleKind::BlackWhite,
+        _ => PuzzleKind::Color,
+    }
+}
+
+#[test]
+fn pages_decode_to_expected_clues() {
+    for (page, expected) in fixtures() {
+        let html = fs::read_to_string(&page).unwrap();
+        let data = parse_puzzle(kind(&expected), &html)
+            .unwrap_or_else(|err| panic!("{}: {err}", page.display()));
+        assert_eq!(data, expected.puzzle, "{}", page.display());
+    }
+}
+
+#[test]
+fn pages_yield_expected_meta() {
+    for (page, expected) in fixtures() {
+        let html = fs::read_to_string(&page).unwrap();
+        let meta = parse_puzzle_meta(kind(&expected), &expected.id, &html);
+        assert_eq!(meta.title, expected.title, "{}", page.display());
+        assert_eq!(meta.author, expected.author, "{}", page.display());
+        assert_eq!(meta.rating, expected.rating, "{}", page.display());
+    }
+}
+
+#[test]
+fn pages_solve_to_expected_grid() {
+    for (page, expected) in fixtures() {
+        let html = fs::read_to_string(&page).unwrap();
+        let data = parse_puzzle(kind(&expected), &html).unwrap();
+        let solved = solve_puzzle(data).unwrap_or_else(|err| panic!("{}: {err}", page.display()));
+        let grid: Vec<Vec<usize>> = solved
+            .grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&mask| mask_to_color_index(mask).expect("unsolved cell"))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(grid, expected.solution, "{}", page.display());
+    }
+}
@@ -0,0 +1,64 @@
+//! Checks that the line solver reuses its buffers instead of allocating per
+//! line. It swaps in a counting global allocator, so it lives in a test
+//! binary of its own rather than under every unit test.
+//!
+//! The line solver is private to the crate, so its source is included here
+//! as a module; its own unit tests come along and run here too.
+
+extern crate alloc;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+#[allow(dead_code)]
+#[path = "../src/one_line_solver.rs"]
+mod one_line_solver;
+
+use one_line_solver::{LineGroup, OneLineSolver};
+
+/// Counts allocations made by the current thread, so parallel tests do not
+/// disturb each other's numbers.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn reuses_buffers_across_updates() {
+    let mut solver = OneLineSolver::new(10);
+    let groups: Vec<LineGroup> = [(2, 1), (1, 2), (3, 1)]
+        .into_iter()
+        .map(LineGroup::from)
+        .collect();
+    let mut lines = vec![vec![0b111u64; 10], vec![0b111u64; 8], vec![0b111u64; 10]];
+    let before = allocations();
+    for cells in &mut lines {
+        assert!(solver.update_state(&groups, cells));
+    }
+    assert_eq!(allocations(), before);
+
+    // Growing takes one allocation per buffer, not one per cache row.
+    let mut long = vec![0b111u64; 40];
+    let before = allocations();
+    assert!(solver.update_state(&groups, &mut long));
+    assert!(allocations() - before <= 3);
+}
@@ -0,0 +1,104 @@
+//! Keeps `public/openapi.json` in step with the server functions in
+//! `src/main.rs` and with the serde models it describes.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use nonogram_solver::prelude::*;
+use serde_json::Value;
+
+fn read(relative: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)).unwrap()
+}
+
+fn spec() -> Value {
+    serde_json::from_str(&read("public/openapi.json")).expect("valid JSON")
+}
+
+/// `(method, path)` for every `#[get(...)]`-style attribute in the app's
+/// server modules, with `:param` segments rewritten to OpenAPI's `{param}`.
+fn declared_routes() -> BTreeSet<(String, String)> {
+    let source = read("src/main.rs") + &read("src/daily.rs");
+    let mut routes = BTreeSet::new();
+    for line in source.lines().map(str::trim) {
+        for method in ["get", "post", "put", "patch", "delete"] {
+            let Some(rest) = line.strip_prefix(&format!("#[{method}(\"")) else {
+                continue;
+            };
+            let path = rest.split('"').next().unwrap();
+            let path = path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{param}}}"),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            routes.insert((method.to_string(), path));
+        }
+    }
+    routes
+}
+
+#[test]
+fn spec_lists_every_server_route() {
+    let spec = spec();
+    let documented: BTreeSet<(String, String)> = spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, item)| {
+            item.as_object()
+                .unwrap()
+                .keys()
+                .map(move |method| (method.clone(), path.clone()))
+        })
+        .collect();
+    assert!(!documented.is_empty());
+    assert_eq!(documented, declared_routes());
+}
+
+fn assert_fields_match(spec: &Value, name: &str, value: &Value) {
+    let mut required: Vec<&str> = spec["components"]["schemas"][name]["required"]
+        .as_array()
+        .unwrap_or_else(|| panic!("{name} has no required list"))
+        .iter()
+        .map(|key| key.as_str().unwrap())
+        .collect();
+    let mut actual: Vec<&str> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    required.sort_unstable();
+    actual.sort_unstable();
+    assert_eq!(required, actual, "{name}");
+}
+
+#[test]
+fn schemas_match_serialized_models() {
+    let spec = spec();
+    let data = PuzzleData::builder()
+        .palette(["#ffffff", "#000000"])
+        .row([(1, 1)])
+        .col([(1, 1)])
+        .build()
+        .unwrap();
+    let fetched = FetchedPuzzle {
+        data: data.clone(),
+        meta: PuzzleMeta::default(),
+    };
+    let steps = solve_puzzle_steps(data.clone()).unwrap();
+
+    let fetched = serde_json::to_value(&fetched).unwrap();
+    let steps = serde_json::to_value(&steps).unwrap();
+    assert_fields_match(&spec, "PuzzleData", &serde_json::to_value(&data).unwrap());
+    assert_fields_match(&spec, "Group", &fetched["data"]["row_groups"][0][0]);
+    assert_fields_match(&spec, "FetchedPuzzle", &fetched);
+    assert_fields_match(&spec, "PuzzleMeta", &fetched["meta"]);
+    assert_fields_match(&spec, "SolveSteps", &steps);
+    assert_fields_match(&spec, "SolveStats", &steps["stats"]);
+    assert_fields_match(&spec, "Duration", &steps["stats"]["elapsed"]);
+}
@@ -0,0 +1,91 @@
+//! Corpus-style robustness tests for `parse_puzzle`: hostile pages must be
+//! rejected with an error, never a panic. `fuzz/` runs the same inputs under
+//! libFuzzer for longer sessions.
+
+use nonogram_solver::prelude::{PuzzleKind, parse_clue_text, parse_puzzle};
+use proptest::prelude::*;
+
+fn page_with(entries: &[[i64; 4]]) -> String {
+    let body: Vec<String> = entries
+        .iter()
+        .map(|entry| format!("[{},{},{},{}]", entry[0], entry[1], entry[2], entry[3]))
+        .collect();
+    format!("<script>var d=[{}];</script>", body.join(","))
+}
+
+fn kind() -> impl Strategy<Value = PuzzleKind> {
+    prop_oneof![Just(PuzzleKind::Color), Just(PuzzleKind::BlackWhite)]
+}
+
+/// Values that stress the decoder's modular arithmetic.
+fn d_value() -> impl Strategy<Value = i64> {
+    prop_oneof![
+        -3i64..100,
+        Just(i64::MIN),
+        Just(i64::MAX),
+        Just(0),
+        any::<i64>(),
+    ]
+}
+
+#[test]
+fn rejects_known_bad_pages() {
+    let cases = [
+        "",
+        "var d=",
+        "var d=[",
+        "var d=[];",
+        "var d=[[1,2,3]];",
+        "var d=[[99999999999999999999999,0,0,1]];",
+        "var d=[[--1,-,-2,3]];",
+        "var d=[[1,1,1,1],[1,1,1,1],[1,1,1,1],[1,1,1,1],[1,1,1,1],[1,1,1,1]];",
+    ];
+    for html in cases {
+        for kind in [PuzzleKind::Color, PuzzleKind::BlackWhite] {
+            assert!(parse_puzzle(kind, html).is_err(), "{html:?}");
+        }
+    }
+    let extremes = [[i64::MIN, i64::MAX, i64::MIN, -1]; 8];
+    assert!(parse_puzzle(PuzzleKind::Color, &page_with(&extremes)).is_err());
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_text_never_panics(kind in kind(), html in ".{0,200}") {
+        let _ = parse_puzzle(kind, &html);
+        let _ = parse_puzzle(kind, &format!("var d={html}];"));
+    }
+
+    #[test]
+    fn arbitrary_clue_text_never_panics(text in "(rows:|cols:|[0-9a-zé;, \n]){0,80}") {
+        let _ = parse_clue_text(&text);
+    }
+
+    #[test]
+    fn arbitrary_d_arrays_never_panic(
+        kind in kind(),
+        entries in prop::collection::vec(prop::array::uniform4(d_value()), 0..24),
+    ) {
+        let _ = parse_puzzle(kind, &page_with(&entries));
+    }
+
+    #[test]
+    fn decoded_grids_fit_their_dimensions(
+        kind in kind(),
+        entries in prop::collection::vec(prop::array::uniform4(-3i64..40), 6..24),
+    ) {
+        if let Ok(data) = parse_puzzle(kind, &page_with(&entries)) {
+            let cols = data.col_groups.len();
+            let rows = data.row_groups.len();
+            for groups in &data.row_groups {
+                prop_assert!(groups.iter().map(|g| g.len.min()).sum::<usize>() <= cols);
+            }
+            for groups in &data.col_groups {
+                prop_assert!(groups.iter().map(|g| g.len.min()).sum::<usize>() <= rows);
+            }
+            for group in data.row_groups.iter().chain(&data.col_groups).flatten() {
+                prop_assert!(group.color_id < data.color_panel.len());
+            }
+        }
+    }
+}
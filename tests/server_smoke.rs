@@ -0,0 +1,207 @@
+//! End-to-end smoke test of the server functions: boots the app binary on a
+//! free port, with page fetches sent through `NONOGRAM_FETCH_PROXY` to a mock
+//! site serving `tests/fixtures/`, and checks the JSON that `/api/puzzle` and
+//! `/api/solve` return against `public/openapi.json`'s contracts.
+
+#![cfg(feature = "server")]
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nonogram_solver::prelude::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Deserialize)]
+struct Expected {
+    kind: String,
+    id: String,
+    title: Option<String>,
+    puzzle: PuzzleData,
+}
+
+impl Expected {
+    fn puzzle_ref(&self) -> PuzzleRef {
+        let kind = match self.kind.as_str() {
+            "bw" => PuzzleKind::BlackWhite,
+            _ => PuzzleKind::Color,
+        };
+        PuzzleRef::nonograms_org(kind, self.id.clone())
+    }
+
+    /// The `kind` path segment of `/api/puzzle/{kind}/{puzzle_id}`.
+    fn kind_param(&self) -> &str {
+        if self.kind == "bw" { "bw" } else { "color" }
+    }
+}
+
+/// Every fixture's sidecar, with the page keyed by its nonograms.org URL.
+fn fixtures() -> (Vec<Expected>, HashMap<String, String>) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut expected = Vec::new();
+    let mut pages = HashMap::new();
+    for entry in fs::read_dir(&dir).expect("fixtures directory") {
+        let page = entry.unwrap().path();
+        if page.extension().is_none_or(|ext| ext != "html") {
+            continue;
+        }
+        let sidecar: Expected =
+            serde_json::from_str(&fs::read_to_string(page.with_extension("json")).unwrap())
+                .unwrap_or_else(|err| panic!("{}: {err}", page.display()));
+        pages.insert(
+            sidecar.puzzle_ref().url(),
+            fs::read_to_string(&page).unwrap(),
+        );
+        expected.push(sidecar);
+    }
+    assert!(!expected.is_empty(), "no fixtures in {}", dir.display());
+    (expected, pages)
+}
+
+/// A prefix-style proxy standing in for nonograms.org: `GET /<page url>`
+/// answers with that fixture page, anything else with 404.
+fn mock_site(pages: HashMap<String, String>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            let mut header = String::new();
+            while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+                header.clear();
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let response = match pages.get(path.trim_start_matches('/')) {
+                Some(html) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{html}",
+                    html.len()
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    addr
+}
+
+/// The app binary, killed on drop.
+struct Server {
+    child: Child,
+    base: String,
+}
+
+impl Server {
+    fn start(site: SocketAddr) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nonogram-solver"))
+            .env("IP", "127.0.0.1")
+            .env("PORT", port.to_string())
+            .env("NONOGRAM_FETCH_PROXY", format!("http://{site}/"))
+            .env_remove("NONOGRAM_CACHE_DIR")
+            .env_remove("NONOGRAM_PREFETCH")
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("start the server binary");
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while TcpStream::connect(("127.0.0.1", port)).is_err() {
+            if let Some(status) = child.try_wait().unwrap() {
+                panic!("server exited before listening: {status}");
+            }
+            assert!(Instant::now() < deadline, "server not listening on {port}");
+            thread::sleep(Duration::from_millis(100));
+        }
+        Self {
+            child,
+            base: format!("http://127.0.0.1:{port}"),
+        }
+    }
+
+    async fn get(&self, path: &str) -> Value {
+        let response = reqwest::get(format!("{}{path}", self.base)).await.unwrap();
+        assert!(
+            response.status().is_success(),
+            "GET {path}: {}",
+            response.status()
+        );
+        serde_json::from_str(&response.text().await.unwrap()).unwrap()
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Value {
+        let response = reqwest::Client::new()
+            .post(format!("{}{path}", self.base))
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap();
+        assert!(
+            response.status().is_success(),
+            "POST {path}: {}",
+            response.status()
+        );
+        serde_json::from_str(&response.text().await.unwrap()).unwrap()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[tokio::test]
+async fn server_functions_keep_their_json_contracts() {
+    let (expected, pages) = fixtures();
+    let server = Server::start(mock_site(pages));
+
+    for fixture in &expected {
+        let path = format!("/api/puzzle/{}/{}", fixture.kind_param(), fixture.id);
+        let body = server.get(&path).await;
+        let fetched: FetchedPuzzle = serde_json::from_value(body["Ok"].clone())
+            .unwrap_or_else(|err| panic!("{path}: {err} in {body}"));
+        assert_eq!(fetched.data, fixture.puzzle, "{path}");
+        assert_eq!(fetched.meta.id, fixture.id, "{path}");
+        assert_eq!(fetched.meta.title, fixture.title, "{path}");
+        assert_eq!(
+            fetched.meta.source_url,
+            fixture.puzzle_ref().url(),
+            "{path}"
+        );
+    }
+    assert_eq!(
+        server.get("/api/puzzle/color/999999").await,
+        json!({ "Err": "NotFound" })
+    );
+
+    for fixture in &expected {
+        let body = server
+            .post("/api/solve", &json!({ "data": fixture.puzzle }))
+            .await;
+        let steps: SolveSteps = serde_json::from_value(body["Ok"].clone())
+            .unwrap_or_else(|err| panic!("{}: {err} in {body}", fixture.id));
+        let local = solve_puzzle_steps_with(fixture.puzzle.clone(), &SolverOptions::web()).unwrap();
+        assert_eq!(steps.steps, local.steps, "{}", fixture.id);
+        assert!(steps.stats.solved, "{}", fixture.id);
+    }
+    let no_palette =
+        json!({ "data": { "color_panel": [], "row_groups": [[]], "col_groups": [[]] } });
+    let body = server.post("/api/solve", &no_palette).await;
+    assert!(body["Err"].is_string(), "{body}");
+}
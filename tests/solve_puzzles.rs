@@ -1,5 +1,6 @@
-use nonogram_solver::nonogram_solver::{mask_to_color_index, solve_puzzle};
-use nonogram_solver::puzzle_crawler::{Group, PuzzleKind, fetch_puzzle};
+#![cfg(feature = "crawler")]
+
+use nonogram_solver::prelude::*;
 
 fn groups_from_grid(grid: &[Vec<u64>]) -> (Vec<Vec<Group>>, Vec<Vec<Group>>) {
     let rows = grid.len();
@@ -17,7 +18,7 @@ fn groups_from_grid(grid: &[Vec<u64>]) -> (Vec<Vec<Group>>, Vec<Vec<Group>>) {
             let len = col - start;
             if color > 0 && len > 0 {
                 groups.push(Group {
-                    len,
+                    len: GroupLen::Exact(len),
                     color_id: color,
                 });
             }
@@ -38,7 +39,7 @@ fn groups_from_grid(grid: &[Vec<u64>]) -> (Vec<Vec<Group>>, Vec<Vec<Group>>) {
             let len = row - start;
             if color > 0 && len > 0 {
                 groups.push(Group {
-                    len,
+                    len: GroupLen::Exact(len),
                     color_id: color,
                 });
             }
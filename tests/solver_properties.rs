@@ -0,0 +1,145 @@
+//! Property tests: random solved grids, their derived clues, and the solver.
+//!
+//! proptest shrinks any failure down to a minimal grid and records it under
+//! `proptest-regressions/`, so commit that file to keep replaying the case.
+
+use nonogram_solver::prelude::*;
+use proptest::prelude::*;
+
+/// Color ids per cell, row-major; 0 is white.
+type ColorGrid = Vec<Vec<usize>>;
+
+fn line_clues(line: impl IntoIterator<Item = usize>) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut prev = 0;
+    for color in line {
+        if color != 0 {
+            match groups.last_mut() {
+                Some(group) if prev == color => group.len = GroupLen::Exact(group.len.min() + 1),
+                _ => groups.push(Group {
+                    len: GroupLen::Exact(1),
+                    color_id: color,
+                }),
+            }
+        }
+        prev = color;
+    }
+    groups
+}
+
+fn puzzle_from_grid(grid: &ColorGrid, color_count: usize) -> PuzzleData {
+    let cols = grid[0].len();
+    PuzzleData {
+        color_panel: (0..color_count).map(|id| format!("#{id:06x}")).collect(),
+        row_groups: grid
+            .iter()
+            .map(|row| line_clues(row.iter().copied()))
+            .collect(),
+        col_groups: (0..cols)
+            .map(|col| line_clues(grid.iter().map(|row| row[col])))
+            .collect(),
+    }
+}
+
+/// A solved grid up to 8x8 with 2-4 colors, plus its color count.
+fn solved_grid() -> impl Strategy<Value = (ColorGrid, usize)> {
+    (1usize..=8, 1usize..=8, 2usize..=4).prop_flat_map(|(rows, cols, colors)| {
+        let row = prop::collection::vec(0..colors, cols);
+        (prop::collection::vec(row, rows), Just(colors))
+    })
+}
+
+/// A clue of up to five groups in 1-3 colors, some blotted or bounded.
+fn any_clue() -> impl Strategy<Value = Vec<Group>> {
+    let len = prop_oneof![
+        4 => (1usize..=5).prop_map(GroupLen::Exact),
+        1 => Just(GroupLen::Unknown),
+        1 => (1usize..=3, 0usize..=2).prop_map(|(min, extra)| GroupLen::Range(min, min + extra)),
+    ];
+    prop::collection::vec(
+        (len, 1usize..=3).prop_map(|(len, color_id)| Group { len, color_id }),
+        0..=5,
+    )
+}
+
+proptest! {
+    /// Debug builds check every line of 20 cells or fewer against a
+    /// brute-force enumeration, so feeding `solve_line` arbitrary lines is
+    /// the test; the assertion here only covers what the oracle does not.
+    #[test]
+    fn line_solver_matches_brute_force(
+        groups in any_clue(),
+        cells in prop::collection::vec(1u64..16, 0..=20),
+    ) {
+        let mut solved = cells.clone();
+        if solve_line(&groups, &mut solved) {
+            for (after, before) in solved.iter().zip(&cells) {
+                prop_assert_eq!(after & !before, 0);
+            }
+        } else {
+            prop_assert_eq!(solved, cells);
+        }
+    }
+
+    #[test]
+    fn never_rules_out_the_true_solution((grid, colors) in solved_grid()) {
+        let steps = solve_puzzle_steps(puzzle_from_grid(&grid, colors)).unwrap();
+        let last = steps.steps.last().unwrap();
+        for (row, line) in grid.iter().enumerate() {
+            for (col, &color) in line.iter().enumerate() {
+                prop_assert!(last[row][col] & (1 << color) != 0, "cell ({row}, {col})");
+            }
+        }
+    }
+
+    #[test]
+    fn blotted_clues_keep_the_true_solution((grid, colors) in solved_grid(), blot in any::<u64>()) {
+        let mut puzzle = puzzle_from_grid(&grid, colors);
+        for (idx, group) in puzzle.row_groups.iter_mut().flatten().enumerate() {
+            if blot >> (idx % 64) & 1 == 1 {
+                group.len = GroupLen::Unknown;
+            }
+        }
+        let steps = solve_puzzle_steps(puzzle).unwrap();
+        let last = steps.steps.last().unwrap();
+        for (row, line) in grid.iter().enumerate() {
+            for (col, &color) in line.iter().enumerate() {
+                prop_assert!(last[row][col] & (1 << color) != 0, "cell ({row}, {col})");
+            }
+        }
+    }
+
+    #[test]
+    fn solved_grids_match_the_clues((grid, colors) in solved_grid()) {
+        let puzzle = puzzle_from_grid(&grid, colors);
+        let steps = solve_puzzle_steps(puzzle.clone()).unwrap();
+        prop_assume!(steps.stats.solved);
+        let found: ColorGrid = steps
+            .steps
+            .last()
+            .unwrap()
+            .iter()
+            .map(|row| row.iter().map(|&mask| mask_to_color_index(mask).unwrap()).collect())
+            .collect();
+        prop_assert_eq!(puzzle_from_grid(&found, colors), puzzle);
+    }
+
+    #[test]
+    fn masks_only_shrink_between_steps((grid, colors) in solved_grid()) {
+        let steps = solve_puzzle_steps(puzzle_from_grid(&grid, colors)).unwrap();
+        for pair in steps.steps.windows(2) {
+            for (before, after) in pair[0].iter().flatten().zip(pair[1].iter().flatten()) {
+                prop_assert_eq!(after & !before, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_replay_to_the_solved_grid((grid, colors) in solved_grid(), use_symmetry in any::<bool>()) {
+        let puzzle = puzzle_from_grid(&grid, colors);
+        let options = SolverOptions { use_symmetry, ..SolverOptions::default() };
+        let (steps, proof) = solve_puzzle_steps_proved(puzzle.clone(), &options).unwrap();
+        let proved = verify_proof(&puzzle, &proof);
+        prop_assert_eq!(proved.as_ref(), Ok(steps.steps.last().unwrap()));
+    }
+}